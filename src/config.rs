@@ -1,13 +1,31 @@
 use anyhow::Result;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Local secrets file written by the `derive-api-key` subcommand (git-ignored)
+pub const SECRETS_PATH: &str = "config/secrets.toml";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub general: GeneralConfig,
     pub binance: BinanceConfig,
+    #[serde(default)]
+    pub coinbase: CoinbaseConfig,
+    #[serde(default)]
+    pub kraken: KrakenConfig,
+    #[serde(default)]
+    pub spot: SpotConfig,
+    #[serde(default)]
+    pub index: IndexConfig,
     pub polymarket: PolymarketConfig,
     pub trading: TradingConfig,
     pub signal: SignalConfig,
+    #[serde(default)]
+    pub auto_trade: AutoTradeConfig,
+    #[serde(default)]
+    pub settlement: SettlementConfig,
     pub logging: LoggingConfig,
 }
 
@@ -23,14 +41,77 @@ pub struct BinanceConfig {
     pub rest_url: String,
     pub symbol: String,
     pub snapshot_limit: u32,
+    // Trade stream subscribed to on the depth connection, e.g. btcusd@aggTrade (empty = off)
+    #[serde(default)]
+    pub trade_stream: String,
+    // Trades of at least this many BTC count as large prints (0 = off)
+    #[serde(default)]
+    pub large_trade_qty: f64,
+}
+
+/// Coinbase Advanced Trade level2 feed (see coinbase.rs)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CoinbaseConfig {
+    pub ws_url: String,
+    pub product_id: String,
+}
+
+/// Kraken v2 book feed (see kraken.rs)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct KrakenConfig {
+    pub ws_url: String,
+    pub symbol: String,
+    // Levels per side; Kraken only sends changes within this depth
+    pub depth: usize,
+    // Decimals Kraken uses for the pair, needed to verify its book checksums
+    pub price_precision: usize,
+    pub qty_precision: usize,
+}
+
+/// Which spot BTC books to run and which of them feeds the signals (see book.rs)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SpotConfig {
+    // binance, coinbase and/or kraken, in order of preference for the signal inputs
+    pub venues: Vec<String>,
+    // A venue whose book hasn't changed for this long gives way to the next one
+    pub stale_ms: i64,
+}
+
+/// Composite BTC index over the spot venues (see index.rs)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct IndexConfig {
+    // Relative weight per venue; venues not listed count 1.0, 0 leaves one out
+    pub weights: HashMap<String, f64>,
+    // With three or more venues, drop any mid this far from their median (0 = keep all)
+    pub max_deviation_bps: f64,
+    // Leave out a venue whose book hasn't changed for this long
+    pub stale_ms: i64,
+    // How often the index is sampled for its returns and volatility
+    pub sample_ms: u64,
+}
+
+impl SpotConfig {
+    pub fn enabled(&self, venue: &str) -> bool {
+        self.venues.iter().any(|v| v == venue)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PolymarketConfig {
     pub ws_url: String,
+    pub user_ws_url: String,
     pub rest_url: String,
     pub gamma_url: String,
+    pub rtds_url: String,
+    pub event_url: String,
     pub btc_15m_event_id: String,
+    // Taker fee rate assumed until the market's own rate has been fetched
+    #[serde(default)]
+    pub fee_rate_bps: u64,
     // API credentials (loaded from environment)
     #[serde(default)]
     pub api_key: String,
@@ -40,6 +121,11 @@ pub struct PolymarketConfig {
     pub passphrase: String,
     #[serde(default)]
     pub wallet_address: String,
+    // Order signing key (hex) and how orders are signed: 0 = EOA, 1 = proxy, 2 = safe
+    #[serde(default)]
+    pub private_key: String,
+    #[serde(default)]
+    pub signature_type: u8,
     // These are now fetched dynamically, kept for fallback
     #[serde(default)]
     pub yes_token_id: String,
@@ -55,8 +141,19 @@ pub struct TradingConfig {
     pub max_size: f64,
     pub max_price_yes: f64,
     pub max_price_no: f64,
+    // Exits never sell below these
+    pub min_price_yes: f64,
+    pub min_price_no: f64,
     pub max_spread: f64,
     pub stale_quote_threshold_ms: u64,
+    // Buys join the best bid as post-only orders instead of lifting the ask
+    pub post_only: bool,
+    // Cancel our resting orders this many seconds before the window's end_date (0 = only at rollover)
+    #[serde(default)]
+    pub cancel_before_close_secs: u64,
+    // Position ledger location; empty keeps positions in memory only
+    #[serde(default)]
+    pub positions_file: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -65,18 +162,72 @@ pub struct SignalConfig {
     pub binance_return_threshold_3s: f64,
     pub poly_lag_threshold_ms: u64,
     pub min_confidence: f64,
+    // Lower bound on per-second BTC volatility (USD) used by the fair-value model
+    #[serde(default)]
+    pub vol_floor_per_sec: f64,
+    // Strategies to run side by side, each with its own signal stream (see strategy.rs)
+    #[serde(default)]
+    pub strategies: Vec<String>,
+    #[serde(default)]
+    pub strategy_interval_ms: u64,
+    // A strategy repeating the same side publishes it again only after this long
+    #[serde(default)]
+    pub strategy_cooldown_ms: u64,
+    // fair_value: minimum after-fee edge per share
+    #[serde(default)]
+    pub fair_value_min_edge: f64,
+    // lag: minimum after-fee edge per share at the stale ask
+    #[serde(default)]
+    pub lag_min_edge: f64,
+    // momentum: minimum 10s Binance return
+    #[serde(default)]
+    pub momentum_threshold_10s: f64,
+}
+
+/// Automated execution of one strategy's signals (see auto_trader.rs)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AutoTradeConfig {
+    // Start with auto mode on (it can always be toggled from the TUI)
+    pub enabled: bool,
+    // Strategy whose signals are traded; must be listed in signal.strategies
+    pub strategy: String,
+    pub min_confidence: f64,
+    // Minimum time between automated entries
+    pub cooldown_secs: u64,
+    // Per window: entries, and shares held plus resting buys
+    pub max_trades_per_window: u32,
+    pub max_position_per_window: f64,
+    // No new entries this close to the window's end
+    pub min_remaining_secs: u64,
+}
+
+/// Window outcome log (see settlement.rs)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SettlementConfig {
+    // After each window ends, poll Gamma until its outcomePrices show the resolution
+    pub confirm_with_gamma: bool,
+    pub confirm_poll_secs: u64,
+    pub confirm_timeout_secs: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct LoggingConfig {
     pub log_dir: String,
     pub rotation: String,
+    // Record every raw feed frame to <frames_dir>/<date>/frames.jsonl.gz (see recorder.rs)
+    #[serde(default)]
+    pub record_frames: bool,
+    #[serde(default)]
+    pub frames_dir: String,
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
         let settings = config::Config::builder()
             .add_source(config::File::with_name("config/default"))
+            .add_source(config::File::from(Path::new(SECRETS_PATH)).required(false))
             .add_source(
                 config::Environment::with_prefix("POLY")
                     .separator("__")
@@ -89,6 +240,48 @@ impl Config {
     }
 }
 
+/// CLOB API credentials persisted to the secrets file, layered over default.toml by `Config::load`
+#[derive(Debug, Clone)]
+pub struct PolymarketSecrets {
+    pub api_key: String,
+    pub api_secret: String,
+    pub passphrase: String,
+    /// Only written for EOA wallets; proxy wallets keep their configured funder address
+    pub wallet_address: Option<String>,
+}
+
+impl PolymarketSecrets {
+    fn to_toml(&self) -> String {
+        let quote = |v: &str| format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut out = String::from("# Generated by `polymarket-monitor derive-api-key` - do not commit\n[polymarket]\n");
+        out.push_str(&format!("api_key = {}\n", quote(&self.api_key)));
+        out.push_str(&format!("api_secret = {}\n", quote(&self.api_secret)));
+        out.push_str(&format!("passphrase = {}\n", quote(&self.passphrase)));
+        if let Some(address) = &self.wallet_address {
+            out.push_str(&format!("wallet_address = {}\n", quote(address)));
+        }
+        out
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(path)?;
+        file.write_all(self.to_toml().as_bytes())?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,4 +292,29 @@ mod tests {
         let config = Config::load();
         assert!(config.is_ok(), "Config should load successfully");
     }
+
+    #[test]
+    fn test_secrets_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.toml");
+        let secrets = PolymarketSecrets {
+            api_key: "key-123".to_string(),
+            api_secret: "c2Vj\"cmV0".to_string(),
+            passphrase: "pass".to_string(),
+            wallet_address: Some("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string()),
+        };
+        secrets.write(&path).unwrap();
+
+        let loaded = config::Config::builder()
+            .add_source(config::File::from(path.as_path()))
+            .build()
+            .unwrap();
+        assert_eq!(loaded.get_string("polymarket.api_key").unwrap(), "key-123");
+        assert_eq!(loaded.get_string("polymarket.api_secret").unwrap(), "c2Vj\"cmV0");
+        assert_eq!(loaded.get_string("polymarket.passphrase").unwrap(), "pass");
+        assert_eq!(
+            loaded.get_string("polymarket.wallet_address").unwrap(),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+        );
+    }
 }
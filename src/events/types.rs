@@ -13,6 +13,33 @@ pub struct SnapshotEvent {
     pub binance_ret_10s: Option<f64>,
     pub binance_obi_top5: Option<f64>,
     pub binance_std_5m: Option<f64>,      // 5-minute price std dev
+    // Other spot venues, and the one the signals read from
+    #[serde(default)]
+    pub coinbase_mid: Option<f64>,
+    #[serde(default)]
+    pub kraken_mid: Option<f64>,
+    // Binance taker flow (trade stream)
+    #[serde(default)]
+    pub binance_flow_1s: Option<FlowStats>,
+    #[serde(default)]
+    pub binance_flow_3s: Option<FlowStats>,
+    #[serde(default)]
+    pub binance_flow_10s: Option<FlowStats>,
+    #[serde(default)]
+    pub spot_venue: Option<String>,
+    // Composite index over the spot venues, and the venues in it
+    #[serde(default)]
+    pub index_price: Option<f64>,
+    #[serde(default)]
+    pub index_ret_1s: Option<f64>,
+    #[serde(default)]
+    pub index_ret_3s: Option<f64>,
+    #[serde(default)]
+    pub index_ret_10s: Option<f64>,
+    #[serde(default)]
+    pub index_std_5m: Option<f64>,
+    #[serde(default)]
+    pub index_venues: Vec<String>,
     // Polymarket data
     pub poly_yes_bid: Option<f64>,
     pub poly_yes_ask: Option<f64>,
@@ -23,6 +50,10 @@ pub struct SnapshotEvent {
     pub poly_stale_ms: Option<i64>,
     pub poly_target_price: Option<f64>,   // BTC price at window start
     pub poly_remaining_secs: Option<i64>, // Seconds until window ends
+    #[serde(default)]
+    pub poly_condition_id: Option<String>,
+    #[serde(default)]
+    pub chainlink_price: Option<f64>,     // Settlement price feed
     // Signal
     pub signal_side: String,
     pub signal_score: f64,
@@ -40,6 +71,18 @@ impl Default for SnapshotEvent {
             binance_ret_10s: None,
             binance_obi_top5: None,
             binance_std_5m: None,
+            coinbase_mid: None,
+            kraken_mid: None,
+            binance_flow_1s: None,
+            binance_flow_3s: None,
+            binance_flow_10s: None,
+            spot_venue: None,
+            index_price: None,
+            index_ret_1s: None,
+            index_ret_3s: None,
+            index_ret_10s: None,
+            index_std_5m: None,
+            index_venues: Vec::new(),
             poly_yes_bid: None,
             poly_yes_ask: None,
             poly_no_bid: None,
@@ -49,6 +92,8 @@ impl Default for SnapshotEvent {
             poly_stale_ms: None,
             poly_target_price: None,
             poly_remaining_secs: None,
+            poly_condition_id: None,
+            chainlink_price: None,
             signal_side: "NONE".to_string(),
             signal_score: 0.0,
         }
@@ -61,6 +106,7 @@ pub struct TradeEvent {
     pub t_resp_ms: Option<i64>,
     pub client_order_id: String,
     pub side: String,
+    pub order_side: Option<String>, // "BUY" or "SELL"
     pub size: f64,
     pub limit_price: f64,
     pub post_only: bool,
@@ -70,10 +116,23 @@ pub struct TradeEvent {
     pub fills: Option<Vec<FillInfo>>,
 }
 
+impl TradeEvent {
+    /// The exchange (or the paper exchange, in dry run) took the order
+    pub fn accepted(&self) -> bool {
+        matches!(self.api_status.as_deref(), Some("success" | "dry_run_success"))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FillInfo {
     pub price: f64,
     pub size: f64,
+    pub trade_id: Option<String>,
+    pub order_id: Option<String>,
+    pub status: Option<String>, // MATCHED / MINED / CONFIRMED / FAILED
+    pub t_fill_ms: Option<i64>,
+    pub liquidity: Option<String>, // TAKER / MAKER
+    pub fee: Option<f64>,          // USDC, filled in when the fill is booked
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,16 +146,105 @@ pub struct HealthEvent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignalEvent {
     pub t_recv_ms: i64,
+    #[serde(default)]
+    pub strategy: String,
     pub suggested_side: String,
     pub confidence: f64,
     pub reasons: Vec<String>,
     pub binance_ret_1s: f64,
     pub binance_ret_3s: f64,
     pub poly_lag_ms: i64,
+    #[serde(default)]
+    pub features: SignalFeatures,
+}
+
+/// Taker flow over the last `window_ms`: buys count positive, sells negative
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowStats {
+    pub window_ms: i64,
+    pub trade_count: u32,
+    pub volume: f64,
+    pub signed_volume: f64,
+    pub large_prints: u32,
+    pub large_signed_volume: f64,
+    pub vwap: Option<f64>,
+}
+
+/// The market view a signal was generated from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignalFeatures {
+    pub condition_id: String,
+    pub binance_mid: Option<f64>,
+    pub binance_ret_10s: Option<f64>,
+    pub binance_std_5m: Option<f64>,
+    pub binance_obi_top5: Option<f64>,
+    #[serde(default)]
+    pub binance_flow_3s: Option<FlowStats>,
+    #[serde(default)]
+    pub index_price: Option<f64>,
+    #[serde(default)]
+    pub index_ret_10s: Option<f64>,
+    #[serde(default)]
+    pub index_std_5m: Option<f64>,
+    pub yes_bid: Option<f64>,
+    pub yes_ask: Option<f64>,
+    pub no_bid: Option<f64>,
+    pub no_ask: Option<f64>,
+    pub chainlink_price: Option<f64>,
+    pub target_price: Option<f64>,
+    pub remaining_secs: Option<i64>,
+}
+
+/// How a signal played out at one horizon (signal_scores.jsonl)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalScore {
+    pub t_signal_ms: i64,
+    pub t_score_ms: i64,
+    pub strategy: String,
+    pub suggested_side: String,
+    pub confidence: f64,
+    pub condition_id: String,
+    pub horizon: String, // "5s", "30s", "60s" or "settlement"
+    pub entry_price: Option<f64>, // mid of the suggested side when the signal fired
+    pub exit_price: Option<f64>, // mid at the horizon, or 1.0 / 0.0 at settlement
+    pub price_move: Option<f64>,
+    pub hit: Option<bool>, // None if the window ended before the horizon
+}
+
+/// How a window resolved (settlements.jsonl). Written at the window's end from the
+/// Chainlink close, and again when Gamma shows the market's official resolution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementEvent {
+    pub t_recv_ms: i64,
+    pub source: String, // "chainlink" or "gamma"
+    pub condition_id: String,
+    pub slug: String,
+    pub end_date: String,
+    pub target_price: Option<f64>,
+    pub close_price: Option<f64>,
+    pub winner: Option<String>, // "UP" or "DOWN"
+    pub yes_bid: Option<f64>,   // last quotes seen in the window
+    pub yes_ask: Option<f64>,
+    pub no_bid: Option<f64>,
+    pub no_ask: Option<f64>,
+    pub positions: Vec<SettledPosition>,
+    pub realized_pnl: f64,
+    pub agrees: Option<bool>, // gamma records: whether it matches the Chainlink settlement
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettledPosition {
+    pub token_id: String,
+    pub side: String, // "YES" or "NO"
+    pub size: f64,    // held into the window's end
+    pub avg_cost: f64,
+    pub payout: Option<f64>, // 1.0 won, 0.0 lost
+    pub realized_pnl: f64,
+    pub fees_paid: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BinanceBookUpdate {
+pub struct BookUpdate {
     pub best_bid: Decimal,
     pub best_bid_qty: Decimal,
     pub best_ask: Decimal,
@@ -7,7 +7,7 @@ use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::events::{HealthEvent, SnapshotEvent, TradeEvent};
+use crate::events::{HealthEvent, SettlementEvent, SignalEvent, SignalScore, SnapshotEvent, TradeEvent};
 
 pub struct JsonlLogger {
     base_dir: PathBuf,
@@ -15,6 +15,9 @@ pub struct JsonlLogger {
     snapshot_writer: Mutex<Option<BufWriter<File>>>,
     trade_writer: Mutex<Option<BufWriter<File>>>,
     health_writer: Mutex<Option<BufWriter<File>>>,
+    signal_writer: Mutex<Option<BufWriter<File>>>,
+    settlement_writer: Mutex<Option<BufWriter<File>>>,
+    signal_score_writer: Mutex<Option<BufWriter<File>>>,
 }
 
 impl JsonlLogger {
@@ -29,6 +32,9 @@ impl JsonlLogger {
             snapshot_writer: Mutex::new(None),
             trade_writer: Mutex::new(None),
             health_writer: Mutex::new(None),
+            signal_writer: Mutex::new(None),
+            settlement_writer: Mutex::new(None),
+            signal_score_writer: Mutex::new(None),
         });
 
         logger.ensure_writers()?;
@@ -80,6 +86,30 @@ impl JsonlLogger {
                 .append(true)
                 .open(health_path)?;
             *self.health_writer.lock() = Some(BufWriter::new(health_file));
+
+            // Create/open signal file
+            let signal_path = date_dir.join("signals.jsonl");
+            let signal_file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(signal_path)?;
+            *self.signal_writer.lock() = Some(BufWriter::new(signal_file));
+
+            // Create/open settlement file
+            let settlement_path = date_dir.join("settlements.jsonl");
+            let settlement_file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(settlement_path)?;
+            *self.settlement_writer.lock() = Some(BufWriter::new(settlement_file));
+
+            // Create/open signal score file
+            let signal_score_path = date_dir.join("signal_scores.jsonl");
+            let signal_score_file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(signal_score_path)?;
+            *self.signal_score_writer.lock() = Some(BufWriter::new(signal_score_file));
         }
 
         Ok(())
@@ -107,6 +137,18 @@ impl JsonlLogger {
     pub fn log_health(&self, event: HealthEvent) -> Result<()> {
         self.write_json(&self.health_writer, &event)
     }
+
+    pub fn log_signal(&self, event: SignalEvent) -> Result<()> {
+        self.write_json(&self.signal_writer, &event)
+    }
+
+    pub fn log_settlement(&self, event: SettlementEvent) -> Result<()> {
+        self.write_json(&self.settlement_writer, &event)
+    }
+
+    pub fn log_signal_score(&self, score: SignalScore) -> Result<()> {
+        self.write_json(&self.signal_score_writer, &score)
+    }
 }
 
 #[cfg(test)]
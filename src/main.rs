@@ -7,15 +7,17 @@ mod logger;
 mod services;
 mod tui;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::time::{interval, Duration};
 
-use crate::config::Config;
+use crate::config::{Config, PolymarketSecrets, SpotConfig, SECRETS_PATH};
 use crate::events::{HealthEvent, SnapshotEvent};
 use crate::logger::JsonlLogger;
-use crate::services::{BinanceBookService, ChainlinkService, ClobClient, ClobCredentials, PolymarketService, SignalService, TradeService};
+use crate::services::{calibration, default_jobs, format_calibration, load_recordings, read_scores, run_backtest, run_sweep, score_files, ActiveMarket, AutoTrader, BacktestOptions, BinanceBookService, ChainlinkService, ClobClient, ClobCredentials, CoinbaseBookService, FrameRecorder, IndexService, KrakenBookService, Objective, ParamRange, PolymarketService, RecordedDay, ReplayEngine, ReplaySpeed, SearchMode, SettlementTracker, SignalScorer, SignalService, SpotBook, SpotFeeds, SweepOptions, TradeService, UserChannelService};
 use crate::tui::{App, TuiLogBuffer, TuiLogLayer};
 
 #[derive(Parser, Debug)]
@@ -34,9 +36,372 @@ struct Args {
     #[arg(long)]
     headless: bool,
 
+    /// Start with automated trading on (see [auto_trade] in the config)
+    #[arg(long)]
+    auto: bool,
+
+    /// Record raw feed frames (same as logging.record_frames)
+    #[arg(long)]
+    record: bool,
+
     /// Snapshot rate in Hz
     #[arg(long, default_value = "1")]
     snapshot_hz: u32,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Derive (or create) CLOB API credentials from the wallet private key and save them
+    DeriveApiKey {
+        /// Key nonce; each nonce maps to a separate API key for the wallet
+        #[arg(long, default_value = "0")]
+        nonce: u64,
+
+        /// Always create a new API key instead of deriving the existing one
+        #[arg(long)]
+        create: bool,
+
+        /// Where to write the credentials (read by Config::load)
+        #[arg(long, default_value = SECRETS_PATH)]
+        output: PathBuf,
+    },
+
+    /// Replay recorded snapshots through the signal and paper-trading logic
+    Replay {
+        /// First day to replay (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+
+        /// Last day to replay (defaults to --from)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Directory holding the recorded daily logs (defaults to logging.log_dir)
+        #[arg(long)]
+        log_dir: Option<PathBuf>,
+
+        /// max, realtime, or a speed-up factor such as 20x
+        #[arg(long, default_value = "max")]
+        speed: ReplaySpeed,
+
+        /// Where the replay's trades and signals are logged
+        #[arg(long, default_value = "data/replay")]
+        out: String,
+
+        /// Shares assumed at each recorded bid and ask
+        #[arg(long, default_value = "1000")]
+        book_depth: f64,
+
+        /// Only evaluate signals; don't auto-trade
+        #[arg(long)]
+        no_trade: bool,
+
+        /// Replay raw feed frames (logging.frames_dir) instead of snapshots
+        #[arg(long)]
+        frames: bool,
+    },
+
+    /// Calibration of logged signal scores: realized hit rate by confidence bucket
+    Calibrate {
+        /// First day (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+
+        /// Last day (defaults to --from)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Directory holding the daily logs (defaults to logging.log_dir)
+        #[arg(long)]
+        log_dir: Option<PathBuf>,
+
+        /// Only this strategy
+        #[arg(long)]
+        strategy: Option<String>,
+    },
+
+    /// Backtest one strategy over recorded days and report PnL per window
+    Backtest {
+        #[command(flatten)]
+        args: BacktestArgs,
+
+        /// Where the orders, fills and JSON report are written
+        #[arg(long, default_value = "data/backtest")]
+        out: String,
+    },
+
+    /// Backtest a strategy across a grid or random sample of signal thresholds and rank them
+    Sweep {
+        #[command(flatten)]
+        args: BacktestArgs,
+
+        /// Values to try, repeatable: name=a,b,c or name=lo:hi:step
+        #[arg(long = "param", required = true)]
+        params: Vec<ParamRange>,
+
+        /// Draw this many random parameter sets from the ranges instead of the full grid
+        #[arg(long)]
+        random: Option<usize>,
+
+        /// Seed for --random
+        #[arg(long, default_value = "1")]
+        seed: u64,
+
+        /// What to rank by
+        #[arg(long, value_enum, default_value = "pnl")]
+        objective: Objective,
+
+        /// Walk forward: pick the best set on this many days, then test it on the next --test-days
+        #[arg(long)]
+        train_days: Option<usize>,
+
+        #[arg(long, default_value = "1")]
+        test_days: usize,
+
+        /// Backtests to run in parallel (defaults to the number of cores)
+        #[arg(long)]
+        jobs: Option<usize>,
+
+        /// Rows of the ranking to print
+        #[arg(long, default_value = "20")]
+        top: usize,
+
+        /// Where each run's orders and the ranked table are written
+        #[arg(long, default_value = "data/sweep")]
+        out: String,
+    },
+}
+
+/// Where recordings live by default: raw frames under logging.frames_dir, snapshots under logging.log_dir
+fn recording_dir(config: &Config, frames: bool) -> PathBuf {
+    if frames {
+        PathBuf::from(&config.logging.frames_dir)
+    } else {
+        PathBuf::from(&config.logging.log_dir)
+    }
+}
+
+/// Replay recorded snapshots (or raw frames) for a range of days and print what happened
+#[allow(clippy::too_many_arguments)]
+async fn replay(
+    from: NaiveDate,
+    to: Option<NaiveDate>,
+    log_dir: Option<PathBuf>,
+    speed: ReplaySpeed,
+    out: String,
+    book_depth: f64,
+    no_trade: bool,
+    frames: bool,
+) -> Result<()> {
+    let config = Config::load()?;
+    let dir = log_dir.unwrap_or_else(|| recording_dir(&config, frames));
+    let days = load_recordings(&dir, from, to.unwrap_or(from), frames)?;
+
+    let mut engine = ReplayEngine::new(&config, &out, book_depth, !no_trade)?;
+    for day in &days {
+        tracing::info!("Replaying {} ({} records)", day.date, day.len());
+        engine.run_day(day, speed).await?;
+    }
+    println!("{}", engine.finish()?);
+    Ok(())
+}
+
+/// Print calibration tables from signal_scores.jsonl for a range of days
+fn calibrate(from: NaiveDate, to: Option<NaiveDate>, log_dir: Option<PathBuf>, strategy: Option<String>) -> Result<()> {
+    let config = Config::load()?;
+    let log_dir = log_dir.unwrap_or_else(|| PathBuf::from(&config.logging.log_dir));
+    let files = score_files(&log_dir, from, to.unwrap_or(from));
+    if files.is_empty() {
+        anyhow::bail!("No signal_scores.jsonl under {} for the requested days", log_dir.display());
+    }
+
+    let mut scores = Vec::new();
+    for file in &files {
+        scores.extend(read_scores(file)?);
+    }
+    if let Some(strategy) = &strategy {
+        scores.retain(|s| &s.strategy == strategy);
+    }
+    println!("{} scores from {} days", scores.len(), files.len());
+    print!("{}", format_calibration(&calibration(&scores)));
+    Ok(())
+}
+
+/// Strategy, data and execution assumptions shared by `backtest` and `sweep`
+#[derive(clap::Args, Debug)]
+struct BacktestArgs {
+    /// Strategy to trade (lag, fair_value, momentum)
+    #[arg(long)]
+    strategy: String,
+
+    /// First day (YYYY-MM-DD)
+    #[arg(long)]
+    from: NaiveDate,
+
+    /// Last day (defaults to --from)
+    #[arg(long)]
+    to: Option<NaiveDate>,
+
+    /// Directory holding the recordings (defaults to logging.log_dir, or logging.frames_dir with --frames)
+    #[arg(long)]
+    log_dir: Option<PathBuf>,
+
+    /// Use raw feed frames, with full recorded books, instead of snapshots
+    #[arg(long)]
+    frames: bool,
+
+    /// Time from signal to the order reaching the book
+    #[arg(long, default_value = "250")]
+    latency_ms: i64,
+
+    /// Taker fee rate to charge instead of the recorded / configured one
+    #[arg(long)]
+    fee_bps: Option<u64>,
+
+    /// Shares per entry (defaults to trading.default_size)
+    #[arg(long)]
+    size: Option<f64>,
+
+    /// Shares assumed at each recorded bid and ask (snapshots only)
+    #[arg(long, default_value = "1000")]
+    book_depth: f64,
+}
+
+impl BacktestArgs {
+    fn to(&self) -> NaiveDate {
+        self.to.unwrap_or(self.from)
+    }
+
+    fn load(&self, config: &Config) -> Result<Vec<RecordedDay>> {
+        let dir = self.log_dir.clone().unwrap_or_else(|| recording_dir(config, self.frames));
+        load_recordings(&dir, self.from, self.to(), self.frames)
+    }
+
+    fn options(&self, out: String) -> BacktestOptions {
+        BacktestOptions {
+            strategy: self.strategy.clone(),
+            latency_ms: self.latency_ms,
+            fee_rate_bps: self.fee_bps,
+            size: self.size,
+            book_depth: self.book_depth,
+            out_dir: out,
+        }
+    }
+}
+
+/// Backtest a strategy and print the report; the JSON version is written under `out`
+async fn backtest(args: BacktestArgs, out: String) -> Result<()> {
+    let config = Config::load()?;
+    let days = args.load(&config)?;
+    let options = args.options(out);
+
+    let report = run_backtest(&config, &days, &options).await?;
+    println!("{}", report);
+
+    let path = PathBuf::from(&options.out_dir).join(format!("backtest_{}_{}_{}.json", options.strategy, args.from, args.to()));
+    std::fs::write(&path, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Report written to {}", path.display());
+    Ok(())
+}
+
+/// Sweep signal thresholds, print the best sets and write the full ranking as TSV under `out`
+#[allow(clippy::too_many_arguments)]
+async fn sweep(
+    args: BacktestArgs,
+    params: Vec<ParamRange>,
+    random: Option<usize>,
+    seed: u64,
+    objective: Objective,
+    train_days: Option<usize>,
+    test_days: usize,
+    jobs: Option<usize>,
+    top: usize,
+    out: String,
+) -> Result<()> {
+    let config = Config::load()?;
+    let days = args.load(&config)?;
+    let options = SweepOptions {
+        backtest: args.options(out.clone()),
+        ranges: params,
+        mode: match random {
+            Some(samples) => SearchMode::Random { samples, seed },
+            None => SearchMode::Grid,
+        },
+        objective,
+        train_days,
+        test_days,
+        jobs: jobs.unwrap_or_else(default_jobs),
+    };
+
+    // The backtests run on their own threads and runtimes
+    let result = tokio::task::spawn_blocking(move || run_sweep(&config, &days, &options)).await??;
+    print!("{}", result.format_table(top));
+
+    std::fs::create_dir_all(&out)?;
+    let path = PathBuf::from(&out).join(format!("sweep_{}_{}_{}.tsv", args.strategy, args.from, args.to()));
+    std::fs::write(&path, result.to_tsv()).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Ranking written to {}", path.display());
+    Ok(())
+}
+
+/// The `[spot]` venues in their configured order, for the signal service and TUI
+fn spot_feeds(
+    config: &SpotConfig,
+    binance: &Arc<BinanceBookService>,
+    coinbase: &Arc<CoinbaseBookService>,
+    kraken: &Arc<KrakenBookService>,
+) -> Result<SpotFeeds> {
+    let mut venues: Vec<Arc<dyn SpotBook>> = Vec::new();
+    for venue in &config.venues {
+        match venue.as_str() {
+            "binance" => venues.push(binance.clone()),
+            "coinbase" => venues.push(coinbase.clone()),
+            "kraken" => venues.push(kraken.clone()),
+            other => anyhow::bail!("Unknown spot venue '{}' (expected binance, coinbase or kraken)", other),
+        }
+    }
+    if venues.is_empty() {
+        anyhow::bail!("No spot venues configured under [spot]");
+    }
+    Ok(SpotFeeds::new(venues, config.stale_ms))
+}
+
+/// Run the L1 auth flow and write the resulting API credentials to the secrets file
+async fn derive_api_key(nonce: u64, create: bool, output: PathBuf) -> Result<()> {
+    let config = Config::load()?;
+    if config.polymarket.private_key.is_empty() {
+        anyhow::bail!("No private key configured - set POLY__POLYMARKET__PRIVATE_KEY");
+    }
+
+    let client = ClobClient::with_private_key(&config.polymarket.rest_url, &config.polymarket.private_key)?;
+    let address = client.signer_address().context("No signer address")?.to_string();
+    tracing::info!("Requesting CLOB API credentials for {} (nonce {})", address, nonce);
+
+    let creds = if create {
+        client.create_api_key(nonce).await?
+    } else {
+        client.create_or_derive_api_key(nonce).await?
+    };
+
+    let secrets = PolymarketSecrets {
+        api_key: creds.api_key.clone(),
+        api_secret: creds.secret,
+        passphrase: creds.passphrase,
+        wallet_address: if config.polymarket.signature_type == 0 { Some(address) } else { None },
+    };
+    secrets.write(&output)?;
+
+    tracing::info!(
+        "Saved API key {}...{} to {}",
+        &creds.api_key[..creds.api_key.len().min(8)],
+        &creds.api_key[creds.api_key.len().saturating_sub(4)..],
+        output.display()
+    );
+    Ok(())
 }
 
 #[tokio::main]
@@ -47,6 +412,28 @@ async fn main() -> Result<()> {
     let args = Args::parse();
     let dry_run = args.dry_run && !args.no_dry_run;
 
+    if let Some(command) = args.command {
+        tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
+            .with_env_filter(
+                tracing_subscriber::EnvFilter::from_default_env()
+                    .add_directive("polymarket_monitor=info".parse().unwrap()),
+            )
+            .init();
+
+        return match command {
+            Command::DeriveApiKey { nonce, create, output } => derive_api_key(nonce, create, output).await,
+            Command::Replay { from, to, log_dir, speed, out, book_depth, no_trade, frames } => {
+                replay(from, to, log_dir, speed, out, book_depth, no_trade, frames).await
+            }
+            Command::Calibrate { from, to, log_dir, strategy } => calibrate(from, to, log_dir, strategy),
+            Command::Backtest { args, out } => backtest(args, out).await,
+            Command::Sweep { args, params, random, seed, objective, train_days, test_days, jobs, top, out } => {
+                sweep(args, params, random, seed, objective, train_days, test_days, jobs, top, out).await
+            }
+        };
+    }
+
     // Initialize tracing: file logs + in-memory buffer for TUI display
     let log_buffer = TuiLogBuffer::new();
 
@@ -93,14 +480,28 @@ async fn main() -> Result<()> {
         component: "main".to_string(),
     })?;
 
+    // Raw frame recorder for tick-accurate replays
+    let recorder = if args.record || config.logging.record_frames {
+        let dir = if config.logging.frames_dir.is_empty() { "data/frames" } else { config.logging.frames_dir.as_str() };
+        tracing::info!("Recording raw feed frames under {}", dir);
+        Some(FrameRecorder::new(dir)?)
+    } else {
+        None
+    };
+
     // Create services
-    let binance = Arc::new(BinanceBookService::new(config.binance.clone()));
-    let polymarket = Arc::new(PolymarketService::new(config.polymarket.clone()));
-    let signal = Arc::new(SignalService::new(
-        config.signal.clone(),
-        binance.clone(),
-        polymarket.clone(),
-    ));
+    let binance = Arc::new(BinanceBookService::new(config.binance.clone()).with_recorder(recorder.clone()));
+    let polymarket = Arc::new(PolymarketService::new(config.polymarket.clone()).with_recorder(recorder.clone()));
+    // Create Chainlink service for accurate target price
+    let coinbase = Arc::new(CoinbaseBookService::new(config.coinbase.clone()));
+    let kraken = Arc::new(KrakenBookService::new(config.kraken.clone()));
+    let spot = Arc::new(spot_feeds(&config.spot, &binance, &coinbase, &kraken)?);
+    let chainlink = Arc::new(ChainlinkService::new(&config.polymarket.rtds_url).with_recorder(recorder.clone()));
+    let index = Arc::new(IndexService::new(config.index.clone(), spot.venues().to_vec()));
+    let signal = Arc::new(
+        SignalService::new(config.signal.clone(), spot.clone(), polymarket.clone(), chainlink.clone())?
+            .with_index(index.clone()),
+    );
     // Create CLOB credentials if available
     let clob_credentials = if !config.polymarket.api_key.is_empty()
         && !config.polymarket.api_secret.is_empty()
@@ -110,11 +511,16 @@ async fn main() -> Result<()> {
         tracing::info!("CLOB API credentials configured (wallet: {})", &config.polymarket.wallet_address);
         tracing::info!("  API key: {}...{}", &config.polymarket.api_key[..8], &config.polymarket.api_key[config.polymarket.api_key.len().saturating_sub(4)..]);
         tracing::info!("  Passphrase: {}...", &config.polymarket.passphrase[..8]);
+        if config.polymarket.private_key.is_empty() {
+            tracing::warn!("  Missing: private_key - orders cannot be signed");
+        }
         Some(ClobCredentials {
             api_key: config.polymarket.api_key.clone(),
             secret: config.polymarket.api_secret.clone(),
             passphrase: config.polymarket.passphrase.clone(),
             wallet_address: config.polymarket.wallet_address.clone(),
+            private_key: config.polymarket.private_key.clone(),
+            signature_type: config.polymarket.signature_type,
         })
     } else {
         tracing::warn!("Missing CLOB API credentials - live trading disabled");
@@ -128,13 +534,16 @@ async fn main() -> Result<()> {
     // Run a quick auth check before starting services
     if let Some(ref creds) = clob_credentials {
         tracing::info!("Running CLOB API auth check...");
-        let test_client = ClobClient::new(Some(creds.clone()));
+        let test_client = ClobClient::new(&config.polymarket.rest_url, Some(creds.clone()));
         match test_client.check_auth().await {
             Ok(body) => tracing::info!("Auth check PASSED: {}", &body[..body.len().min(200)]),
             Err(e) => tracing::error!("Auth check FAILED: {:?}", e),
         }
     }
 
+    // User channel: live order and fill updates for our API key
+    let user_channel_credentials = clob_credentials.clone();
+
     let trade = Arc::new(TradeService::new(
         config.trading.clone(),
         polymarket.clone(),
@@ -143,14 +552,117 @@ async fn main() -> Result<()> {
         dry_run,
     ));
 
-    // Create Chainlink service for accurate target price
-    let chainlink = Arc::new(ChainlinkService::new());
+    // Dry run: resting paper orders are re-matched on every book update
+    if dry_run {
+        let mut quote_rx = polymarket.subscribe();
+        let trade_clone = trade.clone();
+        tokio::spawn(async move {
+            loop {
+                match quote_rx.recv().await {
+                    Ok(_) => {
+                        if let Err(e) = trade_clone.match_paper_orders() {
+                            tracing::error!("Paper matching failed: {:?}", e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
 
-    // Start Binance service
-    let binance_clone = binance.clone();
+    // Fetch each new market's tick size and fee rate (the fee rate feeds risk checks and PnL)
+    let trade_params = trade.clone();
     tokio::spawn(async move {
-        if let Err(e) = binance_clone.start().await {
-            tracing::error!("Binance service error: {:?}", e);
+        let mut interval = interval(Duration::from_secs(5));
+        loop {
+            interval.tick().await;
+            if let Err(e) = trade_params.refresh_market_params().await {
+                tracing::warn!("Failed to fetch market params: {:?}", e);
+            }
+        }
+    });
+
+    // Pull resting orders before the window closes and after it rolls over
+    let trade_lifecycle = trade.clone();
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            trade_lifecycle.enforce_window_lifecycle().await;
+        }
+    });
+
+    // Pick up orders still resting from a previous run
+    if user_channel_credentials.is_some() && !dry_run {
+        match trade.refresh_open_orders().await {
+            Ok(n) => tracing::info!("Loaded {} open orders", n),
+            Err(e) => tracing::warn!("Failed to load open orders: {:?}", e),
+        }
+    }
+
+    // Fills and cancellations land in the trade service's order store and trade log
+    let user_channel = user_channel_credentials.map(|creds| {
+        Arc::new(UserChannelService::new(&config.polymarket.user_ws_url, creds, trade.order_store()))
+    });
+    if let Some(user_channel) = &user_channel {
+        let user_channel_clone = user_channel.clone();
+        tokio::spawn(async move {
+            if let Err(e) = user_channel_clone.start().await {
+                tracing::error!("User channel error: {:?}", e);
+            }
+        });
+
+        let mut user_rx = user_channel.subscribe();
+        let trade_clone = trade.clone();
+        tokio::spawn(async move {
+            loop {
+                match user_rx.recv().await {
+                    Ok(event) => {
+                        if let Err(e) = trade_clone.handle_user_event(&event) {
+                            tracing::error!("Failed to log user event: {:?}", e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!("User event consumer lagged by {} events", n);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    // Start the spot book services
+    if config.spot.enabled("binance") {
+        let binance_clone = binance.clone();
+        tokio::spawn(async move {
+            if let Err(e) = binance_clone.start().await {
+                tracing::error!("Binance service error: {:?}", e);
+            }
+        });
+    }
+    if config.spot.enabled("coinbase") {
+        let coinbase_clone = coinbase.clone();
+        tokio::spawn(async move {
+            if let Err(e) = coinbase_clone.start().await {
+                tracing::error!("Coinbase service error: {:?}", e);
+            }
+        });
+    }
+    if config.spot.enabled("kraken") {
+        let kraken_clone = kraken.clone();
+        tokio::spawn(async move {
+            if let Err(e) = kraken_clone.start().await {
+                tracing::error!("Kraken service error: {:?}", e);
+            }
+        });
+    }
+
+    // Start the composite index
+    let index_clone = index.clone();
+    tokio::spawn(async move {
+        if let Err(e) = index_clone.start().await {
+            tracing::error!("Index service error: {:?}", e);
         }
     });
 
@@ -170,16 +682,79 @@ async fn main() -> Result<()> {
         }
     });
 
+    // Run configured strategies side by side, each logged to signals.jsonl and scored
+    // against the market afterwards (signal_scores.jsonl)
+    let scorer = Arc::new(SignalScorer::new(polymarket.clone(), logger.clone()));
+    for name in signal.strategy_names() {
+        let Some(mut signal_rx) = signal.subscribe_strategy(&name) else { continue };
+        let logger_clone = logger.clone();
+        let scorer_clone = scorer.clone();
+        tokio::spawn(async move {
+            loop {
+                match signal_rx.recv().await {
+                    Ok(event) => {
+                        scorer_clone.on_signal(&event);
+                        if let Err(e) = logger_clone.log_signal(event) {
+                            tracing::error!("Failed to log {} signal: {:?}", name, e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+    tokio::spawn(signal.clone().run_strategies());
+    let scorer_tick = scorer.clone();
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_millis(250));
+        loop {
+            interval.tick().await;
+            scorer_tick.tick();
+        }
+    });
+
+    // Automated execution of one strategy's signals; idle until auto mode is switched on
+    let auto_trader = Arc::new(AutoTrader::new(config.auto_trade.clone(), trade.clone(), polymarket.clone()));
+    match signal.subscribe_strategy(auto_trader.strategy()) {
+        Some(signal_rx) => {
+            trade.attach_auto_trader();
+            trade.set_auto_trade(config.auto_trade.enabled || args.auto);
+            if trade.get_state().auto_trade_active {
+                tracing::warn!("AUTO TRADING ON: trading '{}' signals", auto_trader.strategy());
+            }
+            tokio::spawn(auto_trader.clone().run(signal_rx));
+        }
+        None if config.auto_trade.enabled || args.auto => {
+            anyhow::bail!(
+                "auto_trade.strategy '{}' is not one of signal.strategies",
+                config.auto_trade.strategy
+            );
+        }
+        None => {}
+    }
+
     // Start snapshot logging
     let snapshot_interval_ms = 1000 / args.snapshot_hz.max(1) as u64;
     let logger_clone = logger.clone();
     let binance_snapshot = binance.clone();
+    let coinbase_snapshot = coinbase.clone();
+    let kraken_snapshot = kraken.clone();
+    let spot_snapshot = spot.clone();
+    let index_snapshot = index.clone();
     let polymarket_snapshot = polymarket.clone();
     let signal_snapshot = signal.clone();
     let chainlink_snapshot = chainlink.clone();
+    let settlements = Arc::new(SettlementTracker::new(
+        config.settlement.clone(),
+        trade.clone(),
+        logger.clone(),
+        &config.polymarket.gamma_url,
+    ));
     tokio::spawn(async move {
         let mut interval = interval(Duration::from_millis(snapshot_interval_ms));
         let mut last_condition_id = String::new();
+        let mut last_market: Option<ActiveMarket> = None;
 
         loop {
             interval.tick().await;
@@ -193,6 +768,7 @@ async fn main() -> Result<()> {
             let ret_3s = binance_snapshot.get_returns(3000);
             let ret_10s = binance_snapshot.get_returns(10000);
             let std_5m = binance_snapshot.get_std_dev(300_000); // 5 minutes
+            let index_value = index_snapshot.get_value();
 
             // Get Chainlink price (this is what Polymarket uses for "Price to Beat")
             let chainlink_price = chainlink_snapshot.get_btc_price();
@@ -203,10 +779,31 @@ async fn main() -> Result<()> {
             let active_market = polymarket_snapshot.get_active_market();
             let remaining_secs = polymarket_snapshot.get_remaining_secs();
 
+            // Settle positions once the window is over: its target vs the last Chainlink print
+            // at or before its end, once a later print shows nothing earlier is still on the way
+            if remaining_secs == Some(0) {
+                let end_ms = active_market.end_ms();
+                if end_ms.is_some_and(|end| chainlink_snapshot.get_price_state().timestamp_ms > end) {
+                    let close = end_ms.and_then(|end| chainlink_snapshot.price_at(end));
+                    if let Some(settled) = settlements.settle_and_confirm(&active_market, close) {
+                        scorer.on_settlement(&settled.condition_id, settled.winner.as_deref().unwrap_or_default());
+                    }
+                }
+            } else if !active_market.condition_id.is_empty() {
+                settlements.observe(&active_market.condition_id, &poly_quotes);
+            }
+
             // Set target price when market changes OR when window start time has passed
             if !active_market.condition_id.is_empty() {
                 // Market changed - reset and try to fetch price to beat from page
                 if active_market.condition_id != last_condition_id {
+                    // Rolled over before we saw the previous window at zero - settle it now
+                    if let Some(prev) = last_market.take() {
+                        let close = prev.end_ms().and_then(|end| chainlink_snapshot.price_at(end));
+                        if let Some(settled) = settlements.settle_and_confirm(&prev, close) {
+                            scorer.on_settlement(&settled.condition_id, settled.winner.as_deref().unwrap_or_default());
+                        }
+                    }
                     last_condition_id = active_market.condition_id.clone();
                     // Clear old target price for new window
                     polymarket_snapshot.clear_target_price();
@@ -241,6 +838,10 @@ async fn main() -> Result<()> {
                 }
             }
 
+            if !active_market.condition_id.is_empty() {
+                last_market = Some(polymarket_snapshot.get_active_market());
+            }
+
             let snapshot = SnapshotEvent {
                 t_recv_ms: chrono::Utc::now().timestamp_millis(),
                 binance_mid: binance_update.as_ref().map(|u| u.mid.to_string().parse().unwrap_or(0.0)),
@@ -251,6 +852,18 @@ async fn main() -> Result<()> {
                 binance_ret_10s: ret_10s,
                 binance_obi_top5: binance_update.as_ref().map(|u| u.imbalance_top5),
                 binance_std_5m: std_5m,
+                binance_flow_1s: binance_snapshot.get_flow(1000),
+                binance_flow_3s: binance_snapshot.get_flow(3000),
+                binance_flow_10s: binance_snapshot.get_flow(10000),
+                coinbase_mid: coinbase_snapshot.get_mid_price(),
+                kraken_mid: kraken_snapshot.get_mid_price(),
+                spot_venue: Some(spot_snapshot.venue().to_string()),
+                index_price: index_value.as_ref().map(|v| v.price),
+                index_ret_1s: index_snapshot.get_returns(1000),
+                index_ret_3s: index_snapshot.get_returns(3000),
+                index_ret_10s: index_snapshot.get_returns(10000),
+                index_std_5m: index_snapshot.get_std_dev(300_000),
+                index_venues: index_value.map(|v| v.venues).unwrap_or_default(),
                 poly_yes_bid: poly_quotes.yes_bid,
                 poly_yes_ask: poly_quotes.yes_ask,
                 poly_no_bid: poly_quotes.no_bid,
@@ -266,6 +879,8 @@ async fn main() -> Result<()> {
                 poly_stale_ms: if poly_stale == i64::MAX { None } else { Some(poly_stale) },
                 poly_target_price: active_market.target_price,
                 poly_remaining_secs: remaining_secs,
+                poly_condition_id: Some(active_market.condition_id.clone()).filter(|c| !c.is_empty()),
+                chainlink_price,
                 signal_side: sig.suggested_side.map(|s| s.to_string()).unwrap_or("NONE".to_string()),
                 signal_score: sig.confidence,
             };
@@ -282,14 +897,23 @@ async fn main() -> Result<()> {
         tokio::signal::ctrl_c().await?;
     } else {
         // Run TUI
-        let mut app = App::new(binance.clone(), polymarket.clone(), chainlink.clone(), signal.clone(), trade.clone(), log_buffer.clone(), dry_run);
+        let mut app = App::new(spot.clone(), polymarket.clone(), chainlink.clone(), signal.clone(), trade.clone(), log_buffer.clone(), dry_run);
         app.run().await?;
     }
 
     // Shutdown
     binance.stop();
+    coinbase.stop();
+    kraken.stop();
+    index.stop();
     polymarket.stop();
     chainlink.stop();
+    if let Some(user_channel) = &user_channel {
+        user_channel.stop();
+    }
+    if let Some(recorder) = &recorder {
+        recorder.finish()?;
+    }
 
     logger.log_health(HealthEvent {
         t_recv_ms: chrono::Utc::now().timestamp_millis(),
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::AutoTradeConfig;
+use crate::events::{SignalEvent, TradeEvent, TradeSide};
+use crate::services::{PolymarketService, TradeService};
+
+/// Why a signal was or wasn't traded
+#[derive(Debug, Clone, PartialEq)]
+pub enum AutoDecision {
+    Trade(TradeSide),
+    Skip(String),
+}
+
+/// Entries made in the current window
+#[derive(Debug, Default)]
+struct WindowState {
+    condition_id: String,
+    trades: u32,
+    last_attempt_ms: Option<i64>,
+}
+
+/// Places buys from one strategy's signals while auto mode is on. Every order still
+/// goes through `TradeService::place_order`, so the usual risk checks apply on top of
+/// the confidence threshold, cooldown and per-window caps here.
+pub struct AutoTrader {
+    config: AutoTradeConfig,
+    trade: Arc<TradeService>,
+    polymarket: Arc<PolymarketService>,
+    window: RwLock<WindowState>,
+}
+
+impl AutoTrader {
+    pub fn new(config: AutoTradeConfig, trade: Arc<TradeService>, polymarket: Arc<PolymarketService>) -> Self {
+        Self {
+            config,
+            trade,
+            polymarket,
+            window: RwLock::new(WindowState::default()),
+        }
+    }
+
+    pub fn strategy(&self) -> &str {
+        &self.config.strategy
+    }
+
+    /// Whether `event` should be traded now
+    pub fn decide(&self, event: &SignalEvent, now_ms: i64) -> AutoDecision {
+        let skip = |reason: String| AutoDecision::Skip(reason);
+        let state = self.trade.get_state();
+        if !state.auto_trade_active {
+            return skip("auto trading is off".to_string());
+        }
+
+        let side = match event.suggested_side.as_str() {
+            "YES" => TradeSide::Yes,
+            "NO" => TradeSide::No,
+            other => return skip(format!("no side ({})", other)),
+        };
+        if event.confidence < self.config.min_confidence {
+            return skip(format!(
+                "confidence {:.2} below {:.2}",
+                event.confidence, self.config.min_confidence
+            ));
+        }
+
+        let market = self.polymarket.get_active_market();
+        if market.condition_id.is_empty() {
+            return skip("no active market".to_string());
+        }
+        if let Some(remaining) = self.polymarket.get_remaining_secs() {
+            if remaining < self.config.min_remaining_secs as i64 {
+                return skip(format!("{}s left in window", remaining));
+            }
+        }
+
+        // Entries recorded against an earlier window don't count in this one
+        let (trades, last_attempt_ms) = {
+            let window = self.window.read();
+            if window.condition_id == market.condition_id {
+                (window.trades, window.last_attempt_ms)
+            } else {
+                (0, None)
+            }
+        };
+        if let Some(last) = last_attempt_ms {
+            let cooldown_ms = self.config.cooldown_secs as i64 * 1000;
+            if now_ms - last < cooldown_ms {
+                return skip(format!("cooling down ({}s left)", (cooldown_ms - (now_ms - last) + 999) / 1000));
+            }
+        }
+        if trades >= self.config.max_trades_per_window {
+            return skip(format!("{} trades already this window", trades));
+        }
+        let exposure = self.trade.window_exposure();
+        if exposure + state.current_size > self.config.max_position_per_window + 1e-9 {
+            return skip(format!(
+                "position {:.1} + {:.1} would exceed {:.1} per window",
+                exposure, state.current_size, self.config.max_position_per_window
+            ));
+        }
+
+        AutoDecision::Trade(side)
+    }
+
+    /// Start the cooldown from an order that went out, counting it if it was accepted
+    fn record_attempt(&self, condition_id: &str, now_ms: i64, accepted: bool) {
+        let mut window = self.window.write();
+        if window.condition_id != condition_id {
+            *window = WindowState {
+                condition_id: condition_id.to_string(),
+                ..Default::default()
+            };
+        }
+        window.last_attempt_ms = Some(now_ms);
+        if accepted {
+            window.trades += 1;
+        }
+    }
+
+    /// Trade `event` if it passes; returns the order event when one was placed
+    pub async fn on_signal(&self, event: &SignalEvent) -> Result<Option<TradeEvent>> {
+        let now_ms = self.polymarket.clock().now_ms();
+        let condition_id = self.polymarket.get_active_market().condition_id;
+        let side = match self.decide(event, now_ms) {
+            AutoDecision::Trade(side) => side,
+            AutoDecision::Skip(reason) => {
+                tracing::debug!("Auto: skipping {} {}: {}", event.strategy, event.suggested_side, reason);
+                return Ok(None);
+            }
+        };
+
+        tracing::info!(
+            "Auto: {} signal {} ({:.2}) → buying: {}",
+            event.strategy,
+            side,
+            event.confidence,
+            event.reasons.join("; ")
+        );
+        let trade_event = self.trade.place_order(side).await?;
+        self.record_attempt(&condition_id, now_ms, trade_event.accepted());
+        Ok(Some(trade_event))
+    }
+
+    /// Consume the strategy's signal stream until it closes
+    pub async fn run(self: Arc<Self>, mut signal_rx: broadcast::Receiver<SignalEvent>) {
+        loop {
+            match signal_rx.recv().await {
+                Ok(event) => {
+                    if let Err(e) = self.on_signal(&event).await {
+                        tracing::warn!("Auto order failed: {:?}", e);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("Auto trader lagged by {} signals", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::{book_msg, poly_config, trading_config};
+    use crate::services::ActiveMarket;
+    use tempfile::tempdir;
+
+    fn make_auto_config() -> AutoTradeConfig {
+        AutoTradeConfig {
+            enabled: false,
+            strategy: "lag".to_string(),
+            min_confidence: 0.7,
+            cooldown_secs: 30,
+            max_trades_per_window: 2,
+            max_position_per_window: 25.0,
+            min_remaining_secs: 0,
+        }
+    }
+
+    fn make_auto_trader(dir: &tempfile::TempDir) -> AutoTrader {
+        let logger = crate::logger::JsonlLogger::new(dir.path().to_str().unwrap()).unwrap();
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        poly.set_active_market(ActiveMarket {
+            up_token_id: "UP".to_string(),
+            down_token_id: "DOWN".to_string(),
+            condition_id: "0xcond".to_string(),
+            ..Default::default()
+        });
+        poly.handle_message(&book_msg((0.48, 0.50), (0.48, 0.50)));
+        let trade = Arc::new(TradeService::new(trading_config(), poly.clone(), None, logger, true));
+        trade.attach_auto_trader();
+        AutoTrader::new(make_auto_config(), trade, poly)
+    }
+
+    fn signal(side: &str, confidence: f64) -> SignalEvent {
+        SignalEvent {
+            t_recv_ms: 0,
+            strategy: "lag".to_string(),
+            suggested_side: side.to_string(),
+            confidence,
+            reasons: Vec::new(),
+            binance_ret_1s: 0.0,
+            binance_ret_3s: 0.0,
+            poly_lag_ms: 0,
+            features: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decide_gates() {
+        let dir = tempdir().unwrap();
+        let auto = make_auto_trader(&dir);
+        assert!(matches!(auto.decide(&signal("YES", 0.9), 0), AutoDecision::Skip(r) if r.contains("off")));
+
+        auto.trade.toggle_auto_trade();
+        assert!(matches!(auto.decide(&signal("YES", 0.5), 0), AutoDecision::Skip(r) if r.contains("confidence")));
+        // Deciding alone doesn't start the cooldown
+        assert_eq!(auto.decide(&signal("NO", 0.8), 0), AutoDecision::Trade(TradeSide::No));
+        assert_eq!(auto.decide(&signal("NO", 0.8), 0), AutoDecision::Trade(TradeSide::No));
+
+        // Cooldown runs from the last order sent
+        auto.on_signal(&signal("NO", 0.8)).await.unwrap().unwrap();
+        let now_ms = auto.polymarket.clock().now_ms();
+        assert!(matches!(auto.decide(&signal("YES", 0.9), now_ms + 10_000), AutoDecision::Skip(r) if r.contains("cooling down (20s")));
+        assert_eq!(auto.decide(&signal("YES", 0.9), now_ms + 30_000), AutoDecision::Trade(TradeSide::Yes));
+    }
+
+    #[tokio::test]
+    async fn test_window_caps() {
+        let dir = tempdir().unwrap();
+        let mut auto = make_auto_trader(&dir);
+        auto.config.cooldown_secs = 0;
+        auto.trade.set_auto_trade(true);
+
+        // 10 shares per entry against a 25-share cap: the third entry is refused
+        for _ in 0..2 {
+            let event = auto.on_signal(&signal("YES", 0.9)).await.unwrap().unwrap();
+            assert!(event.accepted());
+        }
+        assert!((auto.trade.window_exposure() - 20.0).abs() < 1e-9);
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        assert!(matches!(auto.decide(&signal("YES", 0.9), now_ms), AutoDecision::Skip(r) if r.contains("trades already")));
+
+        auto.config.max_trades_per_window = 5;
+        assert!(matches!(auto.decide(&signal("NO", 0.9), now_ms), AutoDecision::Skip(r) if r.contains("would exceed 25.0")));
+
+        // A new window starts the count over
+        auto.polymarket.set_active_market(ActiveMarket {
+            up_token_id: "UP2".to_string(),
+            down_token_id: "DOWN2".to_string(),
+            condition_id: "0xnext".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(auto.decide(&signal("NO", 0.9), now_ms), AutoDecision::Trade(TradeSide::No));
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::config::Config;
+use super::replay::{RecordedDay, ReplayEngine, ReplaySpeed, WindowResult};
+use super::strategy::STRATEGY_NAMES;
+
+/// Execution assumptions for a backtest
+#[derive(Debug, Clone)]
+pub struct BacktestOptions {
+    pub strategy: String,
+    // Time from signal to the order reaching the book
+    pub latency_ms: i64,
+    // Overrides the recorded / configured taker fee rate
+    pub fee_rate_bps: Option<u64>,
+    // Shares per entry (default trading.default_size)
+    pub size: Option<f64>,
+    // Shares assumed at each recorded bid and ask when replaying snapshots
+    pub book_depth: f64,
+    // Where the backtest's orders and fills are logged
+    pub out_dir: String,
+}
+
+/// Per-window results and the statistics over them
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub strategy: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub latency_ms: i64,
+    pub fee_rate_bps: Option<u64>,
+    pub windows: Vec<WindowResult>,
+    pub trades: u32,
+    pub traded_windows: usize,
+    pub win_rate: Option<f64>, // share of traded windows with positive PnL
+    pub total_pnl: f64,
+    pub fees_paid: f64,
+    pub max_drawdown: f64, // largest peak-to-trough fall of cumulative PnL
+    pub sharpe: Option<f64>, // mean / std of PnL per traded window, not annualized
+}
+
+impl BacktestReport {
+    pub fn new(strategy: &str, from: NaiveDate, to: NaiveDate, options: &BacktestOptions, windows: Vec<WindowResult>) -> Self {
+        let traded: Vec<f64> = windows.iter().filter(|w| w.trades > 0).map(|w| w.pnl).collect();
+        let wins = traded.iter().filter(|pnl| **pnl > 0.0).count();
+
+        let mut cumulative = 0.0_f64;
+        let mut peak = 0.0_f64;
+        let mut max_drawdown = 0.0_f64;
+        for window in &windows {
+            cumulative += window.pnl;
+            peak = peak.max(cumulative);
+            max_drawdown = max_drawdown.max(peak - cumulative);
+        }
+
+        Self {
+            strategy: strategy.to_string(),
+            from,
+            to,
+            latency_ms: options.latency_ms,
+            fee_rate_bps: options.fee_rate_bps,
+            trades: windows.iter().map(|w| w.trades).sum(),
+            traded_windows: traded.len(),
+            win_rate: (!traded.is_empty()).then(|| wins as f64 / traded.len() as f64),
+            total_pnl: windows.iter().fold(0.0, |sum, w| sum + w.pnl),
+            fees_paid: windows.iter().fold(0.0, |sum, w| sum + w.fees),
+            max_drawdown,
+            sharpe: sharpe(&traded),
+            windows,
+        }
+    }
+}
+
+/// Sample Sharpe ratio of `pnls`; None with fewer than two or no variance
+fn sharpe(pnls: &[f64]) -> Option<f64> {
+    if pnls.len() < 2 {
+        return None;
+    }
+    let n = pnls.len() as f64;
+    let mean = pnls.iter().sum::<f64>() / n;
+    let var = pnls.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (var > 1e-12).then(|| mean / var.sqrt())
+}
+
+impl std::fmt::Display for BacktestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Backtest {} {}..{} (latency {}ms, fee {})",
+            self.strategy,
+            self.from,
+            self.to,
+            self.latency_ms,
+            self.fee_rate_bps.map(|r| format!("{}bps", r)).unwrap_or("as recorded".to_string())
+        )?;
+        writeln!(f, "{:<17} {:>6} {:>8} {:>9} {:>7} {:>10}", "WINDOW END", "TRADES", "OUTCOME", "PNL", "FEES", "CUM PNL")?;
+        let mut cumulative = 0.0;
+        for window in self.windows.iter().filter(|w| w.trades > 0) {
+            cumulative += window.pnl;
+            let end = chrono::DateTime::from_timestamp_millis(window.end_ms)
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            writeln!(
+                f,
+                "{:<17} {:>6} {:>8} {:>+9.2} {:>7.2} {:>+10.2}",
+                end,
+                window.trades,
+                window.outcome.as_deref().unwrap_or("-"),
+                window.pnl,
+                window.fees,
+                cumulative
+            )?;
+        }
+        let fmt_opt = |v: Option<f64>, pct: bool| match v {
+            Some(v) if pct => format!("{:.1}%", v * 100.0),
+            Some(v) => format!("{:.2}", v),
+            None => "-".to_string(),
+        };
+        writeln!(
+            f,
+            "Trades {} in {}/{} windows, win rate {}",
+            self.trades,
+            self.traded_windows,
+            self.windows.len(),
+            fmt_opt(self.win_rate, true)
+        )?;
+        write!(
+            f,
+            "PnL {:+.2} (fees {:.2}), max drawdown {:.2}, Sharpe per window {}",
+            self.total_pnl,
+            self.fees_paid,
+            self.max_drawdown,
+            fmt_opt(self.sharpe, false)
+        )
+    }
+}
+
+/// Run `options.strategy` over `days` as the only auto-traded strategy, with paper fills
+/// against the recorded books, and report per window. Cooldowns and per-window caps come
+/// from `[auto_trade]`, risk limits from `[trading]`.
+pub async fn run_backtest(config: &Config, days: &[RecordedDay], options: &BacktestOptions) -> Result<BacktestReport> {
+    if !STRATEGY_NAMES.contains(&options.strategy.as_str()) {
+        bail!("Unknown strategy '{}' (expected one of {})", options.strategy, STRATEGY_NAMES.join(", "));
+    }
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        bail!("Nothing to backtest");
+    };
+
+    let mut config = config.clone();
+    config.signal.strategies = vec![options.strategy.clone()];
+    config.auto_trade.strategy = options.strategy.clone();
+    if let Some(size) = options.size {
+        config.trading.default_size = size;
+    }
+
+    let mut engine = ReplayEngine::new(&config, &options.out_dir, options.book_depth, true)?
+        .with_latency(options.latency_ms)
+        .with_fee_rate_bps(options.fee_rate_bps);
+    for day in days {
+        engine.run_day(day, ReplaySpeed::Max).await?;
+    }
+    let summary = engine.finish()?;
+    Ok(BacktestReport::new(&options.strategy, first.date, last.date, options, summary.window_results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::SnapshotEvent;
+    use crate::services::replay::Recording;
+    use crate::services::testing;
+
+    const T0: i64 = 1_700_000_000_000;
+
+    fn snapshot(t_offset_ms: i64, remaining: i64) -> SnapshotEvent {
+        SnapshotEvent {
+            t_recv_ms: T0 + t_offset_ms,
+            binance_mid: Some(100_000.0),
+            binance_ret_1s: Some(0.0),
+            binance_ret_3s: Some(0.0),
+            poly_yes_bid: Some(0.48),
+            poly_yes_ask: Some(0.50),
+            poly_no_bid: Some(0.48),
+            poly_no_ask: Some(0.50),
+            poly_stale_ms: Some(100),
+            poly_target_price: Some(100_000.0),
+            poly_remaining_secs: Some(remaining),
+            poly_condition_id: Some("0xa".to_string()),
+            chainlink_price: Some(100_000.0),
+            ..Default::default()
+        }
+    }
+
+    fn window(trades: u32, pnl: f64) -> WindowResult {
+        WindowResult {
+            condition_id: String::new(),
+            end_ms: 0,
+            trades,
+            outcome: Some("UP".to_string()),
+            pnl,
+            fees: 0.1 * trades as f64,
+        }
+    }
+
+    #[test]
+    fn test_report_stats() {
+        let options = BacktestOptions {
+            strategy: "lag".to_string(),
+            latency_ms: 0,
+            fee_rate_bps: None,
+            size: None,
+            book_depth: 1000.0,
+            out_dir: String::new(),
+        };
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let windows = vec![window(1, 5.0), window(0, 0.0), window(2, -8.0), window(1, 2.0), window(1, 4.0)];
+        let report = BacktestReport::new("lag", day, day, &options, windows);
+
+        assert_eq!((report.trades, report.traded_windows), (5, 4));
+        assert_eq!(report.win_rate, Some(0.75));
+        assert!((report.total_pnl - 3.0).abs() < 1e-9);
+        assert!((report.fees_paid - 0.5).abs() < 1e-9);
+        // Peak 5.0, trough -3.0
+        assert!((report.max_drawdown - 8.0).abs() < 1e-9);
+        // mean 0.75, sample variance 106.75 / 3
+        assert!((report.sharpe.unwrap() - 0.75 / (106.75_f64 / 3.0).sqrt()).abs() < 1e-9);
+
+        assert_eq!(sharpe(&[1.0]), None);
+        assert_eq!(sharpe(&[1.0, 1.0]), None);
+    }
+
+    #[tokio::test]
+    async fn test_resting_entry_is_not_a_trade() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = testing::config();
+        config.trading.post_only = true;
+        config.auto_trade.min_confidence = 0.5;
+        config.auto_trade.min_remaining_secs = 0;
+        config.trading.cancel_before_close_secs = 0;
+
+        // The lag strategy fires, but the post-only bid rests behind an ask that never moves
+        let mut jump = snapshot(1_000, 99);
+        jump.binance_ret_1s = Some(0.002);
+        jump.binance_ret_3s = Some(0.003);
+        jump.poly_stale_ms = Some(800);
+        let mut close = snapshot(100_000, 0);
+        close.chainlink_price = Some(100_050.0);
+        let day = RecordedDay {
+            date: NaiveDate::from_ymd_opt(2023, 11, 14).unwrap(),
+            recording: Recording::Snapshots(vec![snapshot(0, 100), jump, close]),
+        };
+        let options = BacktestOptions {
+            strategy: "lag".to_string(),
+            latency_ms: 0,
+            fee_rate_bps: None,
+            size: None,
+            book_depth: 1000.0,
+            out_dir: dir.path().to_str().unwrap().to_string(),
+        };
+
+        let report = run_backtest(&config, &[day], &options).await.unwrap();
+        assert_eq!(report.windows.len(), 1);
+        assert_eq!((report.trades, report.traded_windows), (0, 0));
+        assert_eq!(report.win_rate, None);
+        assert_eq!(report.total_pnl, 0.0);
+    }
+}
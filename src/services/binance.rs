@@ -1,16 +1,36 @@
 use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
-use parking_lot::RwLock;
-use rust_decimal::Decimal;
+use parking_lot::{Mutex, RwLock};
 use serde::Deserialize;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::broadcast;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 use crate::config::BinanceConfig;
-use crate::events::BinanceBookUpdate;
+use crate::events::FlowStats;
+use super::book::{BookState, OrderBook, SpotBook};
+use super::clock::Clock;
+use super::flow::TradeFlow;
+use super::recorder::{FrameRecorder, FrameSource};
+
+/// Diffs held while waiting for a snapshot (100ms stream: well over a minute)
+const MAX_PENDING_DIFFS: usize = 1000;
+
+/// Snapshots fetched in a row that don't connect to the stream before reconnecting
+const MAX_SNAPSHOT_ATTEMPTS: u32 = 5;
+
+/// Wait before the first refetch, doubling with each further attempt
+const SNAPSHOT_RETRY_MS: u64 = 250;
+
+/// Delay before fetching snapshot number `attempt` (0 = the first) in a row
+fn snapshot_backoff(attempt: u32) -> Duration {
+    match attempt {
+        0 => Duration::ZERO,
+        n => Duration::from_millis(SNAPSHOT_RETRY_MS << (n - 1).min(4)),
+    }
+}
 
 #[derive(Debug, Clone, Deserialize)]
 struct DepthSnapshot {
@@ -42,58 +62,47 @@ struct DepthDiff {
     asks: Vec<(String, String)>,
 }
 
-#[derive(Debug)]
-struct OrderBook {
-    bids: BTreeMap<Decimal, Decimal>, // price -> qty (descending by price)
-    asks: BTreeMap<Decimal, Decimal>, // price -> qty (ascending by price)
-    last_update_id: u64,
-    initialized: bool,
+// trade / aggTrade stream; m = the buyer was the maker, i.e. the taker sold
+#[derive(Debug, Clone, Deserialize)]
+struct TradeMsg {
+    #[serde(rename = "e")]
+    event_type: String,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    qty: String,
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+    // Trade time (ms); flow is timed by the trade, not by when it reached us
+    #[serde(rename = "T", default)]
+    trade_time: Option<i64>,
 }
 
+// Binance depth snapshot and diff handling
 impl OrderBook {
-    fn new() -> Self {
-        Self {
-            bids: BTreeMap::new(),
-            asks: BTreeMap::new(),
-            last_update_id: 0,
-            initialized: false,
-        }
-    }
-
     fn apply_snapshot(&mut self, snapshot: &DepthSnapshot) -> Result<()> {
         self.bids.clear();
         self.asks.clear();
 
-        for (price_str, qty_str) in &snapshot.bids {
-            let price: Decimal = price_str.parse()?;
-            let qty: Decimal = qty_str.parse()?;
-            if qty > Decimal::ZERO {
-                self.bids.insert(price, qty);
-            }
-        }
-
-        for (price_str, qty_str) in &snapshot.asks {
-            let price: Decimal = price_str.parse()?;
-            let qty: Decimal = qty_str.parse()?;
-            if qty > Decimal::ZERO {
-                self.asks.insert(price, qty);
-            }
-        }
+        self.apply_levels(&snapshot.bids, &snapshot.asks)?;
 
         self.last_update_id = snapshot.last_update_id;
         self.initialized = true;
         Ok(())
     }
 
-    fn apply_diff(&mut self, diff: &DepthDiff) -> Result<bool> {
+    /// Apply a diff if it continues the book. `bridging` is the first diff after a snapshot,
+    /// which has to span the snapshot's id on both APIs.
+    fn apply_diff(&mut self, diff: &DepthDiff, bridging: bool) -> Result<bool> {
         if !self.initialized {
             return Ok(false);
         }
 
         // Check sequence - different logic for spot vs futures
         // For spot API: first_update_id <= last_update_id + 1 AND final_update_id >= last_update_id + 1
-        // For futures API: prev_final_update_id == last_update_id
-        if let Some(pu) = diff.prev_final_update_id {
+        // For futures API: prev_final_update_id == last_update_id, except the first diff after
+        // a snapshot, which only has to span it (the snapshot's id isn't a diff's u)
+        if let Some(pu) = diff.prev_final_update_id.filter(|_| !bridging) {
             // Futures API sequence check
             if pu != self.last_update_id {
                 return Ok(false);
@@ -110,179 +119,76 @@ impl OrderBook {
             }
         }
 
-        for (price_str, qty_str) in &diff.bids {
-            let price: Decimal = price_str.parse()?;
-            let qty: Decimal = qty_str.parse()?;
-            if qty == Decimal::ZERO {
-                self.bids.remove(&price);
-            } else {
-                self.bids.insert(price, qty);
-            }
-        }
-
-        for (price_str, qty_str) in &diff.asks {
-            let price: Decimal = price_str.parse()?;
-            let qty: Decimal = qty_str.parse()?;
-            if qty == Decimal::ZERO {
-                self.asks.remove(&price);
-            } else {
-                self.asks.insert(price, qty);
-            }
-        }
-
+        self.apply_levels(&diff.bids, &diff.asks)?;
         self.last_update_id = diff.final_update_id;
         Ok(true)
     }
 
-    fn best_bid(&self) -> Option<(Decimal, Decimal)> {
-        self.bids.iter().next_back().map(|(p, q)| (*p, *q))
-    }
-
-    fn best_ask(&self) -> Option<(Decimal, Decimal)> {
-        self.asks.iter().next().map(|(p, q)| (*p, *q))
-    }
-
-    fn mid(&self) -> Option<Decimal> {
-        match (self.best_bid(), self.best_ask()) {
-            (Some((bid, _)), Some((ask, _))) => Some((bid + ask) / Decimal::from(2)),
-            _ => None,
+    fn apply_levels(&mut self, bids: &[(String, String)], asks: &[(String, String)]) -> Result<()> {
+        for (price_str, qty_str) in bids {
+            self.set_level(true, price_str.parse()?, qty_str.parse()?);
         }
-    }
-
-    fn imbalance_top_n(&self, n: usize) -> f64 {
-        let bid_sum: Decimal = self.bids.iter().rev().take(n).map(|(_, q)| *q).sum();
-        let ask_sum: Decimal = self.asks.iter().take(n).map(|(_, q)| *q).sum();
-
-        let total = bid_sum + ask_sum;
-        if total == Decimal::ZERO {
-            return 0.0;
+        for (price_str, qty_str) in asks {
+            self.set_level(false, price_str.parse()?, qty_str.parse()?);
         }
-
-        let imbalance = (bid_sum - ask_sum) / total;
-        imbalance.to_string().parse().unwrap_or(0.0)
+        Ok(())
     }
 }
 
 pub struct BinanceBookService {
     config: BinanceConfig,
-    book: Arc<RwLock<OrderBook>>,
-    mid_history: Arc<RwLock<VecDeque<(i64, Decimal)>>>,
-    update_tx: broadcast::Sender<BinanceBookUpdate>,
+    state: BookState,
+    // Diffs received while there's no snapshot to apply them to
+    pending: Mutex<VecDeque<DepthDiff>>,
+    // lastUpdateId of the snapshot the book was built from
+    snapshot_id: AtomicU64,
+    flow: TradeFlow,
     running: Arc<RwLock<bool>>,
+    recorder: Option<Arc<FrameRecorder>>,
 }
 
 impl BinanceBookService {
     pub fn new(config: BinanceConfig) -> Self {
-        let (tx, _) = broadcast::channel(1000);
         Self {
+            flow: TradeFlow::new(config.large_trade_qty),
             config,
-            book: Arc::new(RwLock::new(OrderBook::new())),
-            mid_history: Arc::new(RwLock::new(VecDeque::with_capacity(1000))),
-            update_tx: tx,
+            state: BookState::new(),
+            pending: Mutex::new(VecDeque::new()),
+            snapshot_id: AtomicU64::new(0),
             running: Arc::new(RwLock::new(false)),
+            recorder: None,
         }
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<BinanceBookUpdate> {
-        self.update_tx.subscribe()
-    }
-
-    pub fn get_current_update(&self) -> Option<BinanceBookUpdate> {
-        let book = self.book.read();
-        if !book.initialized {
-            return None;
-        }
-
-        let (best_bid, best_bid_qty) = book.best_bid()?;
-        let (best_ask, best_ask_qty) = book.best_ask()?;
-        let mid = book.mid()?;
-        let imbalance = book.imbalance_top_n(5);
-
-        Some(BinanceBookUpdate {
-            best_bid,
-            best_bid_qty,
-            best_ask,
-            best_ask_qty,
-            mid,
-            imbalance_top5: imbalance,
-            update_id: book.last_update_id,
-            t_recv_ms: chrono::Utc::now().timestamp_millis(),
-        })
-    }
-
-    pub fn get_returns(&self, lookback_ms: i64) -> Option<f64> {
-        let history = self.mid_history.read();
-        if history.len() < 2 {
-            return None;
-        }
-
-        let now = chrono::Utc::now().timestamp_millis();
-        let cutoff = now - lookback_ms;
-
-        // Find the oldest price within lookback window
-        let old_price = history
-            .iter()
-            .find(|(ts, _)| *ts >= cutoff)
-            .map(|(_, p)| *p)?;
-
-        let current_price = history.back().map(|(_, p)| *p)?;
-
-        if old_price == Decimal::ZERO {
-            return None;
-        }
-
-        let ret = (current_price - old_price) / old_price;
-        ret.to_string().parse().ok()
+    /// Record raw depth frames and the REST snapshots the book is built from
+    pub fn with_recorder(mut self, recorder: Option<Arc<FrameRecorder>>) -> Self {
+        self.recorder = recorder;
+        self
     }
 
-    /// Calculate standard deviation of prices over the lookback period
-    pub fn get_std_dev(&self, lookback_ms: i64) -> Option<f64> {
-        let history = self.mid_history.read();
-        if history.len() < 2 {
-            return None;
-        }
-
-        let now = chrono::Utc::now().timestamp_millis();
-        let cutoff = now - lookback_ms;
-
-        // Collect prices within lookback window
-        let prices: Vec<f64> = history
-            .iter()
-            .filter(|(ts, _)| *ts >= cutoff)
-            .filter_map(|(_, p)| p.to_string().parse::<f64>().ok())
-            .collect();
-
-        if prices.len() < 2 {
-            return None;
+    fn record_frame(&self, text: &str) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(FrameSource::Binance, self.state.now_ms(), text);
         }
-
-        // Calculate mean
-        let mean: f64 = prices.iter().sum::<f64>() / prices.len() as f64;
-
-        // Calculate variance
-        let variance: f64 = prices
-            .iter()
-            .map(|p| (p - mean).powi(2))
-            .sum::<f64>()
-            / (prices.len() - 1) as f64; // Sample std dev
-
-        Some(variance.sqrt())
     }
 
-    /// Get the current mid price
-    pub fn get_mid_price(&self) -> Option<f64> {
-        let book = self.book.read();
-        book.mid().and_then(|m| m.to_string().parse().ok())
+    /// Use `clock` for update timestamps and the return/volatility windows (replays)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.state.set_clock(clock);
+        self
     }
 
-    async fn fetch_snapshot(&self) -> Result<DepthSnapshot> {
+    async fn fetch_snapshot(&self, delay: Duration) -> Result<DepthSnapshot> {
+        tokio::time::sleep(delay).await;
         let url = format!(
             "{}?symbol={}&limit={}",
             self.config.rest_url, self.config.symbol, self.config.snapshot_limit
         );
 
         let client = reqwest::Client::new();
-        let snapshot: DepthSnapshot = client.get(&url).send().await?.json().await?;
+        let text = client.get(&url).send().await?.text().await?;
+        self.record_frame(&text);
+        let snapshot: DepthSnapshot = serde_json::from_str(&text)?;
         Ok(snapshot)
     }
 
@@ -313,104 +219,186 @@ impl BinanceBookService {
 
         let (mut write, mut read) = ws_stream.split();
 
-        // Buffer messages while fetching snapshot (not currently used but kept for future diff buffering)
-        let _buffer: Vec<DepthDiff> = Vec::new();
-
-        // Fetch REST snapshot
-        let snapshot = self.fetch_snapshot().await?;
-        tracing::info!("Fetched Binance snapshot, lastUpdateId: {}", snapshot.last_update_id);
-
-        // Apply snapshot
-        {
-            let mut book = self.book.write();
-            book.apply_snapshot(&snapshot)?;
+        // Trades come over the same connection as the depth diffs
+        if !self.config.trade_stream.is_empty() {
+            let subscribe = serde_json::json!({
+                "method": "SUBSCRIBE",
+                "params": [self.config.trade_stream],
+                "id": 1,
+            });
+            write.send(Message::Text(subscribe.to_string())).await?;
         }
 
-        // Process buffered and incoming messages
-        let mut needs_resync = false;
+        // Diffs are buffered from here until a snapshot connects to them
+        self.state.reset();
+        self.pending.lock().clear();
 
-        while let Some(msg) = read.next().await {
-            if !*self.running.read() {
-                break;
-            }
+        // Keep reading the stream while a snapshot is in flight, so no diff is missed
+        let snapshot = self.fetch_snapshot(Duration::ZERO);
+        tokio::pin!(snapshot);
+        let mut fetching = true;
+        let mut attempts = 1;
 
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if let Ok(diff) = serde_json::from_str::<DepthDiff>(&text) {
-                        // Skip updates before our snapshot
-                        if diff.final_update_id <= snapshot.last_update_id {
-                            continue;
-                        }
+        loop {
+            tokio::select! {
+                result = &mut snapshot, if fetching => {
+                    let snapshot_data = result?;
+                    tracing::info!("Fetched Binance snapshot, lastUpdateId: {}", snapshot_data.last_update_id);
+                    // Older than the buffered diffs: try again, backing off
+                    fetching = !self.on_snapshot(&snapshot_data);
+                    if !fetching {
+                        attempts = 0;
+                    } else if attempts >= MAX_SNAPSHOT_ATTEMPTS {
+                        anyhow::bail!("No Binance snapshot connected to the stream in {} attempts", attempts);
+                    } else {
+                        snapshot.set(self.fetch_snapshot(snapshot_backoff(attempts)));
+                        attempts += 1;
+                    }
+                }
+                msg = read.next() => {
+                    let Some(msg) = msg else { break };
+                    if !*self.running.read() {
+                        break;
+                    }
 
-                        let mut book = self.book.write();
-                        match book.apply_diff(&diff) {
-                            Ok(true) => {
-                                // Update successful
-                                drop(book);
-                                self.record_mid();
-                                self.emit_update();
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            self.record_frame(&text);
+                            // A gap: resync from a new snapshot without dropping the stream
+                            if !self.handle_message(&text) && !fetching {
+                                fetching = true;
+                                snapshot.set(self.fetch_snapshot(snapshot_backoff(attempts)));
+                                attempts += 1;
                             }
-                            Ok(false) => {
-                                // Need resync
-                                tracing::warn!("Binance sequence gap, resyncing...");
-                                needs_resync = true;
-                                break;
-                            }
-                            Err(e) => {
-                                tracing::error!("Error applying diff: {:?}", e);
+                        }
+                        Ok(Message::Ping(data)) => {
+                            if let Err(e) = write.send(Message::Pong(data)).await {
+                                tracing::error!("Failed to send pong: {:?}", e);
                             }
                         }
+                        Ok(Message::Close(_)) => {
+                            tracing::warn!("Binance WebSocket closed");
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::error!("WebSocket error: {:?}", e);
+                            break;
+                        }
+                        _ => {}
                     }
                 }
-                Ok(Message::Ping(data)) => {
-                    if let Err(e) = write.send(Message::Pong(data)).await {
-                        tracing::error!("Failed to send pong: {:?}", e);
-                    }
-                }
-                Ok(Message::Close(_)) => {
-                    tracing::warn!("Binance WebSocket closed");
-                    break;
-                }
-                Err(e) => {
-                    tracing::error!("WebSocket error: {:?}", e);
-                    break;
-                }
-                _ => {}
             }
         }
 
-        if needs_resync {
-            // Clear book state
-            let mut book = self.book.write();
-            *book = OrderBook::new();
+        self.state.reset();
+        Ok(())
+    }
+
+    /// Apply one frame: a REST snapshot, a depth diff or a trade. Returns false when the
+    /// book needs a new snapshot (a sequence gap, or a snapshot older than the buffered
+    /// diffs); diffs are buffered until it arrives.
+    pub fn handle_message(&self, text: &str) -> bool {
+        if let Ok(diff) = serde_json::from_str::<DepthDiff>(text) {
+            self.on_diff(diff)
+        } else if let Ok(snapshot) = serde_json::from_str::<DepthSnapshot>(text) {
+            self.on_snapshot(&snapshot)
+        } else {
+            if let Ok(trade) = serde_json::from_str::<TradeMsg>(text) {
+                self.on_trade(&trade);
+            }
+            true
         }
+    }
 
-        Ok(())
+    fn on_trade(&self, trade: &TradeMsg) {
+        if trade.event_type != "trade" && trade.event_type != "aggTrade" {
+            return;
+        }
+        match (trade.price.parse::<f64>(), trade.qty.parse::<f64>()) {
+            (Ok(price), Ok(qty)) => {
+                let recv_ms = self.state.now_ms();
+                let t_ms = trade.trade_time.unwrap_or(recv_ms);
+                self.flow.record(t_ms, recv_ms, price, qty, !trade.buyer_is_maker);
+            }
+            _ => tracing::error!("Bad Binance trade: {:?}", trade),
+        }
     }
 
-    fn record_mid(&self) {
-        let book = self.book.read();
-        if let Some(mid) = book.mid() {
-            let now = chrono::Utc::now().timestamp_millis();
-            let mut history = self.mid_history.write();
-            history.push_back((now, mid));
-
-            // Keep last 60 seconds of history
-            let cutoff = now - 60_000;
-            while let Some((ts, _)) = history.front() {
-                if *ts < cutoff {
-                    history.pop_front();
-                } else {
-                    break;
-                }
+    fn on_diff(&self, diff: DepthDiff) -> bool {
+        let mut book = self.state.write();
+        if !book.initialized {
+            let mut pending = self.pending.lock();
+            if pending.len() >= MAX_PENDING_DIFFS {
+                pending.pop_front();
             }
+            pending.push_back(diff);
+            return true;
         }
+        // From before our snapshot
+        if diff.final_update_id <= book.last_update_id {
+            return true;
+        }
+
+        let bridging = book.last_update_id == self.snapshot_id.load(Ordering::Relaxed);
+        match book.apply_diff(&diff, bridging) {
+            Ok(true) => {
+                drop(book);
+                self.state.updated();
+            }
+            Ok(false) => {
+                tracing::warn!(
+                    "Binance sequence gap (U={} after u={}), resyncing...",
+                    diff.first_update_id,
+                    book.last_update_id
+                );
+                *book = OrderBook::new();
+                *self.pending.lock() = VecDeque::from([diff]);
+                return false;
+            }
+            Err(e) => {
+                tracing::error!("Error applying diff: {:?}", e);
+            }
+        }
+        true
     }
 
-    fn emit_update(&self) {
-        if let Some(update) = self.get_current_update() {
-            let _ = self.update_tx.send(update);
+    /// Rebuild the book from a snapshot and replay the buffered diffs: drop those it already
+    /// covers, then the first must bridge it (U <= lastUpdateId + 1 <= u) and each following
+    /// one continue from the last
+    fn on_snapshot(&self, snapshot: &DepthSnapshot) -> bool {
+        let mut book = self.state.write();
+        if let Err(e) = book.apply_snapshot(snapshot) {
+            tracing::error!("Error applying snapshot: {:?}", e);
+            *book = OrderBook::new();
+            return false;
+        }
+        self.snapshot_id.store(snapshot.last_update_id, Ordering::Relaxed);
+
+        let mut pending = std::mem::take(&mut *self.pending.lock());
+        while let Some(diff) = pending.pop_front() {
+            if diff.final_update_id <= book.last_update_id {
+                continue;
+            }
+            let bridging = book.last_update_id == snapshot.last_update_id;
+            match book.apply_diff(&diff, bridging) {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::warn!(
+                        "Binance snapshot {} doesn't connect to buffered diff U={}, refetching...",
+                        book.last_update_id,
+                        diff.first_update_id
+                    );
+                    *book = OrderBook::new();
+                    pending.push_front(diff);
+                    *self.pending.lock() = pending;
+                    return false;
+                }
+                Err(e) => tracing::error!("Error applying diff: {:?}", e),
+            }
         }
+        drop(book);
+        self.state.updated();
+        true
     }
 
     pub fn stop(&self) {
@@ -418,9 +406,25 @@ impl BinanceBookService {
     }
 }
 
+impl SpotBook for BinanceBookService {
+    fn venue(&self) -> &str {
+        "binance"
+    }
+
+    fn state(&self) -> &BookState {
+        &self.state
+    }
+
+    fn get_flow(&self, window_ms: i64) -> Option<FlowStats> {
+        (!self.config.trade_stream.is_empty()).then(|| self.flow.stats(self.state.now_ms(), window_ms))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::testing;
+    use rust_decimal::Decimal;
 
     #[test]
     fn test_order_book_operations() {
@@ -472,4 +476,158 @@ mod tests {
         // (20 - 10) / 30 = 0.333...
         assert!((imbalance - 0.333).abs() < 0.01);
     }
+
+    fn diff(first: u64, last: u64, bid: &str) -> String {
+        format!(
+            r#"{{"e":"depthUpdate","E":0,"s":"BTCUSD","U":{},"u":{},"b":[["{}","1.0"]],"a":[]}}"#,
+            first, last, bid
+        )
+    }
+
+    fn snapshot(last_update_id: u64) -> String {
+        format!(
+            r#"{{"lastUpdateId":{},"bids":[["100.0","1.0"]],"asks":[["110.0","1.0"]]}}"#,
+            last_update_id
+        )
+    }
+
+    fn service() -> BinanceBookService {
+        BinanceBookService::new(testing::binance_config())
+    }
+
+    fn best_bid(service: &BinanceBookService) -> Option<f64> {
+        service.get_current_update().map(|u| u.best_bid.to_string().parse().unwrap())
+    }
+
+    #[test]
+    fn test_diffs_buffered_until_snapshot() {
+        let service = service();
+        // Arrive while the snapshot is being fetched
+        assert!(service.handle_message(&diff(90, 95, "101.0")));
+        assert!(service.handle_message(&diff(96, 102, "102.0")));
+        assert!(service.handle_message(&diff(103, 105, "103.0")));
+        assert_eq!(best_bid(&service), None);
+
+        // 90..95 is already in the snapshot, 96..102 bridges it, 103..105 follows
+        assert!(service.handle_message(&snapshot(100)));
+        assert_eq!(best_bid(&service), Some(103.0));
+        assert_eq!(service.get_current_update().unwrap().update_id, 105);
+        assert_eq!(service.state.read().bids.len(), 3);
+        assert!(service.pending.lock().is_empty());
+
+        assert!(service.handle_message(&diff(106, 107, "104.0")));
+        assert_eq!(best_bid(&service), Some(104.0));
+    }
+
+    #[test]
+    fn test_resync_on_gap_and_stale_snapshot() {
+        let service = service();
+        assert!(service.handle_message(&snapshot(100)));
+        assert!(service.handle_message(&diff(101, 101, "101.0")));
+        assert_eq!(best_bid(&service), Some(101.0));
+
+        // 102..109 never arrives
+        assert!(!service.handle_message(&diff(110, 112, "105.0")));
+        assert_eq!(best_bid(&service), None);
+        assert!(service.handle_message(&diff(113, 115, "106.0")));
+
+        // A snapshot from before the gap doesn't connect: still waiting
+        assert!(!service.handle_message(&snapshot(105)));
+        assert_eq!(best_bid(&service), None);
+        assert_eq!(service.pending.lock().len(), 2);
+
+        // A newer one does, and replays the rest
+        assert!(service.handle_message(&snapshot(111)));
+        assert_eq!(best_bid(&service), Some(106.0));
+        assert_eq!(service.get_current_update().unwrap().update_id, 115);
+        assert!(service.handle_message(&diff(116, 116, "107.0")));
+        assert_eq!(best_bid(&service), Some(107.0));
+    }
+
+    fn futures_diff(first: u64, last: u64, prev: u64, bid: &str) -> String {
+        format!(
+            r#"{{"e":"depthUpdate","E":0,"s":"BTCUSDT","U":{},"u":{},"pu":{},"b":[["{}","1.0"]],"a":[]}}"#,
+            first, last, prev, bid
+        )
+    }
+
+    #[test]
+    fn test_futures_diffs_bridge_then_chain_on_pu() {
+        let service = service();
+        assert!(service.handle_message(&futures_diff(90, 95, 89, "101.0")));
+        // Spans the snapshot's id, though its pu isn't it
+        assert!(service.handle_message(&futures_diff(96, 104, 95, "102.0")));
+        assert!(service.handle_message(&snapshot(100)));
+        assert_eq!(service.get_current_update().unwrap().update_id, 104);
+
+        // Later diffs follow pu, whatever their U
+        assert!(service.handle_message(&futures_diff(108, 110, 104, "103.0")));
+        assert_eq!(best_bid(&service), Some(103.0));
+        assert!(!service.handle_message(&futures_diff(115, 118, 112, "104.0")));
+        assert_eq!(best_bid(&service), None);
+
+        // The buffered diff is older than the new snapshot, so the next live one bridges it
+        assert!(service.handle_message(&snapshot(120)));
+        assert!(service.handle_message(&futures_diff(119, 123, 118, "105.0")));
+        assert_eq!(best_bid(&service), Some(105.0));
+        assert!(service.handle_message(&futures_diff(124, 124, 123, "106.0")));
+        assert_eq!(service.get_current_update().unwrap().update_id, 124);
+    }
+
+    #[test]
+    fn test_snapshot_backoff() {
+        let delays: Vec<u64> = (0..8).map(|n| snapshot_backoff(n).as_millis() as u64).collect();
+        assert_eq!(delays, vec![0, 250, 500, 1000, 2000, 4000, 4000, 4000]);
+    }
+
+    #[test]
+    fn test_trade_frames_feed_flow() {
+        let clock = Clock::simulated(5_000);
+        let service = BinanceBookService::new(BinanceConfig {
+            trade_stream: "btcusdt@aggTrade".to_string(),
+            ..testing::binance_config()
+        })
+        .with_clock(clock.clone());
+
+        // The subscription ack is neither book nor trade
+        assert!(service.handle_message(r#"{"result":null,"id":1}"#));
+        // m = true: the buyer was the maker, so the taker sold
+        assert!(service.handle_message(
+            r#"{"e":"aggTrade","E":1001,"s":"BTCUSDT","a":1,"p":"100000.0","q":"0.5","f":1,"l":1,"T":1000,"m":true,"M":true}"#
+        ));
+        assert!(service.handle_message(
+            r#"{"e":"trade","E":1501,"s":"BTCUSDT","t":2,"p":"100010.0","q":"0.2","T":1500,"m":false,"M":true}"#
+        ));
+        assert_eq!(best_bid(&service), None);
+
+        let flow = service.get_flow(10_000).unwrap();
+        assert_eq!(flow.trade_count, 2);
+        assert!((flow.volume - 0.7).abs() < 1e-12);
+        assert!((flow.signed_volume + 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_flow_window_ignores_clock_skew() {
+        let clock = Clock::simulated(5_000);
+        let service = BinanceBookService::new(BinanceConfig {
+            trade_stream: "btcusdt@trade".to_string(),
+            ..testing::binance_config()
+        })
+        .with_clock(clock.clone());
+        let trade = |t: i64| format!(r#"{{"e":"trade","s":"BTCUSDT","t":{},"p":"100000.0","q":"0.1","T":{},"m":false}}"#, t, t);
+
+        // Binance stamps them 1s and 1.5s, 3.5s behind our clock: both are still the last second
+        assert!(service.handle_message(&trade(1_000)));
+        assert!(service.handle_message(&trade(1_500)));
+        assert_eq!(service.get_flow(1_000).unwrap().trade_count, 2);
+
+        // 800ms later here is 2.3s on Binance's clock
+        clock.set_ms(5_800);
+        assert_eq!(service.get_flow(1_000).unwrap().trade_count, 1);
+        assert_eq!(service.get_flow(3_000).unwrap().trade_count, 2);
+
+        // Quiet stream: the window keeps moving without new trades
+        clock.set_ms(20_000);
+        assert_eq!(service.get_flow(10_000).unwrap().trade_count, 0);
+    }
 }
@@ -0,0 +1,374 @@
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::events::{BookUpdate, FlowStats};
+use super::clock::Clock;
+
+#[derive(Debug)]
+pub(super) struct OrderBook {
+    pub bids: BTreeMap<Decimal, Decimal>, // price -> qty (descending by price)
+    pub asks: BTreeMap<Decimal, Decimal>, // price -> qty (ascending by price)
+    pub last_update_id: u64,
+    pub initialized: bool,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_id: 0,
+            initialized: false,
+        }
+    }
+
+    /// Set one price level; a zero quantity removes it
+    pub fn set_level(&mut self, is_bid: bool, price: Decimal, qty: Decimal) {
+        let side = if is_bid { &mut self.bids } else { &mut self.asks };
+        if qty == Decimal::ZERO {
+            side.remove(&price);
+        } else {
+            side.insert(price, qty);
+        }
+    }
+
+    /// Keep only the best `depth` levels on each side
+    pub fn truncate(&mut self, depth: usize) {
+        while self.bids.len() > depth {
+            self.bids.pop_first();
+        }
+        while self.asks.len() > depth {
+            self.asks.pop_last();
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<(Decimal, Decimal)> {
+        self.bids.iter().next_back().map(|(p, q)| (*p, *q))
+    }
+
+    pub fn best_ask(&self) -> Option<(Decimal, Decimal)> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q))
+    }
+
+    pub fn mid(&self) -> Option<Decimal> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some((bid, _)), Some((ask, _))) => Some((bid + ask) / Decimal::from(2)),
+            _ => None,
+        }
+    }
+
+    pub fn imbalance_top_n(&self, n: usize) -> f64 {
+        let bid_sum: Decimal = self.bids.iter().rev().take(n).map(|(_, q)| *q).sum();
+        let ask_sum: Decimal = self.asks.iter().take(n).map(|(_, q)| *q).sum();
+
+        let total = bid_sum + ask_sum;
+        if total == Decimal::ZERO {
+            return 0.0;
+        }
+
+        let imbalance = (bid_sum - ask_sum) / total;
+        imbalance.to_string().parse().unwrap_or(0.0)
+    }
+}
+
+/// One venue's book with its recent mids and update broadcast. Each spot book service
+/// maintains one from its own feed; `SpotBook` reads it the same way for all of them.
+pub struct BookState {
+    book: RwLock<OrderBook>,
+    mid_history: RwLock<VecDeque<(i64, Decimal)>>,
+    update_tx: broadcast::Sender<BookUpdate>,
+    last_update_ms: AtomicI64,
+    clock: Clock,
+}
+
+impl BookState {
+    pub(super) fn new() -> Self {
+        let (tx, _) = broadcast::channel(1000);
+        Self {
+            book: RwLock::new(OrderBook::new()),
+            mid_history: RwLock::new(VecDeque::with_capacity(1000)),
+            update_tx: tx,
+            last_update_ms: AtomicI64::new(0),
+            clock: Clock::system(),
+        }
+    }
+
+    pub(super) fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
+    pub(super) fn now_ms(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    pub(super) fn read(&self) -> RwLockReadGuard<'_, OrderBook> {
+        self.book.read()
+    }
+
+    pub(super) fn write(&self) -> RwLockWriteGuard<'_, OrderBook> {
+        self.book.write()
+    }
+
+    /// Drop the book until the venue sends a new snapshot
+    pub(super) fn reset(&self) {
+        *self.book.write() = OrderBook::new();
+    }
+
+    /// Record the new mid and broadcast the update, after a diff has been applied
+    pub(super) fn updated(&self) {
+        let now = self.clock.now_ms();
+        self.last_update_ms.store(now, Ordering::Relaxed);
+        if let Some(mid) = self.book.read().mid() {
+            let mut history = self.mid_history.write();
+            history.push_back((now, mid));
+
+            // Keep last 60 seconds of history
+            let cutoff = now - 60_000;
+            while let Some((ts, _)) = history.front() {
+                if *ts < cutoff {
+                    history.pop_front();
+                } else {
+                    break;
+                }
+            }
+        }
+        if let Some(update) = self.current_update() {
+            let _ = self.update_tx.send(update);
+        }
+    }
+
+    fn current_update(&self) -> Option<BookUpdate> {
+        let book = self.book.read();
+        if !book.initialized {
+            return None;
+        }
+
+        let (best_bid, best_bid_qty) = book.best_bid()?;
+        let (best_ask, best_ask_qty) = book.best_ask()?;
+        let mid = book.mid()?;
+        let imbalance = book.imbalance_top_n(5);
+
+        Some(BookUpdate {
+            best_bid,
+            best_bid_qty,
+            best_ask,
+            best_ask_qty,
+            mid,
+            imbalance_top5: imbalance,
+            update_id: book.last_update_id,
+            t_recv_ms: self.clock.now_ms(),
+        })
+    }
+}
+
+/// A spot BTC order book: Binance, Coinbase or Kraken, or whichever of several is freshest
+pub trait SpotBook: Send + Sync {
+    fn venue(&self) -> &str;
+
+    fn state(&self) -> &BookState;
+
+    fn subscribe(&self) -> broadcast::Receiver<BookUpdate> {
+        self.state().update_tx.subscribe()
+    }
+
+    fn get_current_update(&self) -> Option<BookUpdate> {
+        self.state().current_update()
+    }
+
+    fn get_returns(&self, lookback_ms: i64) -> Option<f64> {
+        let state = self.state();
+        let history = state.mid_history.read();
+        if history.len() < 2 {
+            return None;
+        }
+
+        let now = state.clock.now_ms();
+        let cutoff = now - lookback_ms;
+
+        // Find the oldest price within lookback window
+        let old_price = history
+            .iter()
+            .find(|(ts, _)| *ts >= cutoff)
+            .map(|(_, p)| *p)?;
+
+        let current_price = history.back().map(|(_, p)| *p)?;
+
+        if old_price == Decimal::ZERO {
+            return None;
+        }
+
+        let ret = (current_price - old_price) / old_price;
+        ret.to_string().parse().ok()
+    }
+
+    /// Calculate standard deviation of prices over the lookback period
+    fn get_std_dev(&self, lookback_ms: i64) -> Option<f64> {
+        let state = self.state();
+        let history = state.mid_history.read();
+        if history.len() < 2 {
+            return None;
+        }
+
+        let now = state.clock.now_ms();
+        let cutoff = now - lookback_ms;
+
+        // Collect prices within lookback window
+        let prices: Vec<f64> = history
+            .iter()
+            .filter(|(ts, _)| *ts >= cutoff)
+            .filter_map(|(_, p)| p.to_string().parse::<f64>().ok())
+            .collect();
+
+        if prices.len() < 2 {
+            return None;
+        }
+
+        // Calculate mean
+        let mean: f64 = prices.iter().sum::<f64>() / prices.len() as f64;
+
+        // Calculate variance
+        let variance: f64 = prices
+            .iter()
+            .map(|p| (p - mean).powi(2))
+            .sum::<f64>()
+            / (prices.len() - 1) as f64; // Sample std dev
+
+        Some(variance.sqrt())
+    }
+
+    /// Get the current mid price
+    fn get_mid_price(&self) -> Option<f64> {
+        self.state().read().mid().and_then(|m| m.to_string().parse().ok())
+    }
+
+    /// Taker flow over the last `window_ms`, for venues whose trades are streamed
+    fn get_flow(&self, _window_ms: i64) -> Option<FlowStats> {
+        None
+    }
+
+    /// Milliseconds since the book last changed; None while it isn't built
+    fn age_ms(&self) -> Option<i64> {
+        let state = self.state();
+        if !state.read().initialized {
+            return None;
+        }
+        Some(state.clock.now_ms() - state.last_update_ms.load(Ordering::Relaxed))
+    }
+}
+
+/// The configured venues in order of preference, read through the first whose book
+/// has changed within `stale_ms`, so the signals keep running through one venue's outage
+pub struct SpotFeeds {
+    venues: Vec<Arc<dyn SpotBook>>,
+    stale_ms: i64,
+}
+
+impl SpotFeeds {
+    pub fn new(venues: Vec<Arc<dyn SpotBook>>, stale_ms: i64) -> Self {
+        assert!(!venues.is_empty(), "SpotFeeds needs at least one venue");
+        Self { venues, stale_ms }
+    }
+
+    pub fn venues(&self) -> &[Arc<dyn SpotBook>] {
+        &self.venues
+    }
+
+    fn leader(&self) -> &Arc<dyn SpotBook> {
+        self.venues
+            .iter()
+            .find(|v| v.age_ms().is_some_and(|age| age <= self.stale_ms))
+            .unwrap_or(&self.venues[0])
+    }
+}
+
+impl SpotBook for SpotFeeds {
+    fn venue(&self) -> &str {
+        self.leader().venue()
+    }
+
+    fn state(&self) -> &BookState {
+        self.leader().state()
+    }
+
+    fn get_flow(&self, window_ms: i64) -> Option<FlowStats> {
+        self.leader().get_flow(window_ms)
+    }
+}
+
+/// Spot book quoted by hand, for tests of what reads the venues
+#[cfg(test)]
+pub struct TestVenue(&'static str, BookState);
+
+#[cfg(test)]
+impl TestVenue {
+    /// An empty book timed by `clock`
+    pub fn new(name: &'static str, clock: &Clock) -> Arc<Self> {
+        let mut state = BookState::new();
+        state.set_clock(clock.clone());
+        Arc::new(Self(name, state))
+    }
+
+    /// Replace the book with one 1 BTC level either side of `mid`, $1 away
+    pub fn quote(&self, mid: f64) {
+        {
+            let mut book = self.1.write();
+            *book = OrderBook::new();
+            let mid = Decimal::try_from(mid).expect("finite mid");
+            book.set_level(true, mid - Decimal::ONE, Decimal::ONE);
+            book.set_level(false, mid + Decimal::ONE, Decimal::ONE);
+            book.initialized = true;
+        }
+        self.1.updated();
+    }
+}
+
+#[cfg(test)]
+impl SpotBook for TestVenue {
+    fn venue(&self) -> &str {
+        self.0
+    }
+
+    fn state(&self) -> &BookState {
+        &self.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn venue(name: &'static str, clock: &Clock, mid: f64) -> Arc<TestVenue> {
+        let venue = TestVenue::new(name, clock);
+        venue.quote(mid);
+        venue
+    }
+
+    #[test]
+    fn test_spot_feeds_fall_back_when_stale() {
+        let clock = Clock::simulated(1_000);
+        let binance = venue("binance", &clock, 100_000.0);
+        clock.set_ms(2_000);
+        let coinbase = venue("coinbase", &clock, 100_010.0);
+        let feeds = SpotFeeds::new(vec![binance.clone(), coinbase.clone()], 2_000);
+
+        assert_eq!(feeds.venue(), "binance");
+        assert_eq!(feeds.get_mid_price(), Some(100_000.0));
+
+        // Binance goes quiet, Coinbase keeps updating
+        clock.set_ms(3_500);
+        coinbase.state().updated();
+        assert_eq!(feeds.venue(), "coinbase");
+        assert_eq!(feeds.get_mid_price(), Some(100_010.0));
+
+        // Nothing fresh: stay on the first
+        clock.set_ms(10_000);
+        assert_eq!(feeds.venue(), "binance");
+
+        binance.state().reset();
+        assert_eq!(binance.age_ms(), None);
+    }
+}
@@ -2,11 +2,16 @@ use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
-const RTDS_WS_URL: &str = "wss://ws-live-data.polymarket.com";
+use super::clock::Clock;
+use super::recorder::{FrameRecorder, FrameSource};
+
+/// Keep prints this long, enough to settle the previous window after a late rollover
+const HISTORY_MS: i64 = 30 * 60 * 1000;
 
 #[derive(Debug, Clone, Serialize)]
 struct SubscribeMessage {
@@ -45,18 +50,38 @@ pub struct ChainlinkPriceState {
 }
 
 pub struct ChainlinkService {
+    ws_url: String,
     price_state: Arc<RwLock<ChainlinkPriceState>>,
+    history: RwLock<VecDeque<(i64, f64)>>, // (timestamp_ms, price), oldest first
     running: Arc<RwLock<bool>>,
+    clock: Clock,
+    recorder: Option<Arc<FrameRecorder>>,
 }
 
 impl ChainlinkService {
-    pub fn new() -> Self {
+    pub fn new(ws_url: &str) -> Self {
         Self {
+            ws_url: ws_url.to_string(),
             price_state: Arc::new(RwLock::new(ChainlinkPriceState::default())),
+            history: RwLock::new(VecDeque::new()),
             running: Arc::new(RwLock::new(false)),
+            clock: Clock::system(),
+            recorder: None,
         }
     }
 
+    /// Use `clock` for prices that arrive without a timestamp (replays)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record raw RTDS frames
+    pub fn with_recorder(mut self, recorder: Option<Arc<FrameRecorder>>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
     /// Get the current Chainlink BTC/USD price
     pub fn get_btc_price(&self) -> Option<f64> {
         self.price_state.read().btc_price
@@ -67,6 +92,14 @@ impl ChainlinkService {
         self.price_state.read().clone()
     }
 
+    /// The last price printed at or before `t_ms` (e.g. a window's close), if the
+    /// history reaches back that far
+    pub fn price_at(&self, t_ms: i64) -> Option<f64> {
+        let history = self.history.read();
+        let n = history.partition_point(|(t, _)| *t <= t_ms);
+        n.checked_sub(1).map(|i| history[i].1)
+    }
+
     pub async fn start(&self) -> Result<()> {
         *self.running.write() = true;
 
@@ -87,7 +120,7 @@ impl ChainlinkService {
     async fn run_connection(&self) -> Result<()> {
         tracing::info!("Connecting to Polymarket RTDS for Chainlink prices...");
 
-        let (ws_stream, _) = connect_async(RTDS_WS_URL)
+        let (ws_stream, _) = connect_async(&self.ws_url)
             .await
             .context("Failed to connect to RTDS WebSocket")?;
 
@@ -115,6 +148,9 @@ impl ChainlinkService {
                 msg = read.next() => {
                     match msg {
                         Some(Ok(Message::Text(text))) => {
+                            if let Some(recorder) = &self.recorder {
+                                recorder.record(FrameSource::Chainlink, self.clock.now_ms(), &text);
+                            }
                             self.handle_message(&text);
                         }
                         Some(Ok(Message::Ping(data))) => {
@@ -150,15 +186,23 @@ impl ChainlinkService {
         Ok(())
     }
 
-    fn handle_message(&self, text: &str) {
+    /// Apply one RTDS frame
+    pub fn handle_message(&self, text: &str) {
         if let Ok(msg) = serde_json::from_str::<RtdsMessage>(text) {
             if let Some(payload) = msg.payload {
                 if let Some(price) = payload.value {
+                    let timestamp_ms = payload.timestamp.unwrap_or_else(|| self.clock.now_ms());
                     let mut state = self.price_state.write();
                     state.btc_price = Some(price);
-                    state.timestamp_ms = payload.timestamp.unwrap_or_else(|| {
-                        chrono::Utc::now().timestamp_millis()
-                    });
+                    state.timestamp_ms = timestamp_ms;
+
+                    let mut history = self.history.write();
+                    let i = history.partition_point(|(t, _)| *t <= timestamp_ms);
+                    history.insert(i, (timestamp_ms, price));
+                    let latest = history.back().map_or(timestamp_ms, |(t, _)| *t);
+                    while history.front().is_some_and(|(t, _)| *t < latest - HISTORY_MS) {
+                        history.pop_front();
+                    }
 
                     tracing::debug!("Chainlink BTC/USD: ${:.2}", price);
                 }
@@ -171,8 +215,33 @@ impl ChainlinkService {
     }
 }
 
-impl Default for ChainlinkService {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn print(t_ms: i64, price: f64) -> String {
+        format!(
+            r#"{{"topic":"crypto_prices_chainlink","payload":{{"symbol":"btc/usd","timestamp":{},"value":{}}}}}"#,
+            t_ms, price
+        )
+    }
+
+    #[test]
+    fn test_price_at() {
+        let chainlink = ChainlinkService::new("wss://test");
+        chainlink.handle_message(&print(1_000, 100_000.0));
+        chainlink.handle_message(&print(3_000, 100_030.0));
+        // Arrives late but is filed in time order
+        chainlink.handle_message(&print(2_000, 100_020.0));
+
+        assert_eq!(chainlink.price_at(999), None);
+        assert_eq!(chainlink.price_at(1_000), Some(100_000.0));
+        assert_eq!(chainlink.price_at(2_500), Some(100_020.0));
+        assert_eq!(chainlink.price_at(10_000), Some(100_030.0));
+
+        // Prints older than the history are dropped
+        chainlink.handle_message(&print(HISTORY_MS + 2_500, 100_100.0));
+        assert_eq!(chainlink.price_at(2_500), None);
+        assert_eq!(chainlink.price_at(3_000), Some(100_030.0));
     }
 }
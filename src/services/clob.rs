@@ -1,9 +1,13 @@
 use anyhow::{Context, Result};
 use hmac::{Hmac, Mac};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use std::collections::HashMap;
 
-const CLOB_API_BASE: &str = "https://clob.polymarket.com";
+use super::orders::OrderState;
+use super::signing::{OrderSide, OrderSigner, SignatureType, SignedOrder, POLYGON_CHAIN_ID};
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -13,6 +17,10 @@ pub struct ClobCredentials {
     pub secret: String,
     pub passphrase: String,
     pub wallet_address: String,
+    /// Wallet private key used to sign orders (hex, optional 0x prefix)
+    pub private_key: String,
+    /// 0 = EOA, 1 = Polymarket proxy, 2 = Gnosis safe (wallet_address is then the funder)
+    pub signature_type: u8,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -26,11 +34,88 @@ pub struct OrderRequest {
     pub order_type: String, // "GTC", "FOK", "GTD"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expiration: Option<String>,
+    /// Reject instead of taking liquidity if the order would cross
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub post_only: bool,
+}
+
+/// Body for `POST /order`: the signed exchange order plus the API key that owns it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostOrderRequest {
+    order: SignedOrder,
+    owner: String,
+    order_type: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    post_only: bool,
+}
+
+/// Pagination cursors used by the CLOB data endpoints
+const FIRST_CURSOR: &str = "MA==";
+const END_CURSOR: &str = "LTE=";
+
+/// A resting order as returned by `GET /data/orders`
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenOrder {
+    pub id: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub market: String,
+    pub asset_id: String,
+    pub side: String,
+    pub original_size: String,
+    #[serde(default)]
+    pub size_matched: String,
+    pub price: String,
+    #[serde(default)]
+    pub outcome: String,
+    #[serde(default)]
+    pub order_type: String,
+    #[serde(default)]
+    pub created_at: i64, // unix seconds
+}
+
+impl OpenOrder {
+    pub fn to_order_state(&self) -> OrderState {
+        let parse = |s: &str| s.parse::<f64>().unwrap_or(0.0);
+        let mut order = OrderState::new(
+            &self.id,
+            &self.asset_id,
+            &self.side.to_uppercase(),
+            parse(&self.price),
+            parse(&self.original_size),
+        );
+        order.market = self.market.clone();
+        order.outcome = self.outcome.clone();
+        order.size_matched = parse(&self.size_matched);
+        if self.created_at > 0 {
+            order.created_ms = self.created_at * 1000;
+        }
+        order
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenOrdersPage {
+    #[serde(default)]
+    data: Vec<OpenOrder>,
+    next_cursor: Option<String>,
+}
+
+/// Result of any cancel endpoint
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CancelResponse {
+    #[serde(default)]
+    pub canceled: Vec<String>,
+    #[serde(default)]
+    pub not_canceled: HashMap<String, String>, // order id -> reason
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderResponse {
+    #[serde(alias = "orderID")]
     pub order_id: Option<String>,
     pub status: Option<String>,
     pub error_msg: Option<String>,
@@ -44,6 +129,15 @@ pub struct OrderResponse {
     pub raw_body: Option<String>,
 }
 
+/// API credentials returned by the L1 create/derive endpoints
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyCredentials {
+    pub api_key: String,
+    pub secret: String,
+    pub passphrase: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct OrderBookResponse {
     pub market: Option<String>,
@@ -63,19 +157,89 @@ pub struct MidpointResponse {
     pub mid: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct TickSizeResponse {
+    minimum_tick_size: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NegRiskResponse {
+    neg_risk: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FeeRateResponse {
+    #[serde(default)]
+    base_fee: u64,
+}
+
+/// Per-token parameters that affect how an order is built and signed
+#[derive(Debug, Clone, Copy)]
+pub struct MarketParams {
+    pub tick_size: Decimal,
+    pub neg_risk: bool,
+    pub fee_rate_bps: u64,
+}
+
 pub struct ClobClient {
     client: reqwest::Client,
+    base_url: String,
     credentials: Option<ClobCredentials>,
+    signer: Option<OrderSigner>,
+    market_params: RwLock<HashMap<String, MarketParams>>,
 }
 
 impl ClobClient {
-    pub fn new(credentials: Option<ClobCredentials>) -> Self {
+    pub fn new(base_url: &str, credentials: Option<ClobCredentials>) -> Self {
+        let signer = credentials
+            .as_ref()
+            .filter(|c| !c.private_key.is_empty())
+            .and_then(|c| match Self::build_signer(c) {
+                Ok(signer) => Some(signer),
+                Err(e) => {
+                    tracing::error!("Invalid order signing key: {:?}", e);
+                    None
+                }
+            });
+
         Self {
             client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
             credentials,
+            signer,
+            market_params: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Client holding only a wallet key, for L1 requests made before API credentials exist
+    pub fn with_private_key(base_url: &str, private_key: &str) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            credentials: None,
+            signer: Some(OrderSigner::from_private_key(private_key, POLYGON_CHAIN_ID)?),
+            market_params: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// EOA address of the order signing key, if one is configured
+    pub fn signer_address(&self) -> Option<&str> {
+        self.signer.as_ref().map(|s| s.address())
+    }
+
+    fn build_signer(creds: &ClobCredentials) -> Result<OrderSigner> {
+        let signature_type = SignatureType::try_from(creds.signature_type)?;
+        let signer = OrderSigner::from_private_key(&creds.private_key, POLYGON_CHAIN_ID)?
+            .with_funder(&creds.wallet_address, signature_type);
+        tracing::info!(
+            "Order signer: {} (signature type {}, maker {})",
+            signer.address(),
+            signature_type.as_u8(),
+            if signature_type == SignatureType::Eoa { signer.address() } else { &creds.wallet_address },
+        );
+        Ok(signer)
+    }
+
     /// Generate HMAC-SHA256 signature for a request
     fn sign_request(&self, timestamp: &str, method: &str, path: &str, body: &str) -> Result<String> {
         let creds = self.credentials.as_ref().context("No credentials configured")?;
@@ -144,6 +308,54 @@ impl ClobClient {
         Ok(builder)
     }
 
+    /// Add L1 (wallet signature) headers to a request
+    fn add_l1_headers(&self, builder: reqwest::RequestBuilder, nonce: u64) -> Result<reqwest::RequestBuilder> {
+        let signer = self.signer.as_ref().context("No private key configured")?;
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = signer.sign_clob_auth(timestamp, nonce)?;
+
+        Ok(builder
+            .header("POLY_ADDRESS", signer.address())
+            .header("POLY_SIGNATURE", signature)
+            .header("POLY_TIMESTAMP", timestamp.to_string())
+            .header("POLY_NONCE", nonce.to_string()))
+    }
+
+    async fn send_api_key_request(&self, builder: reqwest::RequestBuilder) -> Result<ApiKeyCredentials> {
+        let response = builder.send().await.context("Failed to send API key request")?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("API key request failed: {} - {}", status, text);
+        }
+        serde_json::from_str(&text).context("Failed to parse API key response")
+    }
+
+    /// Create a new API key for the wallet (L1 auth)
+    pub async fn create_api_key(&self, nonce: u64) -> Result<ApiKeyCredentials> {
+        let url = format!("{}/auth/api-key", self.base_url);
+        let builder = self.add_l1_headers(self.client.post(&url), nonce)?;
+        self.send_api_key_request(builder).await
+    }
+
+    /// Derive the API key previously created for the wallet and nonce (L1 auth)
+    pub async fn derive_api_key(&self, nonce: u64) -> Result<ApiKeyCredentials> {
+        let url = format!("{}/auth/derive-api-key", self.base_url);
+        let builder = self.add_l1_headers(self.client.get(&url), nonce)?;
+        self.send_api_key_request(builder).await
+    }
+
+    /// Derive the wallet's existing API key, creating one if none exists yet
+    pub async fn create_or_derive_api_key(&self, nonce: u64) -> Result<ApiKeyCredentials> {
+        match self.derive_api_key(nonce).await {
+            Ok(creds) => Ok(creds),
+            Err(e) => {
+                tracing::info!("No API key to derive ({}), creating a new one", e);
+                self.create_api_key(nonce).await
+            }
+        }
+    }
+
     /// Run a multi-step auth diagnostic at startup.
     pub async fn check_auth(&self) -> Result<String> {
         let creds = self.credentials.as_ref().context("No credentials configured")?;
@@ -152,7 +364,7 @@ impl ClobClient {
         // Step 1: Public endpoint — verify connectivity
         tracing::info!("[AUTH DIAG] Step 1: Testing connectivity (GET /time)...");
         let resp = self.client
-            .get(format!("{}/time", CLOB_API_BASE))
+            .get(format!("{}/time", self.base_url))
             .send()
             .await;
         match resp {
@@ -171,7 +383,7 @@ impl ClobClient {
         // Step 2: Authenticated endpoint — verify HMAC credentials
         tracing::info!("[AUTH DIAG] Step 2: Testing L2 auth (GET /api-keys)...");
         let path = "/api-keys";
-        let url = format!("{}{}", CLOB_API_BASE, path);
+        let url = format!("{}{}", self.base_url, path);
         let builder = self.client.get(&url);
         let builder = self.add_auth_headers(builder, "GET", path, "")?;
         let resp = builder.send().await;
@@ -195,7 +407,7 @@ impl ClobClient {
         // Step 3: Try GET /orders (another authenticated endpoint)
         tracing::info!("[AUTH DIAG] Step 3: Testing L2 auth (GET /orders)...");
         let path = "/orders";
-        let url = format!("{}{}", CLOB_API_BASE, path);
+        let url = format!("{}{}", self.base_url, path);
         let builder = self.client.get(&url);
         let builder = self.add_auth_headers(builder, "GET", path, "")?;
         let resp = builder.send().await;
@@ -236,7 +448,7 @@ impl ClobClient {
 
     /// Get the current order book for a token
     pub async fn get_order_book(&self, token_id: &str) -> Result<OrderBookResponse> {
-        let url = format!("{}/book?token_id={}", CLOB_API_BASE, token_id);
+        let url = format!("{}/book?token_id={}", self.base_url, token_id);
 
         let response = self.client
             .get(&url)
@@ -255,7 +467,7 @@ impl ClobClient {
 
     /// Get the midpoint price for a token
     pub async fn get_midpoint(&self, token_id: &str) -> Result<Option<f64>> {
-        let url = format!("{}/midpoint?token_id={}", CLOB_API_BASE, token_id);
+        let url = format!("{}/midpoint?token_id={}", self.base_url, token_id);
 
         let response = self.client
             .get(&url)
@@ -271,15 +483,103 @@ impl ClobClient {
         Ok(resp.mid.and_then(|m| m.parse().ok()))
     }
 
+    /// Market params if already fetched for `token_id`
+    pub fn cached_market_params(&self, token_id: &str) -> Option<MarketParams> {
+        self.market_params.read().get(token_id).copied()
+    }
+
+    /// Get tick size, neg-risk flag and taker fee rate for a token (cached per token)
+    pub async fn get_market_params(&self, token_id: &str) -> Result<MarketParams> {
+        if let Some(params) = self.market_params.read().get(token_id) {
+            return Ok(*params);
+        }
+
+        let url = format!("{}/tick-size?token_id={}", self.base_url, token_id);
+        let tick: TickSizeResponse = self.client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to fetch tick size")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse tick size response")?;
+        // Returned as a JSON number (0.01) or string; go through its text form to keep it exact
+        let tick_size: Decimal = tick.minimum_tick_size
+            .to_string()
+            .trim_matches('"')
+            .parse()
+            .context("Invalid tick size")?;
+
+        let url = format!("{}/neg-risk?token_id={}", self.base_url, token_id);
+        let neg: NegRiskResponse = self.client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to fetch neg-risk flag")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse neg-risk response")?;
+
+        // Orders must be signed with the market's fee rate
+        let url = format!("{}/fee-rate?token_id={}", self.base_url, token_id);
+        let fee: FeeRateResponse = self.client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to fetch fee rate")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse fee rate response")?;
+
+        let params = MarketParams { tick_size, neg_risk: neg.neg_risk, fee_rate_bps: fee.base_fee };
+        self.market_params.write().insert(token_id.to_string(), params);
+        Ok(params)
+    }
+
+    /// Build and sign the CTF Exchange order for a request
+    async fn sign_order_request(&self, order: &OrderRequest) -> Result<SignedOrder> {
+        let signer = self.signer.as_ref()
+            .context("Cannot place orders without a private key (POLY__POLYMARKET__PRIVATE_KEY)")?;
+
+        let side: OrderSide = order.side.parse()?;
+        let price: Decimal = order.price.parse().context("Invalid order price")?;
+        let size: Decimal = order.size.parse().context("Invalid order size")?;
+        let expiration: u64 = match &order.expiration {
+            Some(exp) => exp.parse().context("Invalid order expiration")?,
+            None => 0,
+        };
+
+        let params = self.get_market_params(&order.token_id).await?;
+        let exchange_order = signer.build_order(
+            &order.token_id,
+            side,
+            price,
+            size,
+            params.tick_size,
+            params.fee_rate_bps,
+            expiration,
+        )?;
+        signer.sign_order(&exchange_order, params.neg_risk)
+    }
+
     /// Place an order on Polymarket
     pub async fn place_order(&self, order: OrderRequest) -> Result<OrderResponse> {
-        if self.credentials.is_none() {
-            anyhow::bail!("Cannot place orders without API credentials");
-        }
+        let creds = self.credentials.as_ref().context("Cannot place orders without API credentials")?;
+
+        let signed = self.sign_order_request(&order).await?;
+        let payload = PostOrderRequest {
+            order: signed,
+            owner: creds.api_key.clone(),
+            order_type: order.order_type.clone(),
+            post_only: order.post_only,
+        };
 
         let path = "/order";
-        let url = format!("{}{}", CLOB_API_BASE, path);
-        let body = serde_json::to_string(&order)?;
+        let url = format!("{}{}", self.base_url, path);
+        let body = serde_json::to_string(&payload)?;
 
         tracing::info!(
             "Placing order: {} {} @ {} (size: {})",
@@ -329,15 +629,37 @@ impl ClobClient {
         Ok(resp)
     }
 
-    /// Cancel an order
-    pub async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+    /// Send an authenticated DELETE and parse the cancel result
+    async fn send_cancel(&self, path: &str, body: String) -> Result<CancelResponse> {
         if self.credentials.is_none() {
             anyhow::bail!("Cannot cancel orders without API credentials");
         }
 
-        let path = "/order";
-        let url = format!("{}{}", CLOB_API_BASE, path);
+        let url = format!("{}{}", self.base_url, path);
+        let mut builder = self.client.delete(&url);
+        if !body.is_empty() {
+            builder = builder
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+        }
+        let builder = self.add_auth_headers(builder, "DELETE", path, &body)?;
+
+        let response = builder
+            .send()
+            .await
+            .context("Failed to send cancel request")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Cancel failed: {} - {}", status, text);
+        }
+
+        response.json().await.context("Failed to parse cancel response")
+    }
 
+    /// Cancel an order
+    pub async fn cancel_order(&self, order_id: &str) -> Result<CancelResponse> {
         #[derive(Serialize)]
         struct CancelRequest<'a> {
             #[serde(rename = "orderID")]
@@ -345,52 +667,74 @@ impl ClobClient {
         }
 
         let body = serde_json::to_string(&CancelRequest { order_id })?;
+        self.send_cancel("/order", body).await
+    }
 
-        let builder = self.client
-            .delete(&url)
-            .header("Content-Type", "application/json")
-            .body(body.clone());
-
-        let builder = self.add_auth_headers(builder, "DELETE", path, &body)?;
+    /// Cancel every open order for the API key
+    pub async fn cancel_all(&self) -> Result<CancelResponse> {
+        self.send_cancel("/cancel-all", String::new()).await
+    }
 
-        let response = builder
-            .send()
-            .await
-            .context("Failed to send cancel request")?;
+    /// Cancel every open order in a market (condition id)
+    pub async fn cancel_market_orders(&self, condition_id: &str) -> Result<CancelResponse> {
+        #[derive(Serialize)]
+        struct CancelMarketRequest<'a> {
+            market: &'a str,
+            asset_id: &'a str,
+        }
 
-        Ok(response.status().is_success())
+        let body = serde_json::to_string(&CancelMarketRequest { market: condition_id, asset_id: "" })?;
+        self.send_cancel("/cancel-market-orders", body).await
     }
 
-    /// Get open orders
-    pub async fn get_open_orders(&self) -> Result<Vec<serde_json::Value>> {
+    /// Get open orders, optionally only for one market, following pagination
+    pub async fn get_open_orders(&self, market: Option<&str>) -> Result<Vec<OpenOrder>> {
         if self.credentials.is_none() {
             anyhow::bail!("Cannot get orders without API credentials");
         }
 
-        let path = "/orders";
-        let url = format!("{}{}", CLOB_API_BASE, path);
+        // The query string is not part of the signed path
+        let path = "/data/orders";
+        let mut orders = Vec::new();
+        let mut cursor = FIRST_CURSOR.to_string();
 
-        let builder = self.client.get(&url);
-        let builder = self.add_auth_headers(builder, "GET", path, "")?;
+        loop {
+            let mut url = format!("{}{}?next_cursor={}", self.base_url, path, cursor);
+            if let Some(market) = market {
+                url.push_str(&format!("&market={}", market));
+            }
 
-        let response = builder
-            .send()
-            .await
-            .context("Failed to fetch open orders")?;
+            let builder = self.client.get(&url);
+            let builder = self.add_auth_headers(builder, "GET", path, "")?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get orders failed: {} - {}", status, text);
+            let response = builder
+                .send()
+                .await
+                .context("Failed to fetch open orders")?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                anyhow::bail!("Get orders failed: {} - {}", status, text);
+            }
+
+            let page: OpenOrdersPage = response.json().await.context("Failed to parse orders response")?;
+            orders.extend(page.data);
+
+            match page.next_cursor {
+                Some(next) if !next.is_empty() && next != END_CURSOR && next != cursor => cursor = next,
+                _ => break,
+            }
         }
 
-        response.json().await.context("Failed to parse orders response")
+        Ok(orders)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::testing::serve_json;
 
     #[test]
     fn test_order_serialization() {
@@ -401,17 +745,174 @@ mod tests {
             side: "BUY".to_string(),
             order_type: "GTC".to_string(),
             expiration: None,
+            post_only: false,
         };
 
         let json = serde_json::to_string(&order).unwrap();
         assert!(json.contains("tokenId"));
         assert!(json.contains("\"price\":\"0.65\""));
+        assert!(!json.contains("postOnly"));
+    }
+
+    const TEST_CLOB_URL: &str = "http://127.0.0.1:1";
+
+    fn test_credentials() -> ClobCredentials {
+        ClobCredentials {
+            api_key: "00000000-0000-0000-0000-000000000000".to_string(),
+            secret: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".to_string(),
+            passphrase: "passphrase".to_string(),
+            wallet_address: "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string(),
+            private_key: "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".to_string(),
+            signature_type: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_order_payload() {
+        let client = ClobClient::new(TEST_CLOB_URL, Some(test_credentials()));
+        client.market_params.write().insert(
+            "1234".to_string(),
+            MarketParams { tick_size: Decimal::new(1, 2), neg_risk: false, fee_rate_bps: 1000 },
+        );
+
+        let order = OrderRequest {
+            token_id: "1234".to_string(),
+            price: "0.58".to_string(),
+            size: "21.04".to_string(),
+            side: "BUY".to_string(),
+            order_type: "GTC".to_string(),
+            expiration: None,
+            post_only: false,
+        };
+        let signed = client.sign_order_request(&order).await.unwrap();
+        let payload = PostOrderRequest {
+            order: signed,
+            owner: test_credentials().api_key,
+            order_type: order.order_type.clone(),
+            post_only: true,
+        };
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["orderType"], "GTC");
+        assert_eq!(json["postOnly"], true);
+        assert_eq!(json["owner"], "00000000-0000-0000-0000-000000000000");
+        assert_eq!(json["order"]["maker"], "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        assert_eq!(json["order"]["makerAmount"], "12203200");
+        assert_eq!(json["order"]["takerAmount"], "21040000");
+        assert_eq!(json["order"]["expiration"], "0");
+        assert_eq!(json["order"]["feeRateBps"], "1000");
+    }
+
+    #[tokio::test]
+    async fn test_place_order_requires_private_key() {
+        let mut creds = test_credentials();
+        creds.private_key = String::new();
+        let client = ClobClient::new(TEST_CLOB_URL, Some(creds));
+        let order = OrderRequest {
+            token_id: "1234".to_string(),
+            price: "0.50".to_string(),
+            size: "10".to_string(),
+            side: "BUY".to_string(),
+            order_type: "GTC".to_string(),
+            expiration: None,
+            post_only: false,
+        };
+        assert!(client.sign_order_request(&order).await.is_err());
+    }
+
+    #[test]
+    fn test_l1_headers() {
+        let client = ClobClient::with_private_key(TEST_CLOB_URL, &test_credentials().private_key).unwrap();
+        assert_eq!(client.signer_address(), Some("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"));
+
+        let request = client
+            .add_l1_headers(client.client.get("http://localhost/auth/derive-api-key"), 7)
+            .unwrap()
+            .build()
+            .unwrap();
+        let headers = request.headers();
+        assert_eq!(headers["POLY_ADDRESS"], "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        assert_eq!(headers["POLY_NONCE"], "7");
+        assert!(headers["POLY_TIMESTAMP"].to_str().unwrap().parse::<i64>().is_ok());
+        assert_eq!(headers["POLY_SIGNATURE"].len(), 132);
+    }
+
+    #[tokio::test]
+    async fn test_get_open_orders_typed() {
+        let (base_url, requests) = serve_json(
+            r#"{"data":[{"id":"0xb3","status":"LIVE","owner":"key","maker_address":"0xf39F",
+                "market":"0xcond","asset_id":"111","side":"BUY","original_size":"10",
+                "size_matched":"2.5","price":"0.45","associate_trades":[],"outcome":"Up",
+                "created_at":1769961600,"expiration":"0","order_type":"GTC"}],
+                "next_cursor":"LTE=","limit":100,"count":1}"#,
+        )
+        .await;
+        let client = ClobClient::new(&base_url, Some(test_credentials()));
+
+        let orders = client.get_open_orders(Some("0xcond")).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        let order = orders[0].to_order_state();
+        assert_eq!(order.order_id, "0xb3");
+        assert_eq!(order.price, 0.45);
+        assert_eq!(order.size_matched, 2.5);
+        assert_eq!(order.remaining_size(), 7.5);
+        assert_eq!(order.created_ms, 1_769_961_600_000);
+        assert_eq!(
+            requests.lock()[0],
+            "GET /data/orders?next_cursor=MA==&market=0xcond HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_endpoints() {
+        let (base_url, requests) =
+            serve_json(r#"{"canceled":["0x1"],"not_canceled":{"0x2":"order can't be found"}}"#).await;
+        let client = ClobClient::new(&base_url, Some(test_credentials()));
+
+        let response = client.cancel_all().await.unwrap();
+        assert_eq!(response.canceled, vec!["0x1".to_string()]);
+        assert_eq!(response.not_canceled["0x2"], "order can't be found");
+
+        client.cancel_order("0x1").await.unwrap();
+        client.cancel_market_orders("0xcond").await.unwrap();
+        assert_eq!(
+            *requests.lock(),
+            vec![
+                "DELETE /cancel-all HTTP/1.1".to_string(),
+                "DELETE /order HTTP/1.1".to_string(),
+                "DELETE /cancel-market-orders HTTP/1.1".to_string(),
+            ]
+        );
+
+        let no_creds = ClobClient::new(&base_url, None);
+        assert!(no_creds.cancel_all().await.is_err());
+    }
+
+    #[test]
+    fn test_api_key_response_parsing() {
+        let creds: ApiKeyCredentials = serde_json::from_str(
+            r#"{"apiKey":"key-123","secret":"c2VjcmV0","passphrase":"pass"}"#,
+        )
+        .unwrap();
+        assert_eq!(creds.api_key, "key-123");
+        assert_eq!(creds.secret, "c2VjcmV0");
+        assert_eq!(creds.passphrase, "pass");
+    }
+
+    #[test]
+    fn test_order_response_parses_order_id() {
+        let resp: OrderResponse = serde_json::from_str(
+            r#"{"errorMsg":"","orderID":"0xabc","status":"matched","success":true}"#,
+        )
+        .unwrap();
+        assert_eq!(resp.order_id.as_deref(), Some("0xabc"));
+        assert!(resp.success);
     }
 
     #[tokio::test]
     async fn test_get_order_book_no_auth() {
         // This should work without credentials (public endpoint)
-        let client = ClobClient::new(None);
+        let _client = ClobClient::new("https://clob.polymarket.com", None);
         // Note: Would need a valid token ID to actually test
         // let result = client.get_order_book("some_token_id").await;
     }
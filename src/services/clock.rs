@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Where the services get "now" from: the system clock when live, a clock the replay
+/// moves forward when driving them from recorded data. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    simulated: Option<Arc<AtomicI64>>,
+}
+
+impl Clock {
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// A clock that only moves when `set_ms` is called
+    pub fn simulated(start_ms: i64) -> Self {
+        Self {
+            simulated: Some(Arc::new(AtomicI64::new(start_ms))),
+        }
+    }
+
+    pub fn is_simulated(&self) -> bool {
+        self.simulated.is_some()
+    }
+
+    pub fn now_ms(&self) -> i64 {
+        match &self.simulated {
+            Some(ms) => ms.load(Ordering::Relaxed),
+            None => chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    pub fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        match &self.simulated {
+            Some(ms) => chrono::DateTime::from_timestamp_millis(ms.load(Ordering::Relaxed)).unwrap_or_default(),
+            None => chrono::Utc::now(),
+        }
+    }
+
+    /// Move a simulated clock to `ms`; the system clock ignores this
+    pub fn set_ms(&self, ms: i64) {
+        if let Some(sim) = &self.simulated {
+            sim.store(ms, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_clock_is_shared() {
+        let clock = Clock::simulated(1_000);
+        let other = clock.clone();
+        other.set_ms(5_500);
+        assert_eq!(clock.now_ms(), 5_500);
+        assert_eq!(clock.now().timestamp(), 5);
+
+        // The system clock can't be moved
+        let system = Clock::system();
+        system.set_ms(0);
+        assert!(system.now_ms() > 1_600_000_000_000);
+    }
+}
@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::{Mutex, RwLock};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::config::CoinbaseConfig;
+use super::book::{BookState, SpotBook};
+
+// Advanced Trade WebSocket message; sequence_num counts every message on the connection
+#[derive(Debug, Clone, Deserialize)]
+struct CoinbaseMessage {
+    #[serde(default)]
+    channel: String,
+    sequence_num: u64,
+    #[serde(default)]
+    events: Vec<Level2Event>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Level2Event {
+    // "snapshot" or "update"
+    #[serde(rename = "type", default)]
+    event_type: String,
+    #[serde(default)]
+    updates: Vec<Level2Update>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Level2Update {
+    // "bid" or "offer"
+    side: String,
+    price_level: String,
+    new_quantity: String,
+}
+
+/// Coinbase BTC-USD book from the level2 channel: a snapshot on subscribe, then updates
+pub struct CoinbaseBookService {
+    config: CoinbaseConfig,
+    state: BookState,
+    // Last sequence_num seen on this connection
+    sequence: Mutex<Option<u64>>,
+    running: Arc<RwLock<bool>>,
+}
+
+impl CoinbaseBookService {
+    pub fn new(config: CoinbaseConfig) -> Self {
+        Self {
+            config,
+            state: BookState::new(),
+            sequence: Mutex::new(None),
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        *self.running.write() = true;
+
+        loop {
+            if !*self.running.read() {
+                break;
+            }
+
+            if let Err(e) = self.run_connection().await {
+                tracing::error!("Coinbase connection error: {:?}, reconnecting...", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run_connection(&self) -> Result<()> {
+        tracing::info!("Connecting to Coinbase WebSocket...");
+
+        let (ws_stream, _) = connect_async(&self.config.ws_url)
+            .await
+            .context("Failed to connect to Coinbase WS")?;
+
+        let (mut write, mut read) = ws_stream.split();
+
+        // Heartbeats keep the connection open when the book is quiet
+        for channel in ["level2", "heartbeats"] {
+            let subscribe = serde_json::json!({
+                "type": "subscribe",
+                "product_ids": [self.config.product_id],
+                "channel": channel,
+            });
+            write.send(Message::Text(subscribe.to_string())).await?;
+        }
+        *self.sequence.lock() = None;
+
+        while let Some(msg) = read.next().await {
+            if !*self.running.read() {
+                break;
+            }
+
+            match msg {
+                Ok(Message::Text(text)) => {
+                    // Out of sync: reconnect for a fresh snapshot
+                    let in_sync = self.handle_message(&text);
+                    if !in_sync {
+                        break;
+                    }
+                }
+                Ok(Message::Ping(data)) => {
+                    if let Err(e) = write.send(Message::Pong(data)).await {
+                        tracing::error!("Failed to send pong: {:?}", e);
+                    }
+                }
+                Ok(Message::Close(_)) => {
+                    tracing::warn!("Coinbase WebSocket closed");
+                    break;
+                }
+                Err(e) => {
+                    tracing::error!("WebSocket error: {:?}", e);
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        self.state.reset();
+        Ok(())
+    }
+
+    /// Apply one frame. Returns false on a sequence gap, after which the book stays empty
+    /// until a new subscription delivers a snapshot.
+    pub fn handle_message(&self, text: &str) -> bool {
+        let msg: CoinbaseMessage = match serde_json::from_str(text) {
+            Ok(msg) => msg,
+            Err(_) => {
+                tracing::warn!("Unexpected Coinbase message: {}", text);
+                return true;
+            }
+        };
+
+        {
+            let mut sequence = self.sequence.lock();
+            if let Some(last) = *sequence {
+                if msg.sequence_num != last + 1 {
+                    tracing::warn!("Coinbase sequence gap ({} after {}), resyncing...", msg.sequence_num, last);
+                    *sequence = None;
+                    self.state.reset();
+                    return false;
+                }
+            }
+            *sequence = Some(msg.sequence_num);
+        }
+
+        if msg.channel != "l2_data" {
+            return true;
+        }
+
+        let mut changed = false;
+        {
+            let mut book = self.state.write();
+            for event in &msg.events {
+                match event.event_type.as_str() {
+                    "snapshot" => {
+                        book.bids.clear();
+                        book.asks.clear();
+                        book.initialized = true;
+                    }
+                    // Nothing to apply to yet
+                    _ if !book.initialized => continue,
+                    _ => {}
+                }
+                for update in &event.updates {
+                    match (update.price_level.parse(), update.new_quantity.parse()) {
+                        (Ok(price), Ok(qty)) => book.set_level(update.side == "bid", price, qty),
+                        _ => tracing::error!("Bad Coinbase level: {:?}", update),
+                    }
+                }
+                book.last_update_id = msg.sequence_num;
+                changed = true;
+            }
+        }
+        if changed {
+            self.state.updated();
+        }
+        true
+    }
+
+    pub fn stop(&self) {
+        *self.running.write() = false;
+    }
+}
+
+impl SpotBook for CoinbaseBookService {
+    fn venue(&self) -> &str {
+        "coinbase"
+    }
+
+    fn state(&self) -> &BookState {
+        &self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn l2(seq: u64, event_type: &str, updates: &str) -> String {
+        format!(
+            r#"{{"channel":"l2_data","client_id":"","timestamp":"2026-10-17T12:00:00Z","sequence_num":{},
+            "events":[{{"type":"{}","product_id":"BTC-USD","updates":[{}]}}]}}"#,
+            seq, event_type, updates
+        )
+    }
+
+    #[test]
+    fn test_snapshot_updates_and_gap() {
+        let service = CoinbaseBookService::new(CoinbaseConfig::default());
+        assert!(service.handle_message(r#"{"channel":"subscriptions","sequence_num":0,"events":[]}"#));
+        // Updates before the snapshot are ignored
+        assert!(service.handle_message(&l2(1, "update", r#"{"side":"bid","price_level":"99990","new_quantity":"5"}"#)));
+        assert_eq!(service.get_mid_price(), None);
+
+        let snapshot = r#"{"side":"bid","price_level":"99999.50","new_quantity":"1.5"},
+            {"side":"bid","price_level":"99999.00","new_quantity":"2"},
+            {"side":"offer","price_level":"100000.50","new_quantity":"0.5"}"#;
+        assert!(service.handle_message(&l2(2, "snapshot", snapshot)));
+        assert_eq!(service.get_mid_price(), Some(100_000.0));
+
+        // Best bid pulled
+        assert!(service.handle_message(&l2(3, "update", r#"{"side":"bid","price_level":"99999.50","new_quantity":"0"}"#)));
+        assert_eq!(service.get_mid_price(), Some(99_999.75));
+        assert_eq!(service.get_current_update().unwrap().update_id, 3);
+
+        // Heartbeats share the sequence; skipping one is a gap
+        assert!(service.handle_message(r#"{"channel":"heartbeats","sequence_num":4,"events":[{"heartbeat_counter":1}]}"#));
+        assert!(!service.handle_message(&l2(6, "update", r#"{"side":"bid","price_level":"99999.60","new_quantity":"1"}"#)));
+        assert_eq!(service.get_mid_price(), None);
+    }
+}
@@ -0,0 +1,73 @@
+/// Seconds covered by `binance_std_5m`
+pub const STD_WINDOW_SECS: f64 = 300.0;
+
+/// Per-second volatility (USD / sqrt(s)) implied by the standard deviation of mid prices
+/// sampled over `window_secs`. For a random walk the sample variance of the path over a
+/// window T is about sigma^2 * T / 6, so sigma = std / sqrt(T / 6).
+pub fn vol_per_sec_from_std(std_dev: f64, window_secs: f64) -> f64 {
+    if window_secs <= 0.0 {
+        return 0.0;
+    }
+    std_dev / (window_secs / 6.0).sqrt()
+}
+
+/// P(price at expiry >= target) for a driftless random walk with per-second volatility
+/// `vol_per_sec` and `remaining_secs` to go. At expiry (or with no volatility) this is 1
+/// or 0, with ties going to Up like the market's resolution rule.
+pub fn prob_close_above(price: f64, target: f64, vol_per_sec: f64, remaining_secs: f64) -> f64 {
+    let sd = vol_per_sec * remaining_secs.max(0.0).sqrt();
+    if sd <= 0.0 {
+        return if price >= target { 1.0 } else { 0.0 };
+    }
+    norm_cdf((price - target) / sd)
+}
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26, error < 1.5e-7)
+fn norm_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_norm_cdf() {
+        assert!((norm_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((norm_cdf(1.0) - 0.841345).abs() < 1e-5);
+        assert!((norm_cdf(-1.96) - 0.024998).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_prob_close_above() {
+        // At the target with time left: a coin flip
+        assert!((prob_close_above(100_000.0, 100_000.0, 5.0, 600.0) - 0.5).abs() < 1e-7);
+
+        // $100 above with sd 5 * sqrt(100) = $50 to go: 2 sd
+        let p = prob_close_above(100_100.0, 100_000.0, 5.0, 100.0);
+        assert!((p - 0.97725).abs() < 1e-4);
+        assert!((prob_close_above(99_900.0, 100_000.0, 5.0, 100.0) - (1.0 - p)).abs() < 1e-7);
+
+        // Less time, same distance: more certain
+        assert!(prob_close_above(100_100.0, 100_000.0, 5.0, 25.0) > p);
+
+        // Expired: resolved by the rule, ties go Up
+        assert_eq!(prob_close_above(100_000.0, 100_000.0, 5.0, 0.0), 1.0);
+        assert_eq!(prob_close_above(99_999.0, 100_000.0, 5.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_vol_from_std() {
+        // std of $50 over 5 minutes -> 50 / sqrt(50) per second
+        assert!((vol_per_sec_from_std(50.0, STD_WINDOW_SECS) - 50.0 / 50f64.sqrt()).abs() < 1e-9);
+        assert_eq!(vol_per_sec_from_std(50.0, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,78 @@
+/// Polymarket taker fee for one market.
+///
+/// The CTF exchange charges the taker `rate * min(p, 1 - p)` USDC per share, where `rate`
+/// is the market's fee rate in bps. Fees peak at 50c and vanish towards 0 and 1; makers
+/// pay nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeModel {
+    pub rate_bps: u64,
+}
+
+impl FeeModel {
+    pub fn new(rate_bps: u64) -> Self {
+        Self { rate_bps }
+    }
+
+    fn rate(&self) -> f64 {
+        self.rate_bps as f64 / 10_000.0
+    }
+
+    /// Fee in USDC per share taken at `price`
+    pub fn fee_per_share(&self, price: f64) -> f64 {
+        self.rate() * price.min(1.0 - price).max(0.0)
+    }
+
+    /// Fee in USDC for taking `size` shares at `price`
+    pub fn taker_fee(&self, price: f64, size: f64) -> f64 {
+        self.fee_per_share(price) * size
+    }
+
+    /// All-in cost per share of a taker buy
+    pub fn buy_cost(&self, price: f64) -> f64 {
+        price + self.fee_per_share(price)
+    }
+
+    /// Net proceeds per share of a taker sell
+    pub fn sell_proceeds(&self, price: f64) -> f64 {
+        price - self.fee_per_share(price)
+    }
+
+    /// Highest limit price (in cents) whose all-in taker cost stays within `max_cost`
+    pub fn max_buy_price(&self, max_cost: f64) -> f64 {
+        let rate = self.rate();
+        // buy_cost is increasing in price, with a kink at 0.5
+        let price = if max_cost <= 0.5 * (1.0 + rate) {
+            max_cost / (1.0 + rate)
+        } else {
+            (max_cost - rate) / (1.0 - rate)
+        };
+        ((price * 100.0) + 1e-9).floor() / 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_curve() {
+        let fees = FeeModel::new(1000);
+        // Peak at 50c: 10% of min(p, 1-p)
+        assert!((fees.fee_per_share(0.50) - 0.05).abs() < 1e-9);
+        assert!((fees.fee_per_share(0.90) - 0.01).abs() < 1e-9);
+        assert!((fees.fee_per_share(0.10) - 0.01).abs() < 1e-9);
+        assert!((fees.taker_fee(0.40, 10.0) - 0.40).abs() < 1e-9);
+        assert!((fees.buy_cost(0.90) - 0.91).abs() < 1e-9);
+        assert!((fees.sell_proceeds(0.60) - 0.56).abs() < 1e-9);
+        assert_eq!(FeeModel::default().fee_per_share(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_max_buy_price() {
+        let fees = FeeModel::new(1000);
+        assert_eq!(fees.max_buy_price(0.95), 0.94); // 0.94 + 0.006
+        assert_eq!(fees.max_buy_price(0.44), 0.40); // 0.40 + 0.04
+        assert!(fees.buy_cost(fees.max_buy_price(0.55)) <= 0.55);
+        assert_eq!(FeeModel::default().max_buy_price(0.95), 0.95);
+    }
+}
@@ -0,0 +1,107 @@
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+use crate::events::FlowStats;
+
+/// Longest window flow is reported over; older trades are dropped
+pub const MAX_FLOW_WINDOW_MS: i64 = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Trade {
+    t_ms: i64,
+    price: f64,
+    qty: f64,
+    // The taker bought (lifted the offer)
+    taker_buy: bool,
+}
+
+/// Rolling record of one venue's trades, for taker flow over the last few seconds
+pub struct TradeFlow {
+    trades: Mutex<VecDeque<Trade>>,
+    // Newest trade time and when it reached us, to read the venue's clock from ours
+    newest: Mutex<Option<(i64, i64)>>,
+    // Trades at least this size count as large prints (0 = off)
+    large_qty: f64,
+}
+
+impl TradeFlow {
+    pub fn new(large_qty: f64) -> Self {
+        Self {
+            trades: Mutex::new(VecDeque::new()),
+            newest: Mutex::new(None),
+            large_qty,
+        }
+    }
+
+    /// Record a trade made at `t_ms` (venue time) that arrived at `recv_ms` (our time)
+    pub fn record(&self, t_ms: i64, recv_ms: i64, price: f64, qty: f64, taker_buy: bool) {
+        let newest_ms = {
+            let mut newest = self.newest.lock();
+            if newest.is_none_or(|(t, _)| t_ms >= t) {
+                *newest = Some((t_ms, recv_ms));
+            }
+            newest.map_or(t_ms, |(t, _)| t)
+        };
+        let mut trades = self.trades.lock();
+        trades.push_back(Trade { t_ms, price, qty, taker_buy });
+        while trades.front().is_some_and(|t| t.t_ms < newest_ms - MAX_FLOW_WINDOW_MS) {
+            trades.pop_front();
+        }
+    }
+
+    /// Flow over the `window_ms` up to `now_ms` (our time). The window is on the venue's
+    /// clock: it ends at the newest trade time plus what has elapsed here since that trade
+    /// arrived, so clock skew between the venue and this host doesn't shift it.
+    pub fn stats(&self, now_ms: i64, window_ms: i64) -> FlowStats {
+        let end_ms = self.newest.lock().map_or(now_ms, |(t, recv)| t + (now_ms - recv));
+        let trades = self.trades.lock();
+        let mut stats = FlowStats {
+            window_ms,
+            ..Default::default()
+        };
+        let mut notional = 0.0;
+        for trade in trades.iter().filter(|t| t.t_ms >= end_ms - window_ms) {
+            let signed = if trade.taker_buy { trade.qty } else { -trade.qty };
+            stats.trade_count += 1;
+            stats.volume += trade.qty;
+            stats.signed_volume += signed;
+            notional += trade.price * trade.qty;
+            if self.large_qty > 0.0 && trade.qty >= self.large_qty {
+                stats.large_prints += 1;
+                stats.large_signed_volume += signed;
+            }
+        }
+        stats.vwap = (stats.volume > 0.0).then(|| notional / stats.volume);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flow_windows() {
+        let flow = TradeFlow::new(1.0);
+        flow.record(0, 0, 100_000.0, 0.5, true);
+        flow.record(7_500, 7_500, 100_010.0, 2.0, false);
+        flow.record(9_000, 9_000, 100_020.0, 0.25, true);
+        flow.record(9_800, 9_800, 100_030.0, 1.0, true);
+
+        let one = flow.stats(10_000, 1_000);
+        assert_eq!((one.trade_count, one.large_prints), (2, 1));
+        assert!((one.signed_volume - 1.25).abs() < 1e-9);
+        assert!((one.vwap.unwrap() - (100_020.0 * 0.25 + 100_030.0) / 1.25).abs() < 1e-6);
+
+        let three = flow.stats(10_000, 3_000);
+        assert_eq!((three.trade_count, three.large_prints), (3, 2));
+        assert!((three.signed_volume + 0.75).abs() < 1e-9);
+        assert!((three.large_signed_volume + 1.0).abs() < 1e-9);
+
+        let ten = flow.stats(10_000, 10_000);
+        assert_eq!(ten.trade_count, 4);
+        assert!((ten.volume - 3.75).abs() < 1e-9);
+
+        assert_eq!(flow.stats(20_000, 1_000).vwap, None);
+    }
+}
@@ -1,7 +1,6 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
-const GAMMA_API_BASE: &str = "https://gamma-api.polymarket.com";
 const FIFTEEN_MINUTES_SECS: i64 = 900;
 
 #[derive(Debug, Clone, Deserialize)]
@@ -35,6 +34,23 @@ pub struct GammaMarket {
     pub event_start_time: Option<String>,  // When the 15-min window starts (e.g., "2026-02-01T15:30:00Z")
 }
 
+impl GammaMarket {
+    /// The outcome priced at 1 once the market has closed, upper-cased ("UP" / "DOWN")
+    pub fn resolution(&self) -> Option<String> {
+        if !self.closed {
+            return None;
+        }
+        // Both come as JSON strings, e.g. "[\"Up\", \"Down\"]" and "[\"1\", \"0\"]"
+        let outcomes: Vec<String> = serde_json::from_str(self.outcomes.as_deref()?).ok()?;
+        let prices: Vec<String> = serde_json::from_str(self.outcome_prices.as_deref()?).ok()?;
+        outcomes
+            .iter()
+            .zip(&prices)
+            .find(|(_, price)| price.parse::<f64>().is_ok_and(|p| p >= 0.99))
+            .map(|(outcome, _)| outcome.to_uppercase())
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GammaEventInfo {
@@ -56,13 +72,15 @@ pub struct MarketTokens {
 
 pub struct GammaClient {
     client: reqwest::Client,
+    base_url: String,
     coin_slug_prefix: String,
 }
 
 impl GammaClient {
-    pub fn new(_btc_15m_event_id: String) -> Self {
+    pub fn new(base_url: &str) -> Self {
         Self {
             client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
             coin_slug_prefix: "btc-updown-15m".to_string(),
         }
     }
@@ -75,7 +93,7 @@ impl GammaClient {
 
     /// Fetch market by slug
     async fn get_market_by_slug(&self, slug: &str) -> Result<Option<GammaMarket>> {
-        let url = format!("{}/markets/slug/{}", GAMMA_API_BASE, slug);
+        let url = format!("{}/markets/slug/{}", self.base_url, slug);
 
         tracing::debug!("Fetching market from: {}", url);
 
@@ -176,6 +194,14 @@ impl GammaClient {
         })
     }
 
+    /// Winning side ("UP" / "DOWN") of a closed market, once Gamma's outcomePrices show it
+    pub async fn get_resolution(&self, slug: &str) -> Result<Option<String>> {
+        let Some(market) = self.get_market_by_slug(slug).await? else {
+            return Ok(None);
+        };
+        Ok(market.resolution())
+    }
+
     /// Check if the current market has changed (new 15-min window)
     pub async fn check_for_new_market(&self, current_condition_id: &str) -> Result<Option<MarketTokens>> {
         let tokens = self.get_current_btc_15m_market().await?;
@@ -196,10 +222,11 @@ impl GammaClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::testing::serve_json;
 
     #[tokio::test]
     async fn test_fetch_btc_15m_market() {
-        let client = GammaClient::new("unused".to_string());
+        let client = GammaClient::new("https://gamma-api.polymarket.com");
         let result = client.get_current_btc_15m_market().await;
 
         match result {
@@ -218,6 +245,52 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_market_discovery_against_local_server() {
+        let body = serde_json::json!({
+            "id": "1",
+            "question": "Bitcoin Up or Down - test window",
+            "conditionId": "0xcond",
+            "slug": "btc-updown-15m-test",
+            "endDate": "2026-02-01T17:30:00Z",
+            "active": true,
+            "closed": false,
+            "clobTokenIds": "[\"111\", \"222\"]",
+            "acceptingOrders": true,
+            "eventStartTime": "2026-02-01T17:15:00Z"
+        });
+        let (base_url, _) = serve_json(body.to_string()).await;
+
+        let client = GammaClient::new(&base_url);
+        let tokens = client.get_current_btc_15m_market().await.unwrap();
+        assert_eq!(tokens.up_token_id, "111");
+        assert_eq!(tokens.down_token_id, "222");
+        assert_eq!(tokens.condition_id, "0xcond");
+        assert_eq!(tokens.start_time, "2026-02-01T17:15:00Z");
+    }
+
+    #[tokio::test]
+    async fn test_resolution_from_outcome_prices() {
+        let body = serde_json::json!({
+            "id": "1",
+            "question": "Bitcoin Up or Down - test window",
+            "conditionId": "0xcond",
+            "slug": "btc-updown-15m-test",
+            "endDate": "2026-02-01T17:30:00Z",
+            "active": true,
+            "closed": true,
+            "outcomes": "[\"Up\", \"Down\"]",
+            "outcomePrices": "[\"0\", \"1\"]"
+        });
+        let client = GammaClient::new(&serve_json(body.to_string()).await.0);
+        assert_eq!(client.get_resolution("btc-updown-15m-test").await.unwrap().as_deref(), Some("DOWN"));
+
+        // Still trading: no resolution yet
+        let mut market: GammaMarket = serde_json::from_value(body).unwrap();
+        market.closed = false;
+        assert_eq!(market.resolution(), None);
+    }
+
     #[test]
     fn test_window_timestamp() {
         let ts = GammaClient::get_current_window_timestamp();
@@ -0,0 +1,251 @@
+use anyhow::Result;
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::IndexConfig;
+use super::book::SpotBook;
+use super::clock::Clock;
+
+/// Index samples kept, enough for the 5-minute volatility
+const HISTORY_MS: i64 = 300_000;
+
+/// One reading of the index and the venues that went into it
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexValue {
+    pub price: f64,
+    pub venues: Vec<String>,
+}
+
+/// Composite BTC price across the spot venues, closer to what Chainlink reports than any
+/// single book: a weighted mean of the venues' mids, leaving out stale books and, with
+/// three or more venues, any mid too far from their median. Sampled every `sample_ms`
+/// for returns and volatility. The venues' mids differ by a basis of their own, so the
+/// history restarts whenever the set of venues in the index changes rather than read that
+/// basis as a price move.
+pub struct IndexService {
+    config: IndexConfig,
+    venues: Vec<Arc<dyn SpotBook>>,
+    history: RwLock<VecDeque<(i64, f64)>>,
+    history_venues: RwLock<Vec<String>>, // venues behind the samples in `history`
+    last: RwLock<Option<IndexValue>>,
+    running: Arc<RwLock<bool>>,
+    clock: Clock,
+}
+
+impl IndexService {
+    pub fn new(config: IndexConfig, venues: Vec<Arc<dyn SpotBook>>) -> Self {
+        Self {
+            config,
+            venues,
+            history: RwLock::new(VecDeque::new()),
+            history_venues: RwLock::new(Vec::new()),
+            last: RwLock::new(None),
+            running: Arc::new(RwLock::new(false)),
+            clock: Clock::system(),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn weight(&self, venue: &str) -> f64 {
+        self.config.weights.get(venue).copied().unwrap_or(1.0)
+    }
+
+    /// The index from the venues' books right now; None if no venue is usable
+    pub fn compute(&self) -> Option<IndexValue> {
+        let mut mids: Vec<(&str, f64, f64)> = self
+            .venues
+            .iter()
+            .filter(|v| v.age_ms().is_some_and(|age| age <= self.config.stale_ms))
+            .filter_map(|v| Some((v.venue(), v.get_mid_price()?, self.weight(v.venue()))))
+            .filter(|(_, _, weight)| *weight > 0.0)
+            .collect();
+
+        // Two venues that disagree can't tell which one is off
+        if mids.len() >= 3 && self.config.max_deviation_bps > 0.0 {
+            let median = median(mids.iter().map(|(_, mid, _)| *mid).collect());
+            mids.retain(|(_, mid, _)| (mid - median).abs() / median * 10_000.0 <= self.config.max_deviation_bps);
+        }
+        if mids.is_empty() {
+            return None;
+        }
+
+        let total_weight: f64 = mids.iter().map(|(_, _, weight)| weight).sum();
+        let price = mids.iter().map(|(_, mid, weight)| mid * weight).sum::<f64>() / total_weight;
+        Some(IndexValue {
+            price,
+            venues: mids.iter().map(|(venue, _, _)| venue.to_string()).collect(),
+        })
+    }
+
+    /// Compute the index and add it to the history
+    pub fn sample(&self) -> Option<IndexValue> {
+        let value = self.compute();
+        if let Some(value) = &value {
+            let now = self.clock.now_ms();
+            let mut history = self.history.write();
+            let mut venues = self.history_venues.write();
+            if *venues != value.venues {
+                venues.clone_from(&value.venues);
+                history.clear();
+            }
+            history.push_back((now, value.price));
+            while history.front().is_some_and(|(ts, _)| *ts < now - HISTORY_MS) {
+                history.pop_front();
+            }
+        }
+        *self.last.write() = value.clone();
+        value
+    }
+
+    /// Latest sample
+    pub fn get_value(&self) -> Option<IndexValue> {
+        self.last.read().clone()
+    }
+
+    pub fn get_price(&self) -> Option<f64> {
+        self.last.read().as_ref().map(|v| v.price)
+    }
+
+    pub fn get_returns(&self, lookback_ms: i64) -> Option<f64> {
+        let history = self.history.read();
+        if history.len() < 2 {
+            return None;
+        }
+        let cutoff = self.clock.now_ms() - lookback_ms;
+        let (_, old_price) = history.iter().find(|(ts, _)| *ts >= cutoff)?;
+        let (_, current_price) = history.back()?;
+        (*old_price > 0.0).then(|| (current_price - old_price) / old_price)
+    }
+
+    /// Sample standard deviation of the index over the lookback period
+    pub fn get_std_dev(&self, lookback_ms: i64) -> Option<f64> {
+        let history = self.history.read();
+        let cutoff = self.clock.now_ms() - lookback_ms;
+        let prices: Vec<f64> = history.iter().filter(|(ts, _)| *ts >= cutoff).map(|(_, p)| *p).collect();
+        if prices.len() < 2 {
+            return None;
+        }
+        let mean = prices.iter().sum::<f64>() / prices.len() as f64;
+        let variance = prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / (prices.len() - 1) as f64;
+        Some(variance.sqrt())
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        *self.running.write() = true;
+        let mut interval = tokio::time::interval(Duration::from_millis(self.config.sample_ms.max(10)));
+        while *self.running.read() {
+            interval.tick().await;
+            self.sample();
+        }
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        *self.running.write() = false;
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::book::TestVenue;
+
+    fn config(weights: &[(&str, f64)]) -> IndexConfig {
+        IndexConfig {
+            weights: weights.iter().map(|(venue, weight)| (venue.to_string(), *weight)).collect(),
+            max_deviation_bps: 10.0,
+            stale_ms: 2_000,
+            sample_ms: 100,
+        }
+    }
+
+    #[test]
+    fn test_index_weights_outliers_and_staleness() {
+        let clock = Clock::simulated(1_000_000);
+        let (binance, coinbase, kraken) =
+            (TestVenue::new("binance", &clock), TestVenue::new("coinbase", &clock), TestVenue::new("kraken", &clock));
+        let venues: Vec<Arc<dyn SpotBook>> = vec![binance.clone(), coinbase.clone(), kraken.clone()];
+        let index = IndexService::new(config(&[("coinbase", 2.0)]), venues).with_clock(clock.clone());
+        assert_eq!(index.sample(), None);
+
+        binance.quote(100_000.0);
+        coinbase.quote(100_030.0);
+        kraken.quote(100_060.0);
+        let value = index.sample().unwrap();
+        // (100000 + 2 * 100030 + 100060) / 4
+        assert!((value.price - 100_030.0).abs() < 1e-6);
+        assert_eq!(value.venues.len(), 3);
+
+        // Kraken 20bps off the median is dropped. The index moves 10 but that's Kraken's
+        // basis leaving, not a return: the history starts over.
+        clock.set_ms(1_001_000);
+        kraken.quote(100_230.0);
+        let value = index.sample().unwrap();
+        assert_eq!(value.venues, vec!["binance", "coinbase"]);
+        assert!((value.price - 100_020.0).abs() < 1e-6);
+        assert_eq!(index.get_returns(1_000), None);
+        clock.set_ms(1_001_500);
+        binance.quote(100_030.0);
+        let value = index.sample().unwrap();
+        assert!((index.get_returns(1_000).unwrap() - (value.price / 100_020.0 - 1.0)).abs() < 1e-12);
+
+        // Coinbase and Kraken go quiet
+        clock.set_ms(1_003_500);
+        binance.quote(100_010.0);
+        let value = index.sample().unwrap();
+        assert_eq!(value.venues, vec!["binance"]);
+        assert!((value.price - 100_010.0).abs() < 1e-6);
+        assert_eq!(index.get_std_dev(60_000), None);
+        clock.set_ms(1_003_600);
+        binance.quote(100_020.0);
+        index.sample();
+        assert!(index.get_std_dev(60_000).unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_single_venue_is_never_an_outlier() {
+        let clock = Clock::simulated(1_000_000);
+        let binance = TestVenue::new("binance", &clock);
+        let index = IndexService::new(config(&[]), vec![binance.clone()]).with_clock(clock.clone());
+        binance.quote(100_000.0);
+        assert_eq!(index.sample().unwrap().price, 100_000.0);
+
+        // A 50bps move on one venue has no median to be compared against
+        clock.set_ms(1_000_100);
+        binance.quote(100_500.0);
+        let value = index.sample().unwrap();
+        assert_eq!((value.price, value.venues), (100_500.0, vec!["binance".to_string()]));
+        assert!((index.get_returns(1_000).unwrap() - 0.005).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_zero_weight_venues_are_left_out() {
+        let clock = Clock::simulated(1_000_000);
+        let (binance, kraken) = (TestVenue::new("binance", &clock), TestVenue::new("kraken", &clock));
+        let venues: Vec<Arc<dyn SpotBook>> = vec![binance.clone(), kraken.clone()];
+        let index = IndexService::new(config(&[("kraken", 0.0)]), venues).with_clock(clock.clone());
+        kraken.quote(100_100.0);
+        assert_eq!(index.sample(), None);
+
+        binance.quote(100_000.0);
+        let value = index.sample().unwrap();
+        assert_eq!((value.price, value.venues), (100_000.0, vec!["binance".to_string()]));
+    }
+}
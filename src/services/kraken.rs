@@ -0,0 +1,272 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::config::KrakenConfig;
+use super::book::{BookState, OrderBook, SpotBook};
+
+/// Levels per side covered by Kraken's book checksum
+const CHECKSUM_LEVELS: usize = 10;
+
+// v2 WebSocket message; the book channel carries a snapshot then updates
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenMessage {
+    #[serde(default)]
+    channel: String,
+    #[serde(rename = "type", default)]
+    msg_type: String,
+    #[serde(default)]
+    data: Vec<KrakenBook>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenBook {
+    #[serde(default)]
+    bids: Vec<KrakenLevel>,
+    #[serde(default)]
+    asks: Vec<KrakenLevel>,
+    // CRC32 of the top 10 levels after this message is applied
+    #[serde(default)]
+    checksum: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KrakenLevel {
+    price: f64,
+    qty: f64,
+}
+
+/// Kraken BTC/USD book from the v2 book channel. Kraken has no sequence numbers; each
+/// message carries a checksum of the resulting top of book, which is verified instead.
+pub struct KrakenBookService {
+    config: KrakenConfig,
+    state: BookState,
+    running: Arc<RwLock<bool>>,
+}
+
+impl KrakenBookService {
+    pub fn new(config: KrakenConfig) -> Self {
+        Self {
+            config,
+            state: BookState::new(),
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        *self.running.write() = true;
+
+        loop {
+            if !*self.running.read() {
+                break;
+            }
+
+            if let Err(e) = self.run_connection().await {
+                tracing::error!("Kraken connection error: {:?}, reconnecting...", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run_connection(&self) -> Result<()> {
+        tracing::info!("Connecting to Kraken WebSocket...");
+
+        let (ws_stream, _) = connect_async(&self.config.ws_url)
+            .await
+            .context("Failed to connect to Kraken WS")?;
+
+        let (mut write, mut read) = ws_stream.split();
+
+        let subscribe = serde_json::json!({
+            "method": "subscribe",
+            "params": {
+                "channel": "book",
+                "symbol": [self.config.symbol],
+                "depth": self.config.depth,
+            },
+        });
+        write.send(Message::Text(subscribe.to_string())).await?;
+
+        while let Some(msg) = read.next().await {
+            if !*self.running.read() {
+                break;
+            }
+
+            match msg {
+                Ok(Message::Text(text)) => {
+                    // Out of sync: reconnect for a fresh snapshot
+                    let in_sync = self.handle_message(&text);
+                    if !in_sync {
+                        break;
+                    }
+                }
+                Ok(Message::Ping(data)) => {
+                    if let Err(e) = write.send(Message::Pong(data)).await {
+                        tracing::error!("Failed to send pong: {:?}", e);
+                    }
+                }
+                Ok(Message::Close(_)) => {
+                    tracing::warn!("Kraken WebSocket closed");
+                    break;
+                }
+                Err(e) => {
+                    tracing::error!("WebSocket error: {:?}", e);
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        self.state.reset();
+        Ok(())
+    }
+
+    /// Apply one frame. Returns false on a checksum mismatch, after which the book stays
+    /// empty until a new subscription delivers a snapshot.
+    pub fn handle_message(&self, text: &str) -> bool {
+        let msg: KrakenMessage = match serde_json::from_str(text) {
+            Ok(msg) => msg,
+            Err(_) => {
+                tracing::warn!("Unexpected Kraken message: {}", text);
+                return true;
+            }
+        };
+        if msg.channel != "book" {
+            return true;
+        }
+
+        {
+            let mut book = self.state.write();
+            for data in &msg.data {
+                match msg.msg_type.as_str() {
+                    "snapshot" => {
+                        book.bids.clear();
+                        book.asks.clear();
+                        book.initialized = true;
+                    }
+                    _ if !book.initialized => return true,
+                    _ => {}
+                }
+                for (levels, is_bid) in [(&data.bids, true), (&data.asks, false)] {
+                    for level in levels {
+                        match (self.decimal(level.price, self.config.price_precision), self.decimal(level.qty, self.config.qty_precision)) {
+                            (Some(price), Some(qty)) => book.set_level(is_bid, price, qty),
+                            _ => tracing::error!("Bad Kraken level: {:?}", level),
+                        }
+                    }
+                }
+                // Levels pushed out of the subscribed depth aren't deleted explicitly
+                book.truncate(self.config.depth);
+                book.last_update_id += 1;
+
+                let expected = checksum(&book);
+                if data.checksum != expected {
+                    tracing::warn!("Kraken checksum mismatch ({} vs {}), resyncing...", data.checksum, expected);
+                    *book = OrderBook::new();
+                    return false;
+                }
+            }
+        }
+        if !msg.data.is_empty() {
+            self.state.updated();
+        }
+        true
+    }
+
+    /// Kraken sends prices and sizes as JSON numbers; fix them to the pair's precision
+    fn decimal(&self, value: f64, precision: usize) -> Option<Decimal> {
+        format!("{:.*}", precision, value).parse().ok()
+    }
+
+    pub fn stop(&self) {
+        *self.running.write() = false;
+    }
+}
+
+impl SpotBook for KrakenBookService {
+    fn venue(&self) -> &str {
+        "kraken"
+    }
+
+    fn state(&self) -> &BookState {
+        &self.state
+    }
+}
+
+/// Kraken's checksum input: the top 10 asks (ascending) then bids (descending), each
+/// as price and qty with the decimal point and leading zeros removed
+fn checksum_input(book: &OrderBook) -> String {
+    let digits = |d: &Decimal| d.to_string().replace('.', "").trim_start_matches('0').to_string();
+    let asks = book.asks.iter().take(CHECKSUM_LEVELS);
+    let bids = book.bids.iter().rev().take(CHECKSUM_LEVELS);
+    asks.chain(bids).map(|(price, qty)| digits(price) + &digits(qty)).collect()
+}
+
+fn checksum(book: &OrderBook) -> u32 {
+    crc32fast::hash(checksum_input(book).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(depth: usize) -> KrakenBookService {
+        KrakenBookService::new(KrakenConfig {
+            ws_url: String::new(),
+            symbol: "BTC/USD".to_string(),
+            depth,
+            price_precision: 1,
+            qty_precision: 8,
+        })
+    }
+
+    fn book_msg(msg_type: &str, bids: &str, asks: &str, checksum: u32) -> String {
+        format!(
+            r#"{{"channel":"book","type":"{}","data":[{{"symbol":"BTC/USD","bids":[{}],"asks":[{}],"checksum":{}}}]}}"#,
+            msg_type, bids, asks, checksum
+        )
+    }
+
+    #[test]
+    fn test_snapshot_updates_and_checksum() {
+        let service = service(2);
+        let bids = r#"{"price":99999.5,"qty":0.5},{"price":99999.0,"qty":1.25}"#;
+        let asks = r#"{"price":100000.5,"qty":0.1},{"price":100001.0,"qty":2.0}"#;
+        {
+            let mut book = OrderBook::new();
+            for (price, qty, is_bid) in [("99999.5", "0.50000000", true), ("99999.0", "1.25000000", true), ("100000.5", "0.10000000", false), ("100001.0", "2.00000000", false)] {
+                book.set_level(is_bid, price.parse().unwrap(), qty.parse().unwrap());
+            }
+            assert_eq!(checksum_input(&book), "100000510000000100001020000000099999550000000999990125000000");
+        }
+
+        // A wrong checksum on the snapshot is rejected
+        assert!(!service.handle_message(&book_msg("snapshot", bids, asks, 1)));
+        assert_eq!(service.get_mid_price(), None);
+
+        let mut expected = OrderBook::new();
+        expected.set_level(true, Decimal::new(999995, 1), Decimal::new(50000000, 8));
+        expected.set_level(true, Decimal::new(999990, 1), Decimal::new(125000000, 8));
+        expected.set_level(false, Decimal::new(1000005, 1), Decimal::new(10000000, 8));
+        expected.set_level(false, Decimal::new(1000010, 1), Decimal::new(200000000, 8));
+        assert!(service.handle_message(&book_msg("snapshot", bids, asks, checksum(&expected))));
+        assert_eq!(service.get_mid_price(), Some(100_000.0));
+
+        // A better bid pushes 99999.0 out of the subscribed depth
+        expected.set_level(true, Decimal::new(999998, 1), Decimal::new(100000000, 8));
+        expected.truncate(2);
+        assert!(service.handle_message(&book_msg("update", r#"{"price":99999.8,"qty":1.0}"#, "", checksum(&expected))));
+        assert_eq!(service.get_mid_price(), Some(100_000.15));
+        assert_eq!(service.state().read().bids.len(), 2);
+
+        assert!(!service.handle_message(&book_msg("update", r#"{"price":99999.5,"qty":0.0}"#, "", 0)));
+        assert_eq!(service.get_mid_price(), None);
+    }
+}
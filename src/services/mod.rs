@@ -1,17 +1,51 @@
+mod auto_trader;
+mod backtest;
 mod binance;
+mod book;
 mod chainlink;
+mod clock;
 mod clob;
+mod coinbase;
+mod fair_value;
+mod fees;
+mod flow;
 mod gamma;
+mod index;
+mod kraken;
+mod orders;
+mod paper;
 mod polymarket;
+mod positions;
 mod price_scraper;
+mod recorder;
+mod replay;
+mod scoring;
+mod settlement;
 mod signal;
+mod signing;
+mod strategy;
+mod sweep;
+#[cfg(test)]
+mod testing;
 mod trade;
+mod user_channel;
 
+pub use auto_trader::AutoTrader;
+pub use backtest::{run_backtest, BacktestOptions};
 pub use binance::BinanceBookService;
+pub use book::{SpotBook, SpotFeeds};
 pub use chainlink::ChainlinkService;
-pub use clob::{ClobClient, ClobCredentials, OrderRequest, OrderResponse};
-pub use gamma::{GammaClient, MarketTokens};
-pub use polymarket::PolymarketService;
-pub use price_scraper::fetch_price_to_beat;
-pub use signal::SignalService;
-pub use trade::{ActionLogEntry, TradeService};
+pub use clob::{ClobClient, ClobCredentials};
+pub use coinbase::CoinbaseBookService;
+pub use index::IndexService;
+pub use kraken::KrakenBookService;
+pub use orders::OrderStatus;
+pub use polymarket::{ActiveMarket, PolymarketService};
+pub use replay::{load_recordings, RecordedDay, ReplayEngine, ReplaySpeed};
+pub use recorder::FrameRecorder;
+pub use scoring::{calibration, format_calibration, read_scores, score_files, SignalScorer};
+pub use settlement::SettlementTracker;
+pub use signal::{SignalService, SignalState};
+pub use sweep::{default_jobs, run_sweep, Objective, ParamRange, SearchMode, SweepOptions};
+pub use trade::{RiskRejected, TradeService};
+pub use user_channel::UserChannelService;
@@ -0,0 +1,307 @@
+use parking_lot::RwLock;
+use std::collections::HashMap;
+
+use crate::events::FillInfo;
+
+/// Keep at most this many orders; the oldest closed ones are dropped first
+const ORDER_STORE_CAP: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    Live,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn is_open(&self) -> bool {
+        matches!(self, OrderStatus::Live | OrderStatus::PartiallyFilled)
+    }
+}
+
+impl std::fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderStatus::Live => write!(f, "LIVE"),
+            OrderStatus::PartiallyFilled => write!(f, "PARTIAL"),
+            OrderStatus::Filled => write!(f, "FILLED"),
+            OrderStatus::Cancelled => write!(f, "CANCELLED"),
+        }
+    }
+}
+
+/// Latest known state of one of our orders
+#[derive(Debug, Clone)]
+pub struct OrderState {
+    pub order_id: String,
+    pub client_order_id: Option<String>,
+    pub asset_id: String,
+    pub market: String,
+    pub side: String, // "BUY" or "SELL"
+    pub outcome: String,
+    pub price: f64,
+    pub original_size: f64,
+    pub size_matched: f64,
+    pub status: OrderStatus,
+    pub fills: Vec<FillInfo>,
+    pub created_ms: i64,
+    pub updated_ms: i64,
+}
+
+impl OrderState {
+    pub fn new(order_id: &str, asset_id: &str, side: &str, price: f64, original_size: f64) -> Self {
+        let now = chrono::Utc::now().timestamp_millis();
+        Self {
+            order_id: order_id.to_string(),
+            client_order_id: None,
+            asset_id: asset_id.to_string(),
+            market: String::new(),
+            side: side.to_string(),
+            outcome: String::new(),
+            price,
+            original_size,
+            size_matched: 0.0,
+            status: OrderStatus::Live,
+            fills: Vec::new(),
+            created_ms: now,
+            updated_ms: now,
+        }
+    }
+
+    pub fn remaining_size(&self) -> f64 {
+        (self.original_size - self.size_matched).max(0.0)
+    }
+
+    fn refresh_status(&mut self) {
+        if self.status == OrderStatus::Cancelled {
+            return;
+        }
+        self.status = if self.original_size > 0.0 && self.size_matched >= self.original_size {
+            OrderStatus::Filled
+        } else if self.size_matched > 0.0 {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Live
+        };
+    }
+}
+
+/// Order-state store shared by the trade service (placements) and the user channel (updates, fills)
+#[derive(Debug, Default)]
+pub struct OrderStore {
+    orders: RwLock<HashMap<String, OrderState>>,
+}
+
+impl OrderStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_known(&self, order_id: &str) -> bool {
+        self.orders.read().contains_key(order_id)
+    }
+
+    pub fn get(&self, order_id: &str) -> Option<OrderState> {
+        self.orders.read().get(order_id).cloned()
+    }
+
+    /// Find one of our orders by the client order id it was placed with
+    pub fn get_by_client_id(&self, client_order_id: &str) -> Option<OrderState> {
+        self.orders
+            .read()
+            .values()
+            .find(|o| o.client_order_id.as_deref() == Some(client_order_id))
+            .cloned()
+    }
+
+    /// Record an order we just placed. If the user channel already reported it,
+    /// only the client order id is attached.
+    pub fn register(&self, order: OrderState) {
+        let mut orders = self.orders.write();
+        match orders.get_mut(&order.order_id) {
+            Some(existing) => {
+                if existing.client_order_id.is_none() {
+                    existing.client_order_id = order.client_order_id;
+                }
+            }
+            None => {
+                orders.insert(order.order_id.clone(), order);
+                Self::prune(&mut orders);
+            }
+        }
+    }
+
+    /// Merge an order update from the user channel, keeping locally known fields
+    pub fn upsert(&self, update: OrderState, cancelled: bool) -> OrderState {
+        let mut orders = self.orders.write();
+        let order = orders
+            .entry(update.order_id.clone())
+            .or_insert_with(|| update.clone());
+
+        if order.market.is_empty() {
+            order.market = update.market;
+        }
+        if order.outcome.is_empty() {
+            order.outcome = update.outcome;
+        }
+        if update.original_size > 0.0 {
+            order.original_size = update.original_size;
+        }
+        order.size_matched = order.size_matched.max(update.size_matched);
+        order.updated_ms = update.updated_ms;
+        if cancelled {
+            order.status = OrderStatus::Cancelled;
+        }
+        order.refresh_status();
+
+        let order = order.clone();
+        Self::prune(&mut orders);
+        order
+    }
+
+    /// Mark an order cancelled; None if we never saw it
+    pub fn mark_cancelled(&self, order_id: &str) -> Option<OrderState> {
+        let mut orders = self.orders.write();
+        let order = orders.get_mut(order_id)?;
+        order.status = OrderStatus::Cancelled;
+        order.updated_ms = chrono::Utc::now().timestamp_millis();
+        Some(order.clone())
+    }
+
+    /// Attach a fill to its order. Returns the updated order and whether the fill
+    /// changed anything (new trade, or a status transition such as MATCHED -> CONFIRMED).
+    pub fn apply_fill(&self, order_id: &str, fill: FillInfo) -> Option<(OrderState, bool)> {
+        let mut orders = self.orders.write();
+        let order = orders.get_mut(order_id)?;
+
+        let existing = order
+            .fills
+            .iter_mut()
+            .find(|f| f.trade_id.is_some() && f.trade_id == fill.trade_id);
+        let changed = match existing {
+            Some(existing) if existing.status == fill.status => false,
+            Some(existing) => {
+                existing.status = fill.status.clone();
+                true
+            }
+            None => {
+                order.fills.push(fill.clone());
+                true
+            }
+        };
+
+        // Fills that failed on chain don't count towards the matched size
+        let matched: f64 = order
+            .fills
+            .iter()
+            .filter(|f| f.status.as_deref() != Some("FAILED"))
+            .map(|f| f.size)
+            .sum();
+        order.size_matched = order.size_matched.max(matched);
+        order.updated_ms = fill.t_fill_ms.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+        order.refresh_status();
+
+        Some((order.clone(), changed))
+    }
+
+    pub fn open_orders(&self) -> Vec<OrderState> {
+        let mut open: Vec<OrderState> = self
+            .orders
+            .read()
+            .values()
+            .filter(|o| o.status.is_open())
+            .cloned()
+            .collect();
+        open.sort_by_key(|o| std::cmp::Reverse(o.created_ms));
+        open
+    }
+
+    /// Most recently updated orders first
+    pub fn recent(&self, limit: usize) -> Vec<OrderState> {
+        let mut orders: Vec<OrderState> = self.orders.read().values().cloned().collect();
+        orders.sort_by_key(|o| std::cmp::Reverse(o.updated_ms));
+        orders.truncate(limit);
+        orders
+    }
+
+    fn prune(orders: &mut HashMap<String, OrderState>) {
+        if orders.len() <= ORDER_STORE_CAP {
+            return;
+        }
+        let mut closed: Vec<(i64, String)> = orders
+            .values()
+            .filter(|o| !o.status.is_open())
+            .map(|o| (o.updated_ms, o.order_id.clone()))
+            .collect();
+        closed.sort();
+        for (_, id) in closed.into_iter().take(orders.len() - ORDER_STORE_CAP) {
+            orders.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(trade_id: &str, size: f64, status: &str) -> FillInfo {
+        FillInfo {
+            price: 0.55,
+            size,
+            trade_id: Some(trade_id.to_string()),
+            order_id: Some("0xabc".to_string()),
+            status: Some(status.to_string()),
+            t_fill_ms: Some(1_000),
+            liquidity: None,
+            fee: None,
+        }
+    }
+
+    #[test]
+    fn test_fills_update_status_and_dedupe() {
+        let store = OrderStore::new();
+        let mut order = OrderState::new("0xabc", "UP", "BUY", 0.55, 10.0);
+        order.client_order_id = Some("client-1".to_string());
+        store.register(order);
+
+        let (order, changed) = store.apply_fill("0xabc", fill("t1", 4.0, "MATCHED")).unwrap();
+        assert!(changed);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.size_matched, 4.0);
+
+        // Same trade seen again while settling on chain
+        let (_, changed) = store.apply_fill("0xabc", fill("t1", 4.0, "MATCHED")).unwrap();
+        assert!(!changed);
+        let (order, changed) = store.apply_fill("0xabc", fill("t1", 4.0, "CONFIRMED")).unwrap();
+        assert!(changed);
+        assert_eq!(order.fills.len(), 1);
+
+        let (order, _) = store.apply_fill("0xabc", fill("t2", 6.0, "MATCHED")).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert!(store.open_orders().is_empty());
+        assert_eq!(order.client_order_id.as_deref(), Some("client-1"));
+
+        assert!(store.apply_fill("0xunknown", fill("t3", 1.0, "MATCHED")).is_none());
+    }
+
+    #[test]
+    fn test_upsert_keeps_client_id_and_cancels() {
+        let store = OrderStore::new();
+        let mut placed = OrderState::new("0xabc", "UP", "BUY", 0.55, 10.0);
+        placed.client_order_id = Some("client-1".to_string());
+        store.register(placed);
+
+        let mut update = OrderState::new("0xabc", "UP", "BUY", 0.55, 10.0);
+        update.market = "0xcond".to_string();
+        update.size_matched = 2.0;
+        let order = store.upsert(update.clone(), false);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.market, "0xcond");
+
+        let order = store.upsert(update, true);
+        assert_eq!(order.status, OrderStatus::Cancelled);
+        assert_eq!(order.client_order_id.as_deref(), Some("client-1"));
+        assert!(store.open_orders().is_empty());
+    }
+}
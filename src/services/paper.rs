@@ -0,0 +1,367 @@
+use parking_lot::RwLock;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::events::FillInfo;
+use super::polymarket::{BookSide, TokenBook};
+
+/// An order handed to the paper exchange
+#[derive(Debug, Clone)]
+pub struct PaperOrder {
+    pub order_id: String,
+    pub asset_id: String,
+    pub side: String, // "BUY" or "SELL"
+    pub price: f64,
+    pub size: f64,
+    pub order_type: String, // "GTC", "GTD", "FAK", "FOK"
+    pub post_only: bool,
+}
+
+impl PaperOrder {
+    fn is_buy(&self) -> bool {
+        self.side.eq_ignore_ascii_case("BUY")
+    }
+
+    /// Book side we take liquidity from
+    fn contra_side(&self) -> BookSide {
+        if self.is_buy() {
+            BookSide::Ask
+        } else {
+            BookSide::Bid
+        }
+    }
+
+    /// Book side we rest on
+    fn own_side(&self) -> BookSide {
+        if self.is_buy() {
+            BookSide::Bid
+        } else {
+            BookSide::Ask
+        }
+    }
+
+    /// Whether a level at `price` on the contra side is within our limit
+    fn crosses(&self, price: f64) -> bool {
+        if self.is_buy() {
+            price <= self.price + 1e-9
+        } else {
+            price >= self.price - 1e-9
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PaperResult {
+    Rejected(String),
+    Accepted { fills: Vec<FillInfo>, resting: bool },
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    order: PaperOrder,
+    remaining: f64,
+    queue_ahead: f64, // displayed size at our price that was there before us
+    level_size: f64,  // size at our price as of the last book update
+}
+
+/// Simulated matching against the live Polymarket book for dry-run mode.
+///
+/// Marketable orders take displayed liquidity level by level up to their limit. Liquidity
+/// we took stays consumed until the exchange reports a new size for that level, so repeated
+/// orders can't fill against the same shares twice. Resting orders join the back of the
+/// queue at their price and fill when the contra side trades through them, or when size
+/// ahead of them at the touch is worked off.
+#[derive(Debug, Default)]
+pub struct PaperExchange {
+    resting: RwLock<Vec<RestingOrder>>,
+    taken: RwLock<HashMap<(String, String), (f64, f64)>>, // (asset, "ASK@0.62") -> (book size, taken)
+}
+
+fn to_f64(d: Decimal) -> f64 {
+    d.to_f64().unwrap_or(0.0)
+}
+
+fn level_key(side: BookSide, price: f64) -> String {
+    format!("{:?}@{:.4}", side, price)
+}
+
+fn paper_fill(order_id: &str, price: f64, size: f64, liquidity: &str) -> FillInfo {
+    FillInfo {
+        price,
+        size,
+        trade_id: Some(format!("paper-{}", Uuid::new_v4())),
+        order_id: Some(order_id.to_string()),
+        status: Some("MATCHED".to_string()),
+        t_fill_ms: Some(chrono::Utc::now().timestamp_millis()),
+        liquidity: Some(liquidity.to_string()),
+        fee: None,
+    }
+}
+
+impl PaperExchange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match a new order against `book`, resting any GTC/GTD remainder
+    pub fn submit(&self, order: &PaperOrder, book: &TokenBook) -> PaperResult {
+        let contra = order.contra_side();
+        let available = self.available(&order.asset_id, contra, book, order);
+
+        if order.post_only && available.iter().any(|(_, size)| *size > 0.0) {
+            return PaperResult::Rejected("post-only order would cross the book".to_string());
+        }
+        let total: f64 = available.iter().map(|(_, size)| size).sum();
+        if order.order_type == "FOK" && total + 1e-9 < order.size {
+            return PaperResult::Rejected("FOK order can't be filled in full".to_string());
+        }
+
+        let mut remaining = order.size;
+        let mut fills = Vec::new();
+        for (price, size) in available {
+            if remaining <= 1e-9 {
+                break;
+            }
+            let take = remaining.min(size);
+            if take <= 0.0 {
+                continue;
+            }
+            self.take(&order.asset_id, contra, book, price, take);
+            fills.push(paper_fill(&order.order_id, price, take, "TAKER"));
+            remaining -= take;
+        }
+
+        let resting = remaining > 1e-9 && matches!(order.order_type.as_str(), "GTC" | "GTD");
+        if resting {
+            let level_size = book
+                .size_at(order.own_side(), Decimal::try_from(order.price).unwrap_or_default())
+                .map(to_f64)
+                .unwrap_or(0.0);
+            self.resting.write().push(RestingOrder {
+                order: order.clone(),
+                remaining,
+                queue_ahead: level_size,
+                level_size,
+            });
+        }
+        PaperResult::Accepted { fills, resting }
+    }
+
+    /// Re-check resting orders on `asset_id` after its book changed; returns the new fills
+    pub fn on_book(&self, asset_id: &str, book: &TokenBook) -> Vec<FillInfo> {
+        let mut fills = Vec::new();
+        let mut resting = self.resting.write();
+
+        for rest in resting.iter_mut().filter(|r| r.order.asset_id == asset_id) {
+            let order = rest.order.clone();
+            let own = order.own_side();
+
+            // Contra side moved through our price: we're matched as the maker at our price
+            let available: f64 = self
+                .available(asset_id, order.contra_side(), book, &order)
+                .iter()
+                .map(|(_, size)| size)
+                .sum();
+            if available > 0.0 {
+                let take = rest.remaining.min(available);
+                self.take_through(asset_id, order.contra_side(), book, &order, take);
+                fills.push(paper_fill(&order.order_id, order.price, take, "MAKER"));
+                rest.remaining -= take;
+                continue;
+            }
+
+            // Size leaving our level while we're at the touch is treated as trading
+            let level = book
+                .size_at(own, Decimal::try_from(order.price).unwrap_or_default())
+                .map(to_f64)
+                .unwrap_or(0.0);
+            let best = match own {
+                BookSide::Bid => book.best_bid(),
+                BookSide::Ask => book.best_ask(),
+            }
+            .map(|(p, _)| to_f64(p));
+            let at_touch = match (own, best) {
+                (_, None) => true,
+                (BookSide::Bid, Some(best)) => order.price >= best - 1e-9,
+                (BookSide::Ask, Some(best)) => order.price <= best + 1e-9,
+            };
+            if at_touch && level < rest.level_size {
+                let traded = rest.level_size - level;
+                let through = traded - rest.queue_ahead;
+                rest.queue_ahead = (rest.queue_ahead - traded).max(0.0);
+                if through > 1e-9 {
+                    let take = rest.remaining.min(through);
+                    fills.push(paper_fill(&order.order_id, order.price, take, "MAKER"));
+                    rest.remaining -= take;
+                }
+            } else if level < rest.queue_ahead {
+                // Cancels ahead of us still move us up
+                rest.queue_ahead = level;
+            }
+            rest.level_size = level;
+        }
+
+        resting.retain(|r| r.remaining > 1e-9);
+        fills
+    }
+
+    /// Drop a resting order; true if it was resting
+    pub fn cancel(&self, order_id: &str) -> bool {
+        let mut resting = self.resting.write();
+        let before = resting.len();
+        resting.retain(|r| r.order.order_id != order_id);
+        resting.len() != before
+    }
+
+    /// Contra levels within the order's limit, net of liquidity we already took
+    fn available(&self, asset_id: &str, side: BookSide, book: &TokenBook, order: &PaperOrder) -> Vec<(f64, f64)> {
+        let taken = self.taken.read();
+        book.levels(side)
+            .map(|(p, q)| (to_f64(*p), to_f64(*q)))
+            .take_while(|(price, _)| order.crosses(*price))
+            .map(|(price, size)| {
+                let used = match taken.get(&(asset_id.to_string(), level_key(side, price))) {
+                    // Only while the exchange still shows the size we took from
+                    Some((book_size, used)) if (*book_size - size).abs() < 1e-9 => *used,
+                    _ => 0.0,
+                };
+                (price, (size - used).max(0.0))
+            })
+            .collect()
+    }
+
+    fn take(&self, asset_id: &str, side: BookSide, book: &TokenBook, price: f64, size: f64) {
+        let book_size = book
+            .size_at(side, Decimal::try_from(price).unwrap_or_default())
+            .map(to_f64)
+            .unwrap_or(0.0);
+        let mut taken = self.taken.write();
+        let entry = taken
+            .entry((asset_id.to_string(), level_key(side, price)))
+            .or_insert((book_size, 0.0));
+        if (entry.0 - book_size).abs() > 1e-9 {
+            *entry = (book_size, 0.0);
+        }
+        entry.1 += size;
+    }
+
+    /// Consume `size` from the best contra levels within the order's limit
+    fn take_through(&self, asset_id: &str, side: BookSide, book: &TokenBook, order: &PaperOrder, size: f64) {
+        let mut remaining = size;
+        for (price, available) in self.available(asset_id, side, book, order) {
+            if remaining <= 1e-9 {
+                break;
+            }
+            let take = remaining.min(available);
+            if take > 0.0 {
+                self.take(asset_id, side, book, price, take);
+                remaining -= take;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> TokenBook {
+        let mut book = TokenBook::default();
+        for (p, q) in bids {
+            book.apply_level(BookSide::Bid, p, q);
+        }
+        for (p, q) in asks {
+            book.apply_level(BookSide::Ask, p, q);
+        }
+        book
+    }
+
+    fn order(id: &str, side: &str, price: f64, size: f64, order_type: &str) -> PaperOrder {
+        PaperOrder {
+            order_id: id.to_string(),
+            asset_id: "UP".to_string(),
+            side: side.to_string(),
+            price,
+            size,
+            order_type: order_type.to_string(),
+            post_only: false,
+        }
+    }
+
+    fn filled(result: &PaperResult) -> Vec<(f64, f64)> {
+        match result {
+            PaperResult::Accepted { fills, .. } => fills.iter().map(|f| (f.price, f.size)).collect(),
+            PaperResult::Rejected(reason) => panic!("rejected: {}", reason),
+        }
+    }
+
+    #[test]
+    fn test_taker_sweeps_levels_within_limit() {
+        let exchange = PaperExchange::new();
+        let book = book(&[("0.50", "100")], &[("0.52", "10"), ("0.53", "5"), ("0.60", "50")]);
+
+        let result = exchange.submit(&order("a", "BUY", 0.55, 20.0, "FAK"), &book);
+        assert_eq!(filled(&result), vec![(0.52, 10.0), (0.53, 5.0)]);
+        assert!(matches!(result, PaperResult::Accepted { resting: false, .. }));
+
+        // The displayed size at 0.52/0.53 is used up until the book reports it again
+        let result = exchange.submit(&order("b", "BUY", 0.55, 5.0, "FAK"), &book);
+        assert!(filled(&result).is_empty());
+
+        let result = exchange.submit(&order("c", "BUY", 0.55, 5.0, "FOK"), &book);
+        assert!(matches!(result, PaperResult::Rejected(_)));
+
+        // Sells take the bids
+        let result = exchange.submit(&order("d", "SELL", 0.45, 30.0, "FAK"), &book);
+        assert_eq!(filled(&result), vec![(0.50, 30.0)]);
+    }
+
+    #[test]
+    fn test_post_only_rejects_crossing() {
+        let exchange = PaperExchange::new();
+        let book = book(&[("0.50", "100")], &[("0.52", "10")]);
+
+        let mut crossing = order("a", "BUY", 0.52, 5.0, "GTC");
+        crossing.post_only = true;
+        assert!(matches!(exchange.submit(&crossing, &book), PaperResult::Rejected(_)));
+
+        let mut passive = order("b", "BUY", 0.51, 5.0, "GTC");
+        passive.post_only = true;
+        let result = exchange.submit(&passive, &book);
+        assert!(matches!(result, PaperResult::Accepted { resting: true, .. }));
+        assert!(filled(&result).is_empty());
+    }
+
+    #[test]
+    fn test_resting_order_queue_and_trade_through() {
+        let exchange = PaperExchange::new();
+        let book1 = book(&[("0.50", "30")], &[("0.52", "10")]);
+
+        // Joins 30 shares already bid at 0.50
+        let result = exchange.submit(&order("a", "BUY", 0.50, 20.0, "GTC"), &book1);
+        assert!(matches!(result, PaperResult::Accepted { resting: true, .. }));
+
+        // 25 traded at the touch: still 5 ahead of us
+        let book2 = book(&[("0.50", "5")], &[("0.52", "10")]);
+        assert!(exchange.on_book("UP", &book2).is_empty());
+
+        // 7 join behind us, then 10 trade: the 5 ahead go first, then 5 of ours
+        let book3 = book(&[("0.50", "12")], &[("0.52", "10")]);
+        assert!(exchange.on_book("UP", &book3).is_empty());
+        let book4 = book(&[("0.50", "2")], &[("0.52", "10")]);
+        let fills = exchange.on_book("UP", &book4);
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].price, fills[0].size), (0.50, 5.0));
+
+        // Ask comes down through our price: we're filled at our limit
+        let book5 = book(&[("0.49", "40")], &[("0.48", "100")]);
+        let fills = exchange.on_book("UP", &book5);
+        assert_eq!((fills[0].price, fills[0].size), (0.50, 15.0));
+        assert_eq!(fills[0].order_id.as_deref(), Some("a"));
+        assert_eq!(fills[0].liquidity.as_deref(), Some("MAKER"));
+        assert!(exchange.on_book("UP", &book5).is_empty());
+        assert!(!exchange.cancel("a"));
+    }
+}
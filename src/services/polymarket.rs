@@ -1,14 +1,20 @@
 use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::RwLock;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::broadcast;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 use crate::config::PolymarketConfig;
-use crate::events::PolymarketQuote;
+use crate::events::{PolymarketQuote, TradeSide};
+use super::clock::Clock;
+use super::recorder::{FrameRecorder, FrameSource};
+use super::fees::FeeModel;
 use super::gamma::{GammaClient, MarketTokens};
 
 #[derive(Debug, Clone, Serialize)]
@@ -36,6 +42,7 @@ struct PriceChangeMessage {
 struct PriceChange {
     asset_id: String,
     price: Option<String>,
+    size: Option<String>,
     side: Option<String>,
     best_bid: Option<String>,
     best_ask: Option<String>,
@@ -47,6 +54,132 @@ struct OrderBookLevel {
     size: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// Full price-level book for one outcome token
+#[derive(Debug, Clone, Default)]
+pub struct TokenBook {
+    bids: BTreeMap<Decimal, Decimal>, // price -> size (best = highest)
+    asks: BTreeMap<Decimal, Decimal>, // price -> size (best = lowest)
+}
+
+impl TokenBook {
+    fn apply_snapshot(&mut self, bids: &[OrderBookLevel], asks: &[OrderBookLevel]) {
+        self.bids.clear();
+        self.asks.clear();
+        for level in bids {
+            self.apply_level(BookSide::Bid, &level.price, &level.size);
+        }
+        for level in asks {
+            self.apply_level(BookSide::Ask, &level.price, &level.size);
+        }
+    }
+
+    /// Set the size at a price level; size 0 removes the level
+    pub(crate) fn apply_level(&mut self, side: BookSide, price: &str, size: &str) {
+        let (Ok(price), Ok(size)) = (price.parse::<Decimal>(), size.parse::<Decimal>()) else {
+            return;
+        };
+        let levels = match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        };
+        if size.is_zero() {
+            levels.remove(&price);
+        } else {
+            levels.insert(price, size);
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<(Decimal, Decimal)> {
+        self.bids.iter().next_back().map(|(p, q)| (*p, *q))
+    }
+
+    pub fn best_ask(&self) -> Option<(Decimal, Decimal)> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+
+    pub fn size_at(&self, side: BookSide, price: Decimal) -> Option<Decimal> {
+        match side {
+            BookSide::Bid => self.bids.get(&price).copied(),
+            BookSide::Ask => self.asks.get(&price).copied(),
+        }
+    }
+
+    /// Levels from best to worst
+    pub fn levels(&self, side: BookSide) -> Box<dyn Iterator<Item = (&Decimal, &Decimal)> + '_> {
+        match side {
+            BookSide::Bid => Box::new(self.bids.iter().rev()),
+            BookSide::Ask => Box::new(self.asks.iter()),
+        }
+    }
+
+    /// Size resting on `side` at prices no worse than `limit` for the taker
+    /// (asks at or below the limit, bids at or above it)
+    pub fn depth_within(&self, side: BookSide, limit: Decimal) -> Decimal {
+        self.levels(side)
+            .take_while(|(price, _)| match side {
+                BookSide::Bid => **price >= limit,
+                BookSide::Ask => **price <= limit,
+            })
+            .map(|(_, size)| *size)
+            .sum()
+    }
+
+    /// Average fill price for taking `size` shares from `side`; None if the book is too thin
+    pub fn vwap(&self, side: BookSide, size: Decimal) -> Option<Decimal> {
+        if size <= Decimal::ZERO {
+            return None;
+        }
+        let mut remaining = size;
+        let mut notional = Decimal::ZERO;
+        for (price, level_size) in self.levels(side) {
+            let take = remaining.min(*level_size);
+            notional += take * *price;
+            remaining -= take;
+            if remaining.is_zero() {
+                return Some(notional / size);
+            }
+        }
+        None
+    }
+}
+
+/// Best (price, size) on one side. The exchange's best_bid/best_ask hint wins
+/// over our own book for the price; size comes from the book at that price.
+fn top_of_book(book: &TokenBook, side: BookSide, hint: Option<&str>) -> Option<(f64, Option<f64>)> {
+    let best = match side {
+        BookSide::Bid => book.best_bid(),
+        BookSide::Ask => book.best_ask(),
+    };
+    match hint.and_then(|h| h.parse::<Decimal>().ok()) {
+        // Price 0 (bid) or 1 (ask) means the side is empty
+        Some(price) if price.is_zero() || price == Decimal::ONE => None,
+        Some(price) => Some((price.to_f64()?, book.size_at(side, price).and_then(|q| q.to_f64()))),
+        None => best.and_then(|(p, q)| Some((p.to_f64()?, q.to_f64()))),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct TokenBooks {
+    up: TokenBook,
+    down: TokenBook,
+}
+
+impl TokenBooks {
+    fn get_mut(&mut self, is_up: bool) -> &mut TokenBook {
+        if is_up { &mut self.up } else { &mut self.down }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct QuoteState {
     pub yes_bid: Option<f64>,
@@ -60,7 +193,33 @@ pub struct QuoteState {
     pub last_update_ms: i64,
 }
 
-#[derive(Debug, Clone, Default)]
+impl QuoteState {
+    fn set_top(&mut self, is_up: bool, bid: Option<(f64, Option<f64>)>, ask: Option<(f64, Option<f64>)>) {
+        let (bid_px, bid_size, ask_px, ask_size) = if is_up {
+            (&mut self.yes_bid, &mut self.yes_bid_size, &mut self.yes_ask, &mut self.yes_ask_size)
+        } else {
+            (&mut self.no_bid, &mut self.no_bid_size, &mut self.no_ask, &mut self.no_ask_size)
+        };
+        *bid_px = bid.map(|(p, _)| p);
+        *bid_size = bid.and_then(|(_, q)| q);
+        *ask_px = ask.map(|(p, _)| p);
+        *ask_size = ask.and_then(|(_, q)| q);
+    }
+
+    fn quote(&self, token_id: String, is_up: bool, t_recv_ms: i64) -> PolymarketQuote {
+        PolymarketQuote {
+            token_id,
+            side: if is_up { "UP".to_string() } else { "DOWN".to_string() },
+            best_bid: if is_up { self.yes_bid } else { self.no_bid },
+            best_bid_size: if is_up { self.yes_bid_size } else { self.no_bid_size },
+            best_ask: if is_up { self.yes_ask } else { self.no_ask },
+            best_ask_size: if is_up { self.yes_ask_size } else { self.no_ask_size },
+            t_recv_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ActiveMarket {
     pub up_token_id: String,
     pub down_token_id: String,
@@ -70,6 +229,14 @@ pub struct ActiveMarket {
     pub start_time: String,      // When window starts (ISO8601)
     pub end_date: String,        // When window ends (ISO8601)
     pub target_price: Option<f64>, // BTC price at window start
+    pub fee_rate_bps: Option<u64>, // Taker fee rate fetched from the CLOB
+}
+
+impl ActiveMarket {
+    /// When the window ends, in epoch ms
+    pub fn end_ms(&self) -> Option<i64> {
+        chrono::DateTime::parse_from_rfc3339(&self.end_date).ok().map(|end| end.timestamp_millis())
+    }
 }
 
 pub struct PolymarketService {
@@ -77,21 +244,52 @@ pub struct PolymarketService {
     gamma_client: GammaClient,
     active_market: Arc<RwLock<ActiveMarket>>,
     quote_state: Arc<RwLock<QuoteState>>,
+    books: Arc<RwLock<TokenBooks>>,
     update_tx: broadcast::Sender<PolymarketQuote>,
     running: Arc<RwLock<bool>>,
+    clock: Clock,
+    recorder: Option<Arc<FrameRecorder>>,
 }
 
 impl PolymarketService {
     pub fn new(config: PolymarketConfig) -> Self {
         let (tx, _) = broadcast::channel(1000);
-        let gamma_client = GammaClient::new(config.btc_15m_event_id.clone());
+        let gamma_client = GammaClient::new(&config.gamma_url);
         Self {
             config,
             gamma_client,
             active_market: Arc::new(RwLock::new(ActiveMarket::default())),
             quote_state: Arc::new(RwLock::new(QuoteState::default())),
+            books: Arc::new(RwLock::new(TokenBooks::default())),
             update_tx: tx,
             running: Arc::new(RwLock::new(false)),
+            clock: Clock::system(),
+            recorder: None,
+        }
+    }
+
+    /// Use `clock` for quote timestamps, staleness and time left (replays)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Record raw market-channel frames and active market changes
+    pub fn with_recorder(mut self, recorder: Option<Arc<FrameRecorder>>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
+    fn record_market(&self) {
+        if let Some(recorder) = &self.recorder {
+            match serde_json::to_string(&*self.active_market.read()) {
+                Ok(text) => recorder.record(FrameSource::Market, self.clock.now_ms(), &text),
+                Err(e) => tracing::error!("Failed to serialize active market: {:?}", e),
+            }
         }
     }
 
@@ -100,6 +298,12 @@ impl PolymarketService {
         let tokens = self.gamma_client.get_current_btc_15m_market().await?;
 
         let mut market = self.active_market.write();
+        if market.up_token_id != tokens.up_token_id {
+            *self.books.write() = TokenBooks::default();
+        }
+        if market.condition_id != tokens.condition_id {
+            market.fee_rate_bps = None;
+        }
         market.up_token_id = tokens.up_token_id.clone();
         market.down_token_id = tokens.down_token_id.clone();
         market.condition_id = tokens.condition_id.clone();
@@ -115,6 +319,8 @@ impl PolymarketService {
             tokens.start_time,
             tokens.end_date
         );
+        drop(market);
+        self.record_market();
 
         Ok(tokens)
     }
@@ -125,6 +331,8 @@ impl PolymarketService {
         if market.target_price.is_none() {
             market.target_price = Some(price);
             tracing::info!("Target price set: ${:.2}", price);
+            drop(market);
+            self.record_market();
         }
     }
 
@@ -133,12 +341,14 @@ impl PolymarketService {
         let mut market = self.active_market.write();
         market.target_price = Some(price);
         tracing::info!("Target price set (from scraper): ${:.2}", price);
+        drop(market);
+        self.record_market();
     }
 
     /// Clear the target price (for new window)
     pub fn clear_target_price(&self) {
-        let mut market = self.active_market.write();
-        market.target_price = None;
+        self.active_market.write().target_price = None;
+        self.record_market();
     }
 
     /// Fetch the price to beat from the Polymarket website
@@ -153,7 +363,7 @@ impl PolymarketService {
             format!("btc-updown-15m-{}", (now / 900) * 900)
         };
 
-        match super::price_scraper::fetch_price_to_beat(&slug).await {
+        match super::price_scraper::fetch_price_to_beat(&self.config.event_url, &slug).await {
             Ok(Some(data)) => {
                 tracing::info!("Scraped price to beat: ${:.2} from {}", data.open_price, slug);
                 Some(data.open_price)
@@ -169,10 +379,37 @@ impl PolymarketService {
         }
     }
 
+    pub fn config(&self) -> &PolymarketConfig {
+        &self.config
+    }
+
     pub fn get_active_market(&self) -> ActiveMarket {
         self.active_market.read().clone()
     }
 
+    /// Replace the active market (e.g. when driving the service from recorded data)
+    pub fn set_active_market(&self, market: ActiveMarket) {
+        let mut active = self.active_market.write();
+        if active.up_token_id != market.up_token_id || active.down_token_id != market.down_token_id {
+            *self.books.write() = TokenBooks::default();
+        }
+        *active = market;
+    }
+
+    /// Record the fee rate fetched for a market, if it is still the active one
+    pub fn set_fee_rate_bps(&self, condition_id: &str, fee_rate_bps: u64) {
+        let mut market = self.active_market.write();
+        if market.condition_id == condition_id {
+            market.fee_rate_bps = Some(fee_rate_bps);
+        }
+    }
+
+    /// Taker fees for the active market, falling back to the configured rate
+    pub fn fee_model(&self) -> FeeModel {
+        let rate = self.active_market.read().fee_rate_bps.unwrap_or(self.config.fee_rate_bps);
+        FeeModel::new(rate)
+    }
+
     /// Get remaining time in seconds until window ends
     pub fn get_remaining_secs(&self) -> Option<i64> {
         let market = self.active_market.read();
@@ -182,7 +419,7 @@ impl PolymarketService {
 
         // Parse end_date (ISO8601 format)
         if let Ok(end_time) = chrono::DateTime::parse_from_rfc3339(&market.end_date) {
-            let now = chrono::Utc::now();
+            let now = self.clock.now();
             let remaining = end_time.signed_duration_since(now).num_seconds();
             Some(remaining.max(0))
         } else {
@@ -198,12 +435,45 @@ impl PolymarketService {
         self.quote_state.read().clone()
     }
 
+    /// Full book for the Up (Yes) or Down (No) token
+    pub fn get_book(&self, side: TradeSide) -> TokenBook {
+        let books = self.books.read();
+        match side {
+            TradeSide::Yes => books.up.clone(),
+            TradeSide::No => books.down.clone(),
+        }
+    }
+
+    /// Shares available to buy on `side` at or below `limit_price`
+    pub fn ask_depth(&self, side: TradeSide, limit_price: f64) -> f64 {
+        let Ok(limit) = Decimal::try_from(limit_price) else { return 0.0 };
+        self.get_book(side).depth_within(BookSide::Ask, limit).to_f64().unwrap_or(0.0)
+    }
+
+    /// Shares that can be sold on `side` at or above `limit_price`
+    pub fn bid_depth(&self, side: TradeSide, limit_price: f64) -> f64 {
+        let Ok(limit) = Decimal::try_from(limit_price) else { return 0.0 };
+        self.get_book(side).depth_within(BookSide::Bid, limit).to_f64().unwrap_or(0.0)
+    }
+
+    /// Average price to buy `size` shares on `side` by sweeping the asks
+    pub fn buy_vwap(&self, side: TradeSide, size: f64) -> Option<f64> {
+        let size = Decimal::try_from(size).ok()?;
+        self.get_book(side).vwap(BookSide::Ask, size)?.to_f64()
+    }
+
+    /// Average price to sell `size` shares on `side` by sweeping the bids
+    pub fn sell_vwap(&self, side: TradeSide, size: f64) -> Option<f64> {
+        let size = Decimal::try_from(size).ok()?;
+        self.get_book(side).vwap(BookSide::Bid, size)?.to_f64()
+    }
+
     pub fn get_staleness_ms(&self) -> i64 {
         let state = self.quote_state.read();
         if state.last_update_ms == 0 {
             return i64::MAX;
         }
-        chrono::Utc::now().timestamp_millis() - state.last_update_ms
+        self.clock.now_ms() - state.last_update_ms
     }
 
     pub async fn start(&self) -> Result<()> {
@@ -293,7 +563,10 @@ impl PolymarketService {
                             active.start_time = new_tokens.start_time;
                             active.end_date = new_tokens.end_date;
                             active.target_price = None; // Reset for new window
+                            active.fee_rate_bps = None;
                         }
+                        *self.books.write() = TokenBooks::default();
+                        self.record_market();
                         // Break to reconnect with new tokens
                         break;
                     }
@@ -308,6 +581,9 @@ impl PolymarketService {
 
             match msg {
                 Ok(Message::Text(text)) => {
+                    if let Some(recorder) = &self.recorder {
+                        recorder.record(FrameSource::Polymarket, self.clock.now_ms(), &text);
+                    }
                     self.handle_message(&text);
                 }
                 Ok(Message::Ping(data)) => {
@@ -330,7 +606,8 @@ impl PolymarketService {
         Ok(())
     }
 
-    fn handle_message(&self, text: &str) {
+    /// Apply one market-channel frame (book snapshot or price_change batch)
+    pub fn handle_message(&self, text: &str) {
         // Try parsing as price change message first (most common)
         if let Ok(msg) = serde_json::from_str::<PriceChangeMessage>(text) {
             if msg.price_changes.is_some() {
@@ -357,11 +634,16 @@ impl PolymarketService {
     }
 
     fn process_price_changes(&self, msg: PriceChangeMessage) {
-        let now = chrono::Utc::now().timestamp_millis();
-        let market = self.active_market.read();
-        let mut state = self.quote_state.write();
+        let Some(changes) = msg.price_changes else { return };
+        let now = self.clock.now_ms();
+
+        // Apply the whole batch under one lock so readers never see half a message
+        let quotes = {
+            let market = self.active_market.read();
+            let mut state = self.quote_state.write();
+            let mut books = self.books.write();
+            let mut changed: Vec<(String, bool, Option<String>, Option<String>)> = Vec::new();
 
-        if let Some(changes) = msg.price_changes {
             for change in changes {
                 let is_up = change.asset_id == market.up_token_id;
                 let is_down = change.asset_id == market.down_token_id;
@@ -370,99 +652,81 @@ impl PolymarketService {
                     continue;
                 }
 
-                state.last_update_ms = now;
-
-                // Update from best_bid/best_ask in price change
-                if let Some(bid) = &change.best_bid {
-                    if let Ok(price) = bid.parse::<f64>() {
-                        if is_up {
-                            state.yes_bid = Some(price);
-                        } else {
-                            state.no_bid = Some(price);
-                        }
-                    }
+                // Apply the level change to the full book (size is the new total at that price)
+                if let (Some(price), Some(size), Some(side)) = (&change.price, &change.size, &change.side) {
+                    let book_side = if side.eq_ignore_ascii_case("BUY") { BookSide::Bid } else { BookSide::Ask };
+                    books.get_mut(is_up).apply_level(book_side, price, size);
                 }
 
-                if let Some(ask) = &change.best_ask {
-                    if let Ok(price) = ask.parse::<f64>() {
-                        if is_up {
-                            state.yes_ask = Some(price);
-                        } else {
-                            state.no_ask = Some(price);
-                        }
+                // Later entries for the same asset carry the latest best_bid/best_ask
+                match changed.iter_mut().find(|(id, ..)| *id == change.asset_id) {
+                    Some(entry) => {
+                        entry.2 = change.best_bid.or(entry.2.take());
+                        entry.3 = change.best_ask.or(entry.3.take());
                     }
+                    None => changed.push((change.asset_id, is_up, change.best_bid, change.best_ask)),
                 }
+            }
 
-                // Emit update
-                let quote = PolymarketQuote {
-                    token_id: change.asset_id.clone(),
-                    side: if is_up { "UP".to_string() } else { "DOWN".to_string() },
-                    best_bid: if is_up { state.yes_bid } else { state.no_bid },
-                    best_bid_size: None,
-                    best_ask: if is_up { state.yes_ask } else { state.no_ask },
-                    best_ask_size: None,
-                    t_recv_ms: now,
-                };
-
-                drop(state);
-                drop(market);
-                let _ = self.update_tx.send(quote);
+            if changed.is_empty() {
                 return;
             }
+            state.last_update_ms = now;
+
+            changed
+                .into_iter()
+                .map(|(asset_id, is_up, best_bid, best_ask)| {
+                    let book = books.get_mut(is_up);
+                    let bid = top_of_book(book, BookSide::Bid, best_bid.as_deref());
+                    let ask = top_of_book(book, BookSide::Ask, best_ask.as_deref());
+                    state.set_top(is_up, bid, ask);
+                    state.quote(asset_id, is_up, now)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for quote in quotes {
+            let _ = self.update_tx.send(quote);
         }
     }
 
     fn process_book_message(&self, msg: BookMessage) {
-        let now = chrono::Utc::now().timestamp_millis();
-        let market = self.active_market.read();
-        let mut state = self.quote_state.write();
+        let now = self.clock.now_ms();
 
-        let asset_id = match &msg.asset_id {
+        let asset_id = match msg.asset_id {
             Some(id) => id,
             None => return,
         };
 
-        let is_up = asset_id == &market.up_token_id;
-        let is_down = asset_id == &market.down_token_id;
-        drop(market);
+        let quote = {
+            let market = self.active_market.read();
+            let is_up = asset_id == market.up_token_id;
+            let is_down = asset_id == market.down_token_id;
+            drop(market);
 
-        if !is_up && !is_down {
-            return;
-        }
-
-        state.last_update_ms = now;
-
-        // Get best bid (highest price) from bids sorted ascending
-        if let Some(bids) = &msg.bids {
-            if let Some(best) = bids.last() {
-                // Bids are sorted ascending, so last is best (highest)
-                if let Ok(price) = best.price.parse::<f64>() {
-                    if is_up {
-                        state.yes_bid = Some(price);
-                        state.yes_bid_size = best.size.parse().ok();
-                    } else {
-                        state.no_bid = Some(price);
-                        state.no_bid_size = best.size.parse().ok();
-                    }
-                }
+            if !is_up && !is_down {
+                return;
             }
-        }
 
-        // Get best ask (lowest price) from asks
-        if let Some(asks) = &msg.asks {
-            if let Some(best) = asks.first() {
-                // Take first ask (assuming sorted ascending = lowest first)
-                if let Ok(price) = best.price.parse::<f64>() {
-                    if is_up {
-                        state.yes_ask = Some(price);
-                        state.yes_ask_size = best.size.parse().ok();
-                    } else {
-                        state.no_ask = Some(price);
-                        state.no_ask_size = best.size.parse().ok();
-                    }
-                }
-            }
-        }
+            let mut state = self.quote_state.write();
+            let mut books = self.books.write();
+            state.last_update_ms = now;
+
+            // Replace the token's book with the snapshot, then derive top of book from it
+            // (levels arrive in no guaranteed order, so never trust first/last)
+            let book = books.get_mut(is_up);
+            book.apply_snapshot(
+                msg.bids.as_deref().unwrap_or_default(),
+                msg.asks.as_deref().unwrap_or_default(),
+            );
+
+            let bid = top_of_book(book, BookSide::Bid, None);
+            let ask = top_of_book(book, BookSide::Ask, None);
+            state.set_top(is_up, bid, ask);
+            state.quote(asset_id, is_up, now)
+        };
+
+        let _ = self.update_tx.send(quote);
     }
 
     pub fn stop(&self) {
@@ -473,6 +737,21 @@ impl PolymarketService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::testing::{poly_config, LADDER_BOOK_MSG};
+
+    fn make_service() -> PolymarketService {
+        let service = PolymarketService::new(poly_config());
+        {
+            let mut market = service.active_market.write();
+            market.up_token_id = "UP".to_string();
+            market.down_token_id = "DOWN".to_string();
+        }
+        service
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
 
     #[test]
     fn test_quote_state_default() {
@@ -481,4 +760,123 @@ mod tests {
         assert!(state.no_bid.is_none());
         assert_eq!(state.last_update_ms, 0);
     }
+
+    #[test]
+    fn test_book_snapshot_builds_full_book() {
+        let service = make_service();
+        service.handle_message(LADDER_BOOK_MSG);
+
+        let book = service.get_book(TradeSide::Yes);
+        assert_eq!(book.best_bid(), Some((dec("0.50"), dec("20"))));
+        assert_eq!(book.best_ask(), Some((dec("0.52"), dec("10"))));
+        assert!(service.get_book(TradeSide::No).is_empty());
+
+        let quotes = service.get_quote_state();
+        assert_eq!(quotes.yes_bid, Some(0.50));
+        assert_eq!(quotes.yes_ask, Some(0.52));
+        assert_eq!(quotes.yes_ask_size, Some(10.0));
+    }
+
+    #[test]
+    fn test_price_change_updates_levels() {
+        let service = make_service();
+        service.handle_message(LADDER_BOOK_MSG);
+
+        // Best ask level consumed, new bid level added
+        service.handle_message(r#"{"event_type":"price_change","market":"0xcond","price_changes":[
+            {"asset_id":"UP","price":"0.52","size":"0","side":"SELL","hash":"h1","best_bid":"0.50","best_ask":"0.53"}],
+            "timestamp":"1769961601000"}"#);
+        service.handle_message(r#"{"event_type":"price_change","market":"0xcond","price_changes":[
+            {"asset_id":"UP","price":"0.51","size":"75","side":"BUY","hash":"h2","best_bid":"0.51","best_ask":"0.53"}],
+            "timestamp":"1769961602000"}"#);
+
+        let book = service.get_book(TradeSide::Yes);
+        assert_eq!(book.best_ask(), Some((dec("0.53"), dec("40"))));
+        assert_eq!(book.best_bid(), Some((dec("0.51"), dec("75"))));
+    }
+
+    #[test]
+    fn test_price_change_batch_updates_both_sides() {
+        let service = make_service();
+        let mut rx = service.subscribe();
+        service.handle_message(LADDER_BOOK_MSG);
+        service.handle_message(r#"[{"event_type":"book","asset_id":"DOWN","market":"0xcond",
+            "bids":[{"price":"0.46","size":"80"},{"price":"0.47","size":"25"}],
+            "asks":[{"price":"0.51","size":"60"},{"price":"0.50","size":"15"}],
+            "timestamp":"1769961600000","hash":"def"}]"#);
+        assert_eq!(rx.try_recv().unwrap().side, "UP");
+        assert_eq!(rx.try_recv().unwrap().side, "DOWN");
+
+        // A single trade moves both tokens: captured payload shape from the market channel
+        service.handle_message(r#"{"market":"0xcond","price_changes":[
+            {"asset_id":"UP","price":"0.52","size":"0","side":"SELL","hash":"a1","best_bid":"0.50","best_ask":"0.53"},
+            {"asset_id":"DOWN","price":"0.47","size":"0","side":"BUY","hash":"b1","best_bid":"0.46","best_ask":"0.50"},
+            {"asset_id":"DOWN","price":"0.48","size":"30","side":"BUY","hash":"b2","best_bid":"0.48","best_ask":"0.50"}],
+            "timestamp":"1769961601234","event_type":"price_change"}"#);
+
+        let quotes = service.get_quote_state();
+        assert_eq!(quotes.yes_bid, Some(0.50));
+        assert_eq!(quotes.yes_bid_size, Some(20.0));
+        assert_eq!(quotes.yes_ask, Some(0.53));
+        assert_eq!(quotes.yes_ask_size, Some(40.0));
+        assert_eq!(quotes.no_bid, Some(0.48));
+        assert_eq!(quotes.no_bid_size, Some(30.0));
+        assert_eq!(quotes.no_ask, Some(0.50));
+        assert_eq!(quotes.no_ask_size, Some(15.0));
+
+        // One quote per changed asset, carrying the post-batch state
+        let up = rx.try_recv().unwrap();
+        assert_eq!((up.token_id.as_str(), up.side.as_str()), ("UP", "UP"));
+        assert_eq!(up.best_ask, Some(0.53));
+        assert_eq!(up.best_ask_size, Some(40.0));
+        let down = rx.try_recv().unwrap();
+        assert_eq!((down.token_id.as_str(), down.side.as_str()), ("DOWN", "DOWN"));
+        assert_eq!(down.best_bid, Some(0.48));
+        assert_eq!(down.best_bid_size, Some(30.0));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_price_change_ignores_other_assets() {
+        let service = make_service();
+        let mut rx = service.subscribe();
+        service.handle_message(r#"{"market":"0xother","price_changes":[
+            {"asset_id":"OTHER","price":"0.30","size":"10","side":"BUY","hash":"x","best_bid":"0.30","best_ask":"0.31"}],
+            "timestamp":"1769961601234","event_type":"price_change"}"#);
+        assert!(rx.try_recv().is_err());
+        assert_eq!(service.get_quote_state().last_update_ms, 0);
+    }
+
+    #[test]
+    fn test_price_change_empty_side() {
+        let service = make_service();
+        // Last bid level pulled before we have a snapshot: exchange reports best_bid 0
+        service.handle_message(r#"{"market":"0xcond","price_changes":[
+            {"asset_id":"UP","price":"0.50","size":"0","side":"BUY","hash":"a","best_bid":"0","best_ask":"0.52"}],
+            "timestamp":"1769961601234","event_type":"price_change"}"#);
+        let quotes = service.get_quote_state();
+        assert_eq!(quotes.yes_bid, None);
+        assert_eq!(quotes.yes_bid_size, None);
+        assert_eq!(quotes.yes_ask, Some(0.52));
+        assert_eq!(quotes.yes_ask_size, None);
+    }
+
+    #[test]
+    fn test_depth_and_vwap() {
+        let service = make_service();
+        service.handle_message(LADDER_BOOK_MSG);
+
+        assert_eq!(service.ask_depth(TradeSide::Yes, 0.53), 50.0);
+        assert_eq!(service.ask_depth(TradeSide::Yes, 0.51), 0.0);
+        assert_eq!(service.bid_depth(TradeSide::Yes, 0.49), 70.0);
+
+        // 10 @ 0.52 + 40 @ 0.53 + 10 @ 0.54 = 31.8 / 60
+        let vwap = service.buy_vwap(TradeSide::Yes, 60.0).unwrap();
+        assert!((vwap - 0.53).abs() < 1e-9);
+        // 20 @ 0.50 + 10 @ 0.49 = 14.9 / 30
+        let vwap = service.sell_vwap(TradeSide::Yes, 30.0).unwrap();
+        assert!((vwap - 14.9 / 30.0).abs() < 1e-9);
+        // More than the whole ask side
+        assert!(service.buy_vwap(TradeSide::Yes, 1000.0).is_none());
+    }
 }
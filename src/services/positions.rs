@@ -0,0 +1,338 @@
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Holdings of one outcome token within one 15-minute window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub condition_id: String,
+    pub token_id: String,
+    pub side: String, // "YES" (Up) or "NO" (Down)
+    pub size: f64,
+    pub avg_cost: f64,
+    pub realized_pnl: f64,
+    #[serde(default)]
+    pub fees_paid: f64,
+    pub settled: bool,
+    pub settlement_price: Option<f64>, // 1.0 if the token won, 0.0 if it lost
+    #[serde(default)]
+    pub settled_size: f64, // shares held when the window settled
+    pub updated_ms: i64,
+}
+
+impl Position {
+    fn new(condition_id: &str, token_id: &str, side: &str) -> Self {
+        Self {
+            condition_id: condition_id.to_string(),
+            token_id: token_id.to_string(),
+            side: side.to_string(),
+            size: 0.0,
+            avg_cost: 0.0,
+            realized_pnl: 0.0,
+            fees_paid: 0.0,
+            settled: false,
+            settlement_price: None,
+            settled_size: 0.0,
+            updated_ms: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Mark-to-market PnL if the position were sold at `bid`
+    pub fn unrealized_pnl(&self, bid: Option<f64>) -> Option<f64> {
+        if self.size <= 0.0 {
+            return Some(0.0);
+        }
+        bid.map(|b| (b - self.avg_cost) * self.size)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LedgerFile {
+    positions: Vec<Position>,
+}
+
+/// Position ledger keyed by (condition_id, token_id), persisted as JSON after every change
+pub struct PositionLedger {
+    path: Option<PathBuf>,
+    positions: RwLock<Vec<Position>>,
+}
+
+impl PositionLedger {
+    /// In-memory ledger (nothing written to disk)
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            positions: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Load the ledger from `path`, starting empty if the file doesn't exist yet
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let positions = if path.exists() {
+            let data = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str::<LedgerFile>(&data)
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+                .positions
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            path: Some(path),
+            positions: RwLock::new(positions),
+        })
+    }
+
+    fn save(&self, positions: &[Position]) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Write-then-rename so a crash never leaves a truncated ledger
+        let tmp = path.with_extension("json.tmp");
+        let file = LedgerFile { positions: positions.to_vec() };
+        std::fs::write(&tmp, serde_json::to_string_pretty(&file)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut Vec<Position>) -> T) -> Result<T> {
+        let mut positions = self.positions.write();
+        let out = f(&mut positions);
+        self.save(&positions)?;
+        Ok(out)
+    }
+
+    /// Apply a fill. BUYs move the average cost (fee included), SELLs realize PnL
+    /// against it net of the fee.
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_fill(
+        &self,
+        condition_id: &str,
+        token_id: &str,
+        side: &str,
+        order_side: &str,
+        price: f64,
+        size: f64,
+        fee: f64,
+    ) -> Result<Position> {
+        self.update(|positions| {
+            let idx = match positions
+                .iter()
+                .position(|p| p.condition_id == condition_id && p.token_id == token_id)
+            {
+                Some(idx) => idx,
+                None => {
+                    positions.push(Position::new(condition_id, token_id, side));
+                    positions.len() - 1
+                }
+            };
+            let pos = &mut positions[idx];
+
+            if order_side.eq_ignore_ascii_case("SELL") {
+                let sold = size.min(pos.size);
+                pos.realized_pnl += (price - pos.avg_cost) * sold - fee;
+                pos.size -= sold;
+                if pos.size <= 0.0 {
+                    pos.size = 0.0;
+                }
+            } else {
+                let cost = pos.avg_cost * pos.size + price * size + fee;
+                pos.size += size;
+                pos.avg_cost = if pos.size > 0.0 { cost / pos.size } else { 0.0 };
+            }
+            pos.fees_paid += fee;
+            pos.updated_ms = chrono::Utc::now().timestamp_millis();
+            pos.clone()
+        })
+    }
+
+    /// Undo a fill that failed on chain after we had already booked it
+    #[allow(clippy::too_many_arguments)]
+    pub fn revert_fill(
+        &self,
+        condition_id: &str,
+        token_id: &str,
+        order_side: &str,
+        price: f64,
+        size: f64,
+        fee: f64,
+    ) -> Result<Option<Position>> {
+        self.update(|positions| {
+            let pos = positions
+                .iter_mut()
+                .find(|p| p.condition_id == condition_id && p.token_id == token_id)?;
+
+            if order_side.eq_ignore_ascii_case("SELL") {
+                pos.realized_pnl -= (price - pos.avg_cost) * size - fee;
+                pos.size += size;
+            } else {
+                let remaining = (pos.size - size).max(0.0);
+                pos.avg_cost = if remaining > 0.0 {
+                    (pos.avg_cost * pos.size - price * size - fee) / remaining
+                } else {
+                    0.0
+                };
+                pos.size = remaining;
+            }
+            pos.fees_paid -= fee;
+            pos.updated_ms = chrono::Utc::now().timestamp_millis();
+            Some(pos.clone())
+        })
+    }
+
+    /// Settle every open position in a window. Up wins if the closing Chainlink price is at
+    /// or above the target; winning tokens pay 1.0, losing ones 0.0. Returns the window's realized PnL.
+    pub fn settle(&self, condition_id: &str, target_price: f64, close_price: f64) -> Result<f64> {
+        let up_won = close_price >= target_price;
+        self.update(|positions| {
+            let mut realized = 0.0;
+            for pos in positions
+                .iter_mut()
+                .filter(|p| p.condition_id == condition_id && !p.settled)
+            {
+                let payout = if (pos.side == "YES") == up_won { 1.0 } else { 0.0 };
+                let pnl = (payout - pos.avg_cost) * pos.size;
+                pos.realized_pnl += pnl;
+                pos.settled_size = pos.size;
+                pos.size = 0.0;
+                pos.settled = true;
+                pos.settlement_price = Some(payout);
+                pos.updated_ms = chrono::Utc::now().timestamp_millis();
+                realized += pnl;
+            }
+            realized
+        })
+    }
+
+    /// Re-settle a window that resolved the other way from how it was settled: each
+    /// position's payout is replaced and its PnL moved by the difference. Returns the
+    /// change in the window's realized PnL.
+    pub fn resettle(&self, condition_id: &str, up_won: bool) -> Result<f64> {
+        self.update(|positions| {
+            let mut delta = 0.0;
+            for pos in positions
+                .iter_mut()
+                .filter(|p| p.condition_id == condition_id && p.settled)
+            {
+                let payout = if (pos.side == "YES") == up_won { 1.0 } else { 0.0 };
+                let Some(previous) = pos.settlement_price.filter(|p| *p != payout) else { continue };
+                let pnl = (payout - previous) * pos.settled_size;
+                pos.realized_pnl += pnl;
+                pos.settlement_price = Some(payout);
+                pos.updated_ms = chrono::Utc::now().timestamp_millis();
+                delta += pnl;
+            }
+            delta
+        })
+    }
+
+    pub fn get_positions(&self) -> Vec<Position> {
+        self.positions.read().clone()
+    }
+
+    /// Positions for one window (both settled and open)
+    pub fn window_positions(&self, condition_id: &str) -> Vec<Position> {
+        self.positions
+            .read()
+            .iter()
+            .filter(|p| p.condition_id == condition_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Realized PnL across every window in the ledger
+    pub fn total_realized_pnl(&self) -> f64 {
+        self.positions.read().iter().map(|p| p.realized_pnl).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_average_cost_and_sell() {
+        let ledger = PositionLedger::in_memory();
+        ledger.apply_fill("c1", "up", "YES", "BUY", 0.40, 10.0, 0.0).unwrap();
+        let pos = ledger.apply_fill("c1", "up", "YES", "BUY", 0.60, 10.0, 0.0).unwrap();
+        assert_eq!(pos.size, 20.0);
+        assert!((pos.avg_cost - 0.50).abs() < 1e-9);
+
+        // Marked at a 0.55 bid: 20 * 0.05
+        assert!((pos.unrealized_pnl(Some(0.55)).unwrap() - 1.0).abs() < 1e-9);
+        assert!(pos.unrealized_pnl(None).is_none());
+
+        let pos = ledger.apply_fill("c1", "up", "YES", "SELL", 0.70, 5.0, 0.0).unwrap();
+        assert_eq!(pos.size, 15.0);
+        assert!((pos.realized_pnl - 1.0).abs() < 1e-9);
+        assert!((pos.avg_cost - 0.50).abs() < 1e-9);
+
+        let pos = ledger.revert_fill("c1", "up", "BUY", 0.60, 10.0, 0.0).unwrap().unwrap();
+        assert_eq!(pos.size, 5.0);
+        assert!((pos.avg_cost - 0.30).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fees_in_pnl() {
+        let ledger = PositionLedger::in_memory();
+        // 10 @ 0.50 plus a 0.20 taker fee: 0.52 a share all-in
+        let pos = ledger.apply_fill("c1", "up", "YES", "BUY", 0.50, 10.0, 0.20).unwrap();
+        assert!((pos.avg_cost - 0.52).abs() < 1e-9);
+
+        let pos = ledger.apply_fill("c1", "up", "YES", "SELL", 0.60, 10.0, 0.16).unwrap();
+        assert!((pos.realized_pnl - (0.80 - 0.16)).abs() < 1e-9);
+        assert!((pos.fees_paid - 0.36).abs() < 1e-9);
+
+        let pos = ledger.revert_fill("c1", "up", "SELL", 0.60, 10.0, 0.16).unwrap().unwrap();
+        assert!(pos.realized_pnl.abs() < 1e-9);
+        assert_eq!(pos.size, 10.0);
+    }
+
+    #[test]
+    fn test_settlement() {
+        let ledger = PositionLedger::in_memory();
+        ledger.apply_fill("c1", "up", "YES", "BUY", 0.60, 10.0, 0.0).unwrap();
+        ledger.apply_fill("c1", "down", "NO", "BUY", 0.30, 5.0, 0.0).unwrap();
+        ledger.apply_fill("c2", "up2", "YES", "BUY", 0.50, 10.0, 0.0).unwrap();
+
+        // Close above target: Up pays 1, Down pays 0
+        let pnl = ledger.settle("c1", 100_000.0, 100_010.0).unwrap();
+        assert!((pnl - (4.0 - 1.5)).abs() < 1e-9);
+
+        let window = ledger.window_positions("c1");
+        assert!(window.iter().all(|p| p.settled && p.size == 0.0));
+        // Settling twice is a no-op, other windows untouched
+        assert_eq!(ledger.settle("c1", 100_000.0, 90_000.0).unwrap(), 0.0);
+        assert!(!ledger.window_positions("c2")[0].settled);
+        assert!((ledger.total_realized_pnl() - 2.5).abs() < 1e-9);
+
+        // Resolved Down after all: Up's 10 @ 0.60 lose, Down's 5 @ 0.30 win
+        let delta = ledger.resettle("c1", false).unwrap();
+        assert!((delta - (-10.0 + 5.0)).abs() < 1e-9);
+        assert!((ledger.total_realized_pnl() - (-6.0 + 3.5)).abs() < 1e-9);
+        assert_eq!(ledger.resettle("c1", false).unwrap(), 0.0);
+        assert_eq!(ledger.resettle("c2", false).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_persistence_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("positions.json");
+
+        let ledger = PositionLedger::load(&path).unwrap();
+        ledger.apply_fill("c1", "up", "YES", "BUY", 0.45, 8.0, 0.0).unwrap();
+        drop(ledger);
+
+        let reloaded = PositionLedger::load(&path).unwrap();
+        let positions = reloaded.get_positions();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].size, 8.0);
+        assert_eq!(positions[0].side, "YES");
+    }
+}
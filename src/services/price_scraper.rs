@@ -2,8 +2,6 @@ use anyhow::Result;
 use scraper::{Html, Selector};
 use std::str::FromStr;
 
-const POLYMARKET_BASE_URL: &str = "https://polymarket.com/event";
-
 #[derive(Debug, Clone)]
 pub struct ScrapedPriceData {
     pub open_price: f64,
@@ -16,8 +14,8 @@ pub struct ScrapedPriceData {
 /// Polymarket embeds the open price in the page as JSON: "openPrice":77572.06425014541
 /// (this is the Chainlink BTC/USD price at the start of the 15-min window, e.g. 77,572.06).
 /// We try embedded JSON first (reliable), then fall back to the "price to beat" div if present.
-pub async fn fetch_price_to_beat(market_slug: &str) -> Result<Option<ScrapedPriceData>> {
-    let url = format!("{}/{}", POLYMARKET_BASE_URL, market_slug);
+pub async fn fetch_price_to_beat(event_base_url: &str, market_slug: &str) -> Result<Option<ScrapedPriceData>> {
+    let url = format!("{}/{}", event_base_url.trim_end_matches('/'), market_slug);
 
     let client = reqwest::Client::builder()
         .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
@@ -67,13 +65,13 @@ fn extract_open_price_from_embedded_json(html: &str) -> Option<f64> {
     while let Some(pos) = html[search_start..].find(pattern) {
         let value_start = search_start + pos + pattern.len();
         let value_end = html[value_start..]
-            .find(|c: char| c == ',' || c == '}' || c == ' ')
+            .find([',', '}', ' '])
             .map(|i| value_start + i)
             .unwrap_or(html.len());
         let value_str = html[value_start..value_end].trim();
         if let Ok(p) = value_str.parse::<f64>() {
             // Sanity: BTC price should be in a reasonable range (e.g. 10k–200k)
-            if p >= 10_000.0 && p <= 500_000.0 {
+            if (10_000.0..=500_000.0).contains(&p) {
                 last_open_price = Some(p);
             }
         }
@@ -155,10 +153,12 @@ fn extract_price_from_element_text(element: &scraper::ElementRef) -> Option<f64>
         if let scraper::node::Node::Element(_) = node {
             if let Some(child_el) = ElementRef::wrap(child) {
                 let s = child_el.text().collect::<String>().trim().to_string();
-                if !s.is_empty() && s != "price to beat" && s != "PRICE TO BEAT" {
-                    if parse_price_string(&s).is_some() {
-                        last_text_with_number = Some(s);
-                    }
+                if !s.is_empty()
+                    && s != "price to beat"
+                    && s != "PRICE TO BEAT"
+                    && parse_price_string(&s).is_some()
+                {
+                    last_text_with_number = Some(s);
                 }
             }
         }
@@ -198,7 +198,7 @@ mod tests {
     #[tokio::test]
     #[ignore] // requires network
     async fn test_fetch_price_to_beat() {
-        let result = fetch_price_to_beat("btc-updown-15m-1769959800").await;
+        let result = fetch_price_to_beat("https://polymarket.com/event", "btc-updown-15m-1769959800").await;
         match result {
             Ok(Some(data)) => {
                 println!("Open Price: ${:.2}", data.open_price);
@@ -0,0 +1,321 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Local, NaiveDate};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+
+use super::clock::Clock;
+use super::{ActiveMarket, BinanceBookService, ChainlinkService, PolymarketService};
+
+/// Flush the compressed stream at most this often; a crash loses at most this much
+const FLUSH_INTERVAL_MS: i64 = 1_000;
+
+const FRAMES_FILE: &str = "frames.jsonl.gz";
+
+/// Which feed a frame came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameSource {
+    /// Depth diffs, plus the REST snapshots the book is rebuilt from
+    Binance,
+    /// Market channel (book / price_change)
+    Polymarket,
+    /// RTDS Chainlink prices
+    Chainlink,
+    /// Active market changes (tokens, window times, target price), as `ActiveMarket` JSON
+    Market,
+}
+
+/// One raw frame with its local receive time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawFrame {
+    pub t_recv_ms: i64,
+    pub source: FrameSource,
+    pub text: String,
+}
+
+struct DayWriter {
+    date: NaiveDate,
+    encoder: GzEncoder<BufWriter<File>>,
+    last_flush_ms: i64,
+}
+
+enum WriterMsg {
+    Frame(RawFrame),
+    /// Finish the current file and report how that went
+    Finish(mpsc::Sender<Result<()>>),
+}
+
+/// Appends every raw WS frame from the feeds to `<dir>/<date>/frames.jsonl.gz`, one JSON
+/// line per frame in receive order, with `<date>` the local day of the frame's receive
+/// time. Each run appends a new gzip member, which `read_frames` reads back as one stream.
+///
+/// `record` only queues the frame; compression and file IO happen on a dedicated writer
+/// thread so the feeds' WS loops never wait on the disk.
+pub struct FrameRecorder {
+    tx: mpsc::Sender<WriterMsg>,
+}
+
+impl FrameRecorder {
+    pub fn new(base_dir: &str) -> Result<Arc<Self>> {
+        let base_dir = PathBuf::from(base_dir);
+        fs::create_dir_all(&base_dir)?;
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("frame-recorder".to_string())
+            .spawn(move || run_writer(&base_dir, rx))?;
+        Ok(Arc::new(Self { tx }))
+    }
+
+    pub fn record(&self, source: FrameSource, t_recv_ms: i64, text: &str) {
+        let frame = RawFrame {
+            t_recv_ms,
+            source,
+            text: text.to_string(),
+        };
+        if self.tx.send(WriterMsg::Frame(frame)).is_err() {
+            tracing::error!("Frame recorder stopped; dropping {:?} frame", source);
+        }
+    }
+
+    /// Write out everything queued so far and finish the current file (on shutdown)
+    pub fn finish(&self) -> Result<()> {
+        let (done_tx, done_rx) = mpsc::channel();
+        self.tx
+            .send(WriterMsg::Finish(done_tx))
+            .map_err(|_| anyhow!("Frame recorder stopped"))?;
+        done_rx.recv().context("Frame recorder stopped")?
+    }
+}
+
+/// Writer thread: drains the queue in order until the recorder is dropped
+fn run_writer(base_dir: &Path, rx: mpsc::Receiver<WriterMsg>) {
+    let mut writer: Option<DayWriter> = None;
+    for msg in rx {
+        match msg {
+            WriterMsg::Frame(frame) => {
+                if let Err(e) = write_frame(base_dir, &mut writer, &frame) {
+                    tracing::error!("Failed to record {:?} frame: {:?}", frame.source, e);
+                }
+            }
+            WriterMsg::Finish(done) => {
+                let _ = done.send(finish_day(writer.take()));
+            }
+        }
+    }
+    if let Err(e) = finish_day(writer.take()) {
+        tracing::error!("Failed to finish frame file: {:?}", e);
+    }
+}
+
+fn write_frame(base_dir: &Path, writer: &mut Option<DayWriter>, frame: &RawFrame) -> Result<()> {
+    let date = chrono::DateTime::from_timestamp_millis(frame.t_recv_ms)
+        .unwrap_or_default()
+        .with_timezone(&Local)
+        .date_naive();
+    if writer.as_ref().map(|w| w.date) != Some(date) {
+        // New day: close out yesterday's stream so it's a complete gzip member
+        finish_day(writer.take())?;
+        let dir = base_dir.join(date.format("%Y-%m-%d").to_string());
+        fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new().create(true).append(true).open(dir.join(FRAMES_FILE))?;
+        *writer = Some(DayWriter {
+            date,
+            encoder: GzEncoder::new(BufWriter::new(file), Compression::default()),
+            last_flush_ms: 0,
+        });
+    }
+
+    let writer = writer.as_mut().expect("writer opened above");
+    serde_json::to_writer(&mut writer.encoder, frame)?;
+    writer.encoder.write_all(b"\n")?;
+    if frame.t_recv_ms - writer.last_flush_ms >= FLUSH_INTERVAL_MS {
+        writer.encoder.flush()?;
+        writer.last_flush_ms = frame.t_recv_ms;
+    }
+    Ok(())
+}
+
+fn finish_day(writer: Option<DayWriter>) -> Result<()> {
+    if let Some(writer) = writer {
+        writer.encoder.finish()?.flush()?;
+    }
+    Ok(())
+}
+
+/// Recorded frame files for each day in `from..=to` that exists under `dir`
+pub fn frame_files(dir: &Path, from: NaiveDate, to: NaiveDate) -> Vec<PathBuf> {
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| dir.join(day.format("%Y-%m-%d").to_string()).join(FRAMES_FILE))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Read a frame file back in order. A file cut off mid-write (the recorder was killed)
+/// yields everything up to the last complete frame.
+pub fn read_frames(path: &Path) -> Result<Vec<RawFrame>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let reader = BufReader::new(MultiGzDecoder::new(BufReader::new(file)));
+    let mut frames = Vec::new();
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            tracing::warn!("{} ends mid-frame after {} frames", path.display(), frames.len());
+            break;
+        };
+        match serde_json::from_str::<RawFrame>(&line) {
+            Ok(frame) => frames.push(frame),
+            Err(_) => tracing::warn!("Skipping unreadable frame in {}", path.display()),
+        }
+    }
+    Ok(frames)
+}
+
+/// Feeds recorded frames into the services the way their WebSocket loops would,
+/// with the clock set to each frame's receive time
+pub struct FrameFeeder {
+    clock: Clock,
+    binance: Arc<BinanceBookService>,
+    polymarket: Arc<PolymarketService>,
+    chainlink: Arc<ChainlinkService>,
+}
+
+impl FrameFeeder {
+    pub fn new(
+        clock: Clock,
+        binance: Arc<BinanceBookService>,
+        polymarket: Arc<PolymarketService>,
+        chainlink: Arc<ChainlinkService>,
+    ) -> Self {
+        Self {
+            clock,
+            binance,
+            polymarket,
+            chainlink,
+        }
+    }
+
+    pub fn apply(&self, frame: &RawFrame) {
+        self.clock.set_ms(frame.t_recv_ms);
+        match frame.source {
+            FrameSource::Binance => {
+                // A gap leaves the book empty until the next recorded snapshot
+                self.binance.handle_message(&frame.text);
+            }
+            FrameSource::Polymarket => self.polymarket.handle_message(&frame.text),
+            FrameSource::Chainlink => self.chainlink.handle_message(&frame.text),
+            FrameSource::Market => match serde_json::from_str::<ActiveMarket>(&frame.text) {
+                Ok(market) => self.polymarket.set_active_market(market),
+                Err(e) => tracing::warn!("Bad market frame: {:?}", e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::{binance_config, poly_config};
+    use crate::services::SpotBook;
+
+    #[test]
+    fn test_frames_round_trip() {
+        use chrono::TimeZone;
+
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let midnight = Local.from_local_datetime(&day.succ_opt().unwrap().and_hms_opt(0, 0, 0).unwrap()).unwrap();
+        let midnight_ms = midnight.timestamp_millis();
+        let clock = Clock::simulated(midnight_ms - 1_000);
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = FrameRecorder::new(dir.path().to_str().unwrap()).unwrap();
+        recorder.record(FrameSource::Chainlink, clock.now_ms(), r#"{"payload":{"value":100000.5}}"#);
+        clock.set_ms(midnight_ms - 999);
+        recorder.record(FrameSource::Binance, clock.now_ms(), "{\"u\":1}\n");
+        recorder.finish().unwrap();
+
+        // A second session appends another gzip member; the last write is cut short
+        clock.set_ms(midnight_ms - 499);
+        recorder.record(FrameSource::Polymarket, clock.now_ms(), "[]");
+        // Past local midnight frames go to the next day's file
+        clock.set_ms(midnight_ms + 501);
+        recorder.record(FrameSource::Polymarket, clock.now_ms(), "[1]");
+        recorder.finish().unwrap();
+        let path = dir.path().join("2026-10-16").join(FRAMES_FILE);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0x1f, 0x8b, 0x08]).unwrap();
+
+        let next_path = dir.path().join("2026-10-17").join(FRAMES_FILE);
+        assert_eq!(frame_files(dir.path(), day, day.succ_opt().unwrap()), vec![path.clone(), next_path.clone()]);
+        let frames = read_frames(&path).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].text, "{\"u\":1}\n");
+        assert_eq!((frames[2].source, frames[2].t_recv_ms), (FrameSource::Polymarket, midnight_ms - 499));
+        let next = read_frames(&next_path).unwrap();
+        assert_eq!((next.len(), next[0].text.as_str()), (1, "[1]"));
+    }
+
+    #[test]
+    fn test_feeder_drives_services() {
+        let clock = Clock::simulated(0);
+        let binance = Arc::new(
+            BinanceBookService::new(binance_config())
+            .with_clock(clock.clone()),
+        );
+        let polymarket = Arc::new(
+            PolymarketService::new(poly_config())
+            .with_clock(clock.clone()),
+        );
+        let chainlink = Arc::new(ChainlinkService::new("wss://test").with_clock(clock.clone()));
+        let feeder = FrameFeeder::new(clock.clone(), binance.clone(), polymarket.clone(), chainlink.clone());
+
+        let market = ActiveMarket {
+            up_token_id: "UP".to_string(),
+            down_token_id: "DOWN".to_string(),
+            condition_id: "0xcond".to_string(),
+            ..Default::default()
+        };
+        let frames = [
+            RawFrame {
+                t_recv_ms: 1_000,
+                source: FrameSource::Market,
+                text: serde_json::to_string(&market).unwrap(),
+            },
+            RawFrame {
+                t_recv_ms: 1_100,
+                source: FrameSource::Binance,
+                text: r#"{"lastUpdateId":10,"bids":[["99999.0","1.0"]],"asks":[["100001.0","1.0"]]}"#.to_string(),
+            },
+            RawFrame {
+                t_recv_ms: 1_200,
+                source: FrameSource::Binance,
+                text: r#"{"e":"depthUpdate","E":1,"s":"BTCUSDT","U":11,"u":11,"b":[["100000.0","2.0"]],"a":[]}"#.to_string(),
+            },
+            RawFrame {
+                t_recv_ms: 1_300,
+                source: FrameSource::Polymarket,
+                text: r#"[{"event_type":"book","asset_id":"UP","market":"0xcond",
+                    "bids":[{"price":"0.48","size":"10"}],"asks":[{"price":"0.52","size":"10"}]}]"#.to_string(),
+            },
+            RawFrame {
+                t_recv_ms: 1_400,
+                source: FrameSource::Chainlink,
+                text: r#"{"topic":"crypto_prices_chainlink","payload":{"symbol":"btc/usd","value":100000.5}}"#.to_string(),
+            },
+        ];
+        for frame in &frames {
+            feeder.apply(frame);
+        }
+
+        assert_eq!(polymarket.get_active_market().condition_id, "0xcond");
+        assert_eq!(binance.get_mid_price(), Some(100000.5));
+        assert_eq!(polymarket.get_quote_state().yes_ask, Some(0.52));
+        assert_eq!(chainlink.get_price_state().timestamp_ms, 1_400);
+        clock.set_ms(2_000);
+        assert_eq!(polymarket.get_staleness_ms(), 700);
+    }
+}
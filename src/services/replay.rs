@@ -0,0 +1,809 @@
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::events::{SignalEvent, SnapshotEvent};
+use crate::logger::JsonlLogger;
+use super::clock::Clock;
+use super::recorder::{frame_files, read_frames, FrameFeeder, FrameSource, RawFrame};
+use super::strategy::MarketView;
+use super::{ActiveMarket, AutoTrader, BinanceBookService, ChainlinkService, PolymarketService, RiskRejected, SignalService, TradeService};
+
+/// Recorded gaps longer than this (restarts, outages) aren't waited out when pacing
+const MAX_PACED_GAP_MS: i64 = 10_000;
+
+/// Windows are 15 minutes long
+const WINDOW_MS: i64 = 15 * 60 * 1000;
+
+/// A window entered this soon after it opened takes its first Chainlink price as the open
+const OPEN_TOLERANCE_MS: i64 = 5_000;
+
+/// How fast recorded time passes during a replay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// As fast as snapshots can be processed
+    Max,
+    /// Recorded time sped up by this factor (1 = real time)
+    Factor(f64),
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = anyhow::Error;
+
+    /// "max" (or 0), "realtime", or a factor such as "10" / "10x"
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "max" | "0" => Ok(Self::Max),
+            "realtime" | "real-time" => Ok(Self::Factor(1.0)),
+            other => {
+                let factor: f64 = other
+                    .trim_end_matches('x')
+                    .parse()
+                    .with_context(|| format!("Invalid replay speed '{}'", s))?;
+                if factor <= 0.0 {
+                    bail!("Replay speed must be positive, got {}", factor);
+                }
+                Ok(Self::Factor(factor))
+            }
+        }
+    }
+}
+
+/// Sleep out the recorded gap since the previous event at `speed`
+async fn pace(speed: ReplaySpeed, prev_ms: &mut Option<i64>, t_ms: i64) {
+    if let (ReplaySpeed::Factor(factor), Some(prev)) = (speed, *prev_ms) {
+        let gap = (t_ms - prev).clamp(0, MAX_PACED_GAP_MS);
+        tokio::time::sleep(Duration::from_secs_f64(gap as f64 / 1000.0 / factor)).await;
+    }
+    *prev_ms = Some(t_ms);
+}
+
+/// `events_snapshot.jsonl` for each day in `from..=to` that exists under `log_dir`
+pub fn snapshot_files(log_dir: &Path, from: NaiveDate, to: NaiveDate) -> Vec<PathBuf> {
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| log_dir.join(day.format("%Y-%m-%d").to_string()).join("events_snapshot.jsonl"))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Read recorded snapshots in time order, skipping lines that don't parse (e.g. a cut-off last line)
+pub fn read_snapshots(path: &Path) -> Result<Vec<SnapshotEvent>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut snapshots = Vec::new();
+    let mut skipped = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<SnapshotEvent>(&line) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(_) => skipped += 1,
+        }
+    }
+    if skipped > 0 {
+        tracing::warn!("Skipped {} unreadable lines in {}", skipped, path.display());
+    }
+    snapshots.sort_by_key(|s| s.t_recv_ms);
+    Ok(snapshots)
+}
+
+/// One day of recorded data
+#[derive(Debug, Clone)]
+pub enum Recording {
+    Snapshots(Vec<SnapshotEvent>),
+    Frames(Vec<RawFrame>),
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedDay {
+    pub date: NaiveDate,
+    pub recording: Recording,
+}
+
+impl RecordedDay {
+    pub fn len(&self) -> usize {
+        match &self.recording {
+            Recording::Snapshots(snapshots) => snapshots.len(),
+            Recording::Frames(frames) => frames.len(),
+        }
+    }
+}
+
+/// Load each recorded day in `from..=to` under `dir`: raw frames with `frames` set, else snapshots
+pub fn load_recordings(dir: &Path, from: NaiveDate, to: NaiveDate, frames: bool) -> Result<Vec<RecordedDay>> {
+    let files = if frames {
+        frame_files(dir, from, to)
+    } else {
+        snapshot_files(dir, from, to)
+    };
+    if files.is_empty() {
+        let name = if frames { "frames.jsonl.gz" } else { "events_snapshot.jsonl" };
+        bail!("No {} under {} for the requested days", name, dir.display());
+    }
+
+    let mut days = Vec::new();
+    for file in files {
+        // Files live in <dir>/<YYYY-MM-DD>/
+        let date = file
+            .parent()
+            .and_then(|day| day.file_name())
+            .and_then(|name| name.to_str())
+            .and_then(|name| NaiveDate::parse_from_str(name, "%Y-%m-%d").ok())
+            .with_context(|| format!("No date in {}", file.display()))?;
+        let recording = if frames {
+            Recording::Frames(read_frames(&file)?)
+        } else {
+            Recording::Snapshots(read_snapshots(&file)?)
+        };
+        days.push(RecordedDay { date, recording });
+    }
+    Ok(days)
+}
+
+/// How one window went
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowResult {
+    pub condition_id: String,
+    pub end_ms: i64,
+    pub trades: u32,             // orders that filled, at least in part
+    pub outcome: Option<String>, // "UP" / "DOWN"; None if the window couldn't be settled
+    pub pnl: f64,                // realized, net of fees
+    pub fees: f64,
+}
+
+/// What a replay did
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySummary {
+    pub snapshots: usize,
+    pub frames: usize,
+    pub first_ms: i64,
+    pub last_ms: i64,
+    pub windows: usize,
+    pub signals: BTreeMap<String, usize>, // strategy -> intents
+    pub lag_matches: usize,                // snapshots where the lag signal matched the recording
+    pub orders: usize,            // accepted by the paper exchange
+    pub rejected: usize,          // failed a risk check
+    pub exchange_rejected: usize, // refused by the paper exchange (e.g. post-only would cross)
+    pub failed: usize,            // errored before reaching the exchange
+    pub settled_windows: usize,
+    pub realized_pnl: f64,
+    pub fees_paid: f64,
+    pub window_results: Vec<WindowResult>,
+}
+
+impl std::fmt::Display for ReplaySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let span_secs = (self.last_ms - self.first_ms).max(0) / 1000;
+        let replayed = if self.frames > 0 {
+            format!("{} frames", self.frames)
+        } else {
+            format!("{} snapshots", self.snapshots)
+        };
+        writeln!(
+            f,
+            "Replayed {} over {}h{:02}m across {} windows",
+            replayed,
+            span_secs / 3600,
+            (span_secs % 3600) / 60,
+            self.windows
+        )?;
+        if self.snapshots > 0 {
+            writeln!(f, "Lag signal reproduced on {}/{} snapshots", self.lag_matches, self.snapshots)?;
+        }
+        for (strategy, count) in &self.signals {
+            writeln!(f, "  {:<12} {} signals", strategy, count)?;
+        }
+        writeln!(
+            f,
+            "Orders: {} placed, {} rejected by risk checks, {} rejected by the exchange, {} failed",
+            self.orders, self.rejected, self.exchange_rejected, self.failed
+        )?;
+        write!(
+            f,
+            "Settled {} windows: realized PnL {:+.2} (fees {:.2})",
+            self.settled_windows, self.realized_pnl, self.fees_paid
+        )
+    }
+}
+
+/// Window being replayed, identified by its recorded condition id or its end time
+#[derive(Debug, Clone)]
+struct ReplayWindow {
+    condition_id: String,
+    end_ms: i64,
+    target_price: Option<f64>,
+    open_price: Option<f64>,  // first Chainlink price, if the window was entered as it opened
+    close_price: Option<f64>, // last Chainlink price at or before the window's end
+    orders: Vec<String>,      // client order ids the paper exchange accepted
+    settled: bool,
+}
+
+/// A signal waiting out the assumed order latency
+#[derive(Debug, Clone)]
+struct PendingSignal {
+    due_ms: i64,
+    condition_id: String,
+    event: SignalEvent,
+}
+
+/// Drives `SignalService`, the strategies and the paper `TradeService` from recorded data
+/// on a simulated clock.
+///
+/// From snapshots, Polymarket quotes are injected as one-level books (`book_depth` shares
+/// at the recorded bid and ask) at the time the recording says they last changed, so
+/// staleness and time left reproduce what the live process saw. From raw frames, every
+/// service is fed exactly what it received live and strategies run on
+/// `strategy_interval_ms` of simulated time.
+pub struct ReplayEngine {
+    clock: Clock,
+    feeder: FrameFeeder,
+    chainlink: Arc<ChainlinkService>,
+    polymarket: Arc<PolymarketService>,
+    signal: Arc<SignalService>,
+    trade: Arc<TradeService>,
+    auto: Option<AutoTrader>,
+    book_depth: f64,
+    window: Option<ReplayWindow>,
+    last_quote_ms: i64,
+    strategy_interval_ms: i64,
+    last_eval_ms: i64,
+    latency_ms: i64,
+    fee_rate_bps: Option<u64>,
+    pending: VecDeque<PendingSignal>,
+    summary: ReplaySummary,
+}
+
+impl ReplayEngine {
+    /// Trades and the paper ledger are logged under `out_dir`. With `trade` set, the
+    /// configured auto-trade strategy places orders through the paper exchange.
+    pub fn new(config: &Config, out_dir: &str, book_depth: f64, trade: bool) -> Result<Self> {
+        let clock = Clock::simulated(0);
+        let polymarket = Arc::new(PolymarketService::new(config.polymarket.clone()).with_clock(clock.clone()));
+        let binance = Arc::new(BinanceBookService::new(config.binance.clone()).with_clock(clock.clone()));
+        let chainlink = Arc::new(ChainlinkService::new(&config.polymarket.rtds_url).with_clock(clock.clone()));
+        let signal = Arc::new(SignalService::new(
+            config.signal.clone(),
+            binance.clone(),
+            polymarket.clone(),
+            chainlink.clone(),
+        )?);
+        let feeder = FrameFeeder::new(clock.clone(), binance.clone(), polymarket.clone(), chainlink.clone());
+
+        let logger = JsonlLogger::new(out_dir)?;
+        let mut trading = config.trading.clone();
+        trading.positions_file = String::new();
+        let trade_service = Arc::new(TradeService::new(trading, polymarket.clone(), None, logger, true));
+
+        let auto = if trade {
+            let strategy = &config.auto_trade.strategy;
+            if !signal.strategy_names().contains(strategy) {
+                bail!("auto_trade.strategy '{}' is not one of signal.strategies", strategy);
+            }
+            trade_service.attach_auto_trader();
+            trade_service.set_auto_trade(true);
+            Some(AutoTrader::new(config.auto_trade.clone(), trade_service.clone(), polymarket.clone()))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            clock,
+            feeder,
+            chainlink,
+            polymarket,
+            signal,
+            trade: trade_service,
+            auto,
+            book_depth,
+            window: None,
+            last_quote_ms: 0,
+            strategy_interval_ms: config.signal.strategy_interval_ms.max(10) as i64,
+            last_eval_ms: i64::MIN,
+            latency_ms: 0,
+            fee_rate_bps: None,
+            pending: VecDeque::new(),
+            summary: ReplaySummary::default(),
+        })
+    }
+
+    /// Orders reach the book `latency_ms` after the signal that triggered them
+    pub fn with_latency(mut self, latency_ms: i64) -> Self {
+        self.latency_ms = latency_ms.max(0);
+        self
+    }
+
+    /// Charge `fee_rate_bps` in every window instead of the recorded or configured rate
+    pub fn with_fee_rate_bps(mut self, fee_rate_bps: Option<u64>) -> Self {
+        self.fee_rate_bps = fee_rate_bps;
+        self
+    }
+
+    /// Replay one recorded day, pacing it at `speed`
+    pub async fn run_day(&mut self, day: &RecordedDay, speed: ReplaySpeed) -> Result<()> {
+        match &day.recording {
+            Recording::Snapshots(snapshots) => self.run(snapshots, speed).await,
+            Recording::Frames(frames) => self.run_frames(frames, speed).await,
+        }
+    }
+
+    /// Replay `snapshots` in order, pacing them at `speed`
+    pub async fn run(&mut self, snapshots: &[SnapshotEvent], speed: ReplaySpeed) -> Result<()> {
+        let mut prev_ms: Option<i64> = None;
+        for snapshot in snapshots {
+            pace(speed, &mut prev_ms, snapshot.t_recv_ms).await;
+            self.step(snapshot).await?;
+        }
+        Ok(())
+    }
+
+    /// Replay raw recorded frames in order, pacing them at `speed`
+    pub async fn run_frames(&mut self, frames: &[RawFrame], speed: ReplaySpeed) -> Result<()> {
+        let mut prev_ms: Option<i64> = None;
+        for frame in frames {
+            pace(speed, &mut prev_ms, frame.t_recv_ms).await;
+            self.step_frame(frame).await?;
+        }
+        Ok(())
+    }
+
+    fn mark_time(&mut self, t_ms: i64) {
+        if self.summary.snapshots == 0 && self.summary.frames == 0 {
+            self.summary.first_ms = t_ms;
+        }
+        self.summary.last_ms = t_ms;
+    }
+
+    /// Advance the clock to one snapshot and run everything the live loop would
+    pub async fn step(&mut self, snapshot: &SnapshotEvent) -> Result<()> {
+        self.mark_time(snapshot.t_recv_ms);
+        self.summary.snapshots += 1;
+
+        self.clock.set_ms(snapshot.t_recv_ms);
+        self.roll_window(snapshot)?;
+        self.inject_quotes(snapshot);
+        self.clock.set_ms(snapshot.t_recv_ms);
+
+        self.trade.match_paper_orders()?;
+        self.trade.enforce_window_lifecycle().await;
+        self.execute_due().await;
+
+        let view = self.market_view(snapshot);
+        let result = self.evaluate_strategies(&view).await;
+        let state = self.signal.process_view(&view);
+        let side = state.suggested_side.map(|s| s.to_string()).unwrap_or("NONE".to_string());
+        if side == snapshot.signal_side {
+            self.summary.lag_matches += 1;
+        }
+        result
+    }
+
+    /// Feed one raw frame to its service; strategies run once per `strategy_interval_ms`
+    pub async fn step_frame(&mut self, frame: &RawFrame) -> Result<()> {
+        self.mark_time(frame.t_recv_ms);
+        self.summary.frames += 1;
+
+        self.feeder.apply(frame);
+        match frame.source {
+            FrameSource::Polymarket => self.trade.match_paper_orders()?,
+            FrameSource::Market => self.follow_market()?,
+            _ => {}
+        }
+        if let Some(window) = self.window.as_mut() {
+            let chainlink = self.chainlink.get_price_state();
+            if window.open_price.is_none() && frame.t_recv_ms <= window.end_ms - WINDOW_MS + OPEN_TOLERANCE_MS {
+                window.open_price = chainlink.btc_price;
+            }
+            // Prints stamped after the close don't count, even if they arrive before the rollover
+            if chainlink.timestamp_ms <= window.end_ms {
+                if let Some(price) = chainlink.btc_price {
+                    window.close_price = Some(price);
+                }
+            }
+        }
+        self.execute_due().await;
+
+        if frame.t_recv_ms - self.last_eval_ms < self.strategy_interval_ms {
+            return Ok(());
+        }
+        self.last_eval_ms = frame.t_recv_ms;
+        self.trade.enforce_window_lifecycle().await;
+        let view = self.signal.market_view();
+        self.evaluate_strategies(&view).await
+    }
+
+    async fn evaluate_strategies(&mut self, view: &MarketView) -> Result<()> {
+        for event in self.signal.evaluate_strategies(view) {
+            *self.summary.signals.entry(event.strategy.clone()).or_default() += 1;
+            let Some(auto) = &self.auto else { continue };
+            if event.strategy != auto.strategy() {
+                continue;
+            }
+            if self.latency_ms > 0 {
+                self.pending.push_back(PendingSignal {
+                    due_ms: view.t_ms + self.latency_ms,
+                    condition_id: self.polymarket.get_active_market().condition_id,
+                    event,
+                });
+            } else {
+                self.execute(&event).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the signals whose latency has passed; ones whose window has ended are dropped
+    async fn execute_due(&mut self) {
+        let now_ms = self.clock.now_ms();
+        while self.pending.front().is_some_and(|p| p.due_ms <= now_ms) {
+            let pending = self.pending.pop_front().expect("front checked above");
+            if pending.condition_id == self.polymarket.get_active_market().condition_id {
+                self.execute(&pending.event).await;
+            }
+        }
+    }
+
+    async fn execute(&mut self, event: &SignalEvent) {
+        let Some(auto) = &self.auto else { return };
+        match auto.on_signal(event).await {
+            Ok(Some(trade_event)) => {
+                if trade_event.accepted() {
+                    self.summary.orders += 1;
+                    if let Some(window) = self.window.as_mut() {
+                        window.orders.push(trade_event.client_order_id);
+                    }
+                } else {
+                    self.summary.exchange_rejected += 1;
+                }
+            }
+            Ok(None) => {}
+            Err(e) if e.is::<RiskRejected>() => self.summary.rejected += 1,
+            Err(e) => {
+                tracing::warn!("Replay order failed: {:#}", e);
+                self.summary.failed += 1;
+            }
+        }
+    }
+
+    /// Settle the last window if the recording reached its end, and report
+    pub fn finish(mut self) -> Result<ReplaySummary> {
+        if let Some(window) = self.window.take() {
+            if self.clock.now_ms() >= window.end_ms - 1000 {
+                self.settle(window)?;
+            }
+        }
+        self.summary.realized_pnl = self.trade.total_realized_pnl();
+        self.summary.fees_paid = self.trade.total_fees_paid();
+        Ok(self.summary)
+    }
+
+    /// Follow window changes: settle the one that ended and make the next one active
+    fn roll_window(&mut self, snapshot: &SnapshotEvent) -> Result<()> {
+        let Some(remaining) = snapshot.poly_remaining_secs else { return Ok(()) };
+        let end_ms = snapshot.t_recv_ms + remaining * 1000;
+        // Older recordings have no condition id: name windows by their end, to the minute
+        let condition_id = snapshot
+            .poly_condition_id
+            .clone()
+            .unwrap_or_else(|| format!("replay-{}", (end_ms + 30_000) / 60_000 * 60));
+
+        if self.enter_window(&condition_id, end_ms)? {
+            self.polymarket.set_active_market(ActiveMarket {
+                up_token_id: format!("{}-UP", condition_id),
+                down_token_id: format!("{}-DOWN", condition_id),
+                condition_id: condition_id.clone(),
+                end_date: chrono::DateTime::from_timestamp_millis(end_ms)
+                    .unwrap_or_default()
+                    .to_rfc3339(),
+                target_price: snapshot.poly_target_price,
+                ..Default::default()
+            });
+            self.apply_fee_override();
+            self.last_quote_ms = 0;
+        }
+
+        let window = self.window.as_mut().expect("window set above");
+        if window.target_price.is_none() {
+            if let Some(target) = snapshot.poly_target_price {
+                window.target_price = Some(target);
+                self.polymarket.force_set_target_price(target);
+            }
+        }
+        if window.open_price.is_none() && snapshot.t_recv_ms <= window.end_ms - WINDOW_MS + OPEN_TOLERANCE_MS {
+            window.open_price = snapshot.chainlink_price;
+        }
+        if snapshot.t_recv_ms <= window.end_ms {
+            if let Some(price) = snapshot.chainlink_price {
+                window.close_price = Some(price);
+            }
+        }
+        Ok(())
+    }
+
+    /// Make `condition_id` the current window, settling the previous one; true if it changed
+    fn enter_window(&mut self, condition_id: &str, end_ms: i64) -> Result<bool> {
+        if self.window.as_ref().is_some_and(|w| w.condition_id == condition_id) {
+            return Ok(false);
+        }
+        if let Some(previous) = self.window.take() {
+            self.settle(previous)?;
+        }
+        self.summary.windows += 1;
+        self.window = Some(ReplayWindow {
+            condition_id: condition_id.to_string(),
+            end_ms,
+            target_price: None,
+            open_price: None,
+            close_price: None,
+            orders: Vec::new(),
+            settled: false,
+        });
+        Ok(true)
+    }
+
+    /// Recorded market frames drive the window: track rollovers and target prices
+    fn follow_market(&mut self) -> Result<()> {
+        let market = self.polymarket.get_active_market();
+        if market.condition_id.is_empty() {
+            return Ok(());
+        }
+        let end_ms = chrono::DateTime::parse_from_rfc3339(&market.end_date)
+            .map(|end| end.timestamp_millis())
+            .unwrap_or(i64::MAX);
+        self.enter_window(&market.condition_id, end_ms)?;
+        self.apply_fee_override();
+        if let (Some(window), Some(target)) = (self.window.as_mut(), market.target_price) {
+            window.target_price = Some(target);
+        }
+        Ok(())
+    }
+
+    fn apply_fee_override(&self) {
+        if let Some(rate) = self.fee_rate_bps {
+            let condition_id = self.polymarket.get_active_market().condition_id;
+            self.polymarket.set_fee_rate_bps(&condition_id, rate);
+        }
+    }
+
+    fn settle(&mut self, mut window: ReplayWindow) -> Result<()> {
+        if window.settled {
+            return Ok(());
+        }
+        window.settled = true;
+        // The recorded target is the Chainlink open as the live process saw it
+        let outcome = match (window.target_price.or(window.open_price), window.close_price) {
+            (Some(target), Some(close)) => {
+                self.trade.settle_window(&window.condition_id, target, close)?;
+                self.summary.settled_windows += 1;
+                Some(if close >= target { "UP" } else { "DOWN" }.to_string())
+            }
+            _ => {
+                tracing::warn!("Window {} has no target or Chainlink close, not settled", window.condition_id);
+                None
+            }
+        };
+
+        let positions: Vec<_> = self
+            .trade
+            .get_positions()
+            .into_iter()
+            .filter(|p| p.condition_id == window.condition_id)
+            .collect();
+        // Post-only entries can rest unfilled: only orders that traded count
+        let orders = self.trade.order_store();
+        let trades = window
+            .orders
+            .iter()
+            .filter_map(|id| orders.get_by_client_id(id))
+            .filter(|o| o.size_matched > 0.0)
+            .count() as u32;
+        self.summary.window_results.push(WindowResult {
+            condition_id: window.condition_id,
+            end_ms: window.end_ms,
+            trades,
+            outcome,
+            pnl: positions.iter().map(|p| p.realized_pnl).sum(),
+            fees: positions.iter().map(|p| p.fees_paid).sum(),
+        });
+        Ok(())
+    }
+
+    /// Re-create the Polymarket books whenever the recording shows a newer quote update
+    fn inject_quotes(&mut self, snapshot: &SnapshotEvent) {
+        let market = self.polymarket.get_active_market();
+        if market.condition_id.is_empty() {
+            return;
+        }
+        let stale_ms = snapshot.poly_stale_ms.unwrap_or(0).max(0);
+        let updated_ms = snapshot.t_recv_ms - stale_ms;
+        if updated_ms <= self.last_quote_ms {
+            return;
+        }
+        self.last_quote_ms = updated_ms;
+
+        let level = |price: Option<f64>| match price {
+            Some(p) => format!(r#"[{{"price":"{}","size":"{}"}}]"#, p, self.book_depth),
+            None => "[]".to_string(),
+        };
+        let message = format!(
+            r#"[{{"event_type":"book","asset_id":"{}","market":"{}","bids":{},"asks":{}}},
+                {{"event_type":"book","asset_id":"{}","market":"{}","bids":{},"asks":{}}}]"#,
+            market.up_token_id,
+            market.condition_id,
+            level(snapshot.poly_yes_bid),
+            level(snapshot.poly_yes_ask),
+            market.down_token_id,
+            market.condition_id,
+            level(snapshot.poly_no_bid),
+            level(snapshot.poly_no_ask),
+        );
+        self.clock.set_ms(updated_ms);
+        self.polymarket.handle_message(&message);
+    }
+
+    /// Strategy inputs: Binance and index metrics as recorded, Polymarket state from the replayed service
+    fn market_view(&self, snapshot: &SnapshotEvent) -> MarketView {
+        let market = self.polymarket.get_active_market();
+        MarketView {
+            t_ms: snapshot.t_recv_ms,
+            binance_mid: snapshot.binance_mid,
+            binance_ret_1s: snapshot.binance_ret_1s,
+            binance_ret_3s: snapshot.binance_ret_3s,
+            binance_ret_10s: snapshot.binance_ret_10s,
+            binance_std_5m: snapshot.binance_std_5m,
+            binance_obi_top5: snapshot.binance_obi_top5,
+            binance_flow_1s: snapshot.binance_flow_1s.clone(),
+            binance_flow_3s: snapshot.binance_flow_3s.clone(),
+            binance_flow_10s: snapshot.binance_flow_10s.clone(),
+            index_price: snapshot.index_price,
+            index_ret_1s: snapshot.index_ret_1s,
+            index_ret_3s: snapshot.index_ret_3s,
+            index_ret_10s: snapshot.index_ret_10s,
+            index_std_5m: snapshot.index_std_5m,
+            quotes: self.polymarket.get_quote_state(),
+            poly_stale_ms: self.polymarket.get_staleness_ms(),
+            fees: self.polymarket.fee_model(),
+            chainlink_price: snapshot.chainlink_price,
+            target_price: market.target_price,
+            remaining_secs: self.polymarket.get_remaining_secs(),
+            condition_id: market.condition_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing;
+
+    const T0: i64 = 1_700_000_000_000;
+
+    fn snapshot(t_offset_ms: i64, remaining: i64) -> SnapshotEvent {
+        SnapshotEvent {
+            t_recv_ms: T0 + t_offset_ms,
+            binance_mid: Some(100_000.0),
+            binance_ret_1s: Some(0.0),
+            binance_ret_3s: Some(0.0),
+            poly_yes_bid: Some(0.48),
+            poly_yes_ask: Some(0.50),
+            poly_no_bid: Some(0.48),
+            poly_no_ask: Some(0.50),
+            poly_stale_ms: Some(100),
+            poly_target_price: Some(100_000.0),
+            poly_remaining_secs: Some(remaining),
+            poly_condition_id: Some("0xa".to_string()),
+            chainlink_price: Some(100_000.0),
+            ..Default::default()
+        }
+    }
+
+    fn replay_config() -> Config {
+        let mut config = testing::config();
+        config.signal.strategies = vec!["lag".to_string(), "momentum".to_string()];
+        config.auto_trade.strategy = "lag".to_string();
+        config.auto_trade.min_confidence = 0.5;
+        config.auto_trade.cooldown_secs = 0;
+        config.auto_trade.max_trades_per_window = 1;
+        config.auto_trade.max_position_per_window = 100.0;
+        config.auto_trade.min_remaining_secs = 0;
+        config.trading.default_size = 10.0;
+        config.trading.cancel_before_close_secs = 0;
+        // Take the ask so entries fill on the snapshot quotes
+        config.trading.post_only = false;
+        config
+    }
+
+    #[test]
+    fn test_replay_speed() {
+        assert_eq!("max".parse::<ReplaySpeed>().unwrap(), ReplaySpeed::Max);
+        assert_eq!("realtime".parse::<ReplaySpeed>().unwrap(), ReplaySpeed::Factor(1.0));
+        assert_eq!("20x".parse::<ReplaySpeed>().unwrap(), ReplaySpeed::Factor(20.0));
+        assert!("-1".parse::<ReplaySpeed>().is_err());
+        assert!("fast".parse::<ReplaySpeed>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_trades_and_settles() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = ReplayEngine::new(&replay_config(), dir.path().to_str().unwrap(), 1000.0, true).unwrap();
+
+        // Binance jumps while Polymarket hasn't updated for 800ms: the lag strategy buys YES
+        let mut jump = snapshot(1_000, 99);
+        jump.binance_ret_1s = Some(0.002);
+        jump.binance_ret_3s = Some(0.003);
+        jump.poly_stale_ms = Some(800);
+        jump.signal_side = "YES".to_string();
+        let mut close = snapshot(100_000, 0);
+        close.chainlink_price = Some(100_050.0);
+
+        let snapshots = vec![snapshot(0, 100), jump, close];
+        engine.run(&snapshots, ReplaySpeed::Max).await.unwrap();
+        assert_eq!(engine.trade.sellable_size(crate::events::TradeSide::Yes), 10.0);
+
+        // Up wins: 10 shares bought at 0.50 pay out 1.00
+        let summary = engine.finish().unwrap();
+        assert_eq!((summary.snapshots, summary.windows, summary.settled_windows), (3, 1, 1));
+        assert_eq!(summary.lag_matches, 3);
+        assert_eq!(summary.signals.get("lag"), Some(&1));
+        assert_eq!((summary.orders, summary.rejected, summary.exchange_rejected, summary.failed), (1, 0, 0, 0));
+        assert!((summary.realized_pnl - 5.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_latency_delays_fills() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = ReplayEngine::new(&replay_config(), dir.path().to_str().unwrap(), 1000.0, true)
+            .unwrap()
+            .with_latency(1_500);
+
+        let mut jump = snapshot(1_000, 99);
+        jump.binance_ret_1s = Some(0.002);
+        jump.binance_ret_3s = Some(0.003);
+        jump.poly_stale_ms = Some(800);
+        // By the time the order arrives the ask has moved up
+        let mut repriced = snapshot(3_000, 97);
+        repriced.poly_yes_bid = Some(0.58);
+        repriced.poly_yes_ask = Some(0.60);
+        repriced.poly_stale_ms = Some(0);
+        let mut close = snapshot(100_000, 0);
+        close.chainlink_price = Some(100_050.0);
+
+        engine.run(&[snapshot(0, 100), jump, snapshot(2_000, 98), repriced, close], ReplaySpeed::Max).await.unwrap();
+        let summary = engine.finish().unwrap();
+        assert_eq!(summary.orders, 1);
+        let window = &summary.window_results[0];
+        assert_eq!((window.trades, window.outcome.as_deref()), (1, Some("UP")));
+        assert!((window.pnl - 4.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_close_is_last_chainlink_print_before_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = ReplayEngine::new(&replay_config(), dir.path().to_str().unwrap(), 1000.0, false).unwrap();
+
+        let mut close = snapshot(100_000, 0);
+        close.chainlink_price = Some(99_950.0);
+        // Logged after the end but before the next window shows up
+        let mut late = snapshot(101_000, 0);
+        late.chainlink_price = Some(100_100.0);
+        // The next window has Binance but no Chainlink price
+        let mut next = snapshot(102_000, 0);
+        next.poly_condition_id = Some("0xb".to_string());
+        next.chainlink_price = None;
+
+        engine.run(&[snapshot(0, 100), close, late, next], ReplaySpeed::Max).await.unwrap();
+        let summary = engine.finish().unwrap();
+        assert_eq!((summary.windows, summary.settled_windows), (2, 1));
+        let outcomes: Vec<_> = summary.window_results.iter().map(|w| w.outcome.as_deref()).collect();
+        assert_eq!(outcomes, vec![Some("DOWN"), None]);
+    }
+}
@@ -0,0 +1,409 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::events::{SignalEvent, SignalScore};
+use crate::logger::JsonlLogger;
+use super::polymarket::QuoteState;
+use super::PolymarketService;
+
+/// Horizons a signal is scored at, besides the window's settlement
+pub const SCORE_HORIZONS_SECS: &[i64] = &[5, 30, 60];
+
+/// Width of the confidence buckets in the calibration table
+const BUCKET_WIDTH: f64 = 0.1;
+
+/// Signals whose window never reported a settlement are forgotten after this long
+const MAX_PENDING_MS: i64 = 20 * 60 * 1000;
+
+/// A signal waiting for its horizons and its window's settlement
+#[derive(Debug, Clone)]
+struct PendingSignal {
+    event: SignalEvent,
+    condition_id: String,
+    entry_price: Option<f64>,
+    horizons_left: Vec<i64>,
+}
+
+/// Mid of the side a signal suggested ("YES" / "NO")
+fn side_mid(quotes: &QuoteState, side: &str) -> Option<f64> {
+    let (bid, ask) = match side {
+        "YES" => (quotes.yes_bid, quotes.yes_ask),
+        "NO" => (quotes.no_bid, quotes.no_ask),
+        _ => return None,
+    };
+    Some((bid? + ask?) / 2.0)
+}
+
+/// Scores every strategy signal: did the suggested side's Polymarket mid rise 5s / 30s /
+/// 60s later, and did that side win the window. A strategy repeating its side within a
+/// window is one call, scored once from its first signal. Scores go to `signal_scores.jsonl`.
+pub struct SignalScorer {
+    polymarket: Arc<PolymarketService>,
+    logger: Arc<JsonlLogger>,
+    pending: Mutex<Vec<PendingSignal>>,
+    last_calls: Mutex<HashMap<String, (String, String)>>, // strategy -> (condition_id, side)
+}
+
+impl SignalScorer {
+    pub fn new(polymarket: Arc<PolymarketService>, logger: Arc<JsonlLogger>) -> Self {
+        Self {
+            polymarket,
+            logger,
+            pending: Mutex::new(Vec::new()),
+            last_calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn on_signal(&self, event: &SignalEvent) {
+        let condition_id = if event.features.condition_id.is_empty() {
+            self.polymarket.get_active_market().condition_id
+        } else {
+            event.features.condition_id.clone()
+        };
+        if condition_id.is_empty() {
+            return;
+        }
+        let call = (condition_id.clone(), event.suggested_side.clone());
+        if self.last_calls.lock().insert(event.strategy.clone(), call.clone()) == Some(call) {
+            return;
+        }
+        // Entry is the book the strategy saw, not whatever it is by the time this runs
+        let features = &event.features;
+        let quotes = QuoteState {
+            yes_bid: features.yes_bid,
+            yes_ask: features.yes_ask,
+            no_bid: features.no_bid,
+            no_ask: features.no_ask,
+            ..Default::default()
+        };
+        self.pending.lock().push(PendingSignal {
+            entry_price: side_mid(&quotes, &event.suggested_side),
+            event: event.clone(),
+            condition_id,
+            horizons_left: SCORE_HORIZONS_SECS.to_vec(),
+        });
+    }
+
+    fn score(&self, pending: &PendingSignal, horizon: String, now_ms: i64, exit_price: Option<f64>) {
+        let price_move = pending.entry_price.zip(exit_price).map(|(entry, exit)| exit - entry);
+        let score = SignalScore {
+            t_signal_ms: pending.event.t_recv_ms,
+            t_score_ms: now_ms,
+            strategy: pending.event.strategy.clone(),
+            suggested_side: pending.event.suggested_side.clone(),
+            confidence: pending.event.confidence,
+            condition_id: pending.condition_id.clone(),
+            horizon,
+            entry_price: pending.entry_price,
+            exit_price,
+            price_move,
+            // A mid that didn't move says nothing either way
+            hit: price_move.filter(|m| m.abs() > 1e-9).map(|m| m > 0.0),
+        };
+        if let Err(e) = self.logger.log_signal_score(score) {
+            tracing::error!("Failed to log signal score: {:?}", e);
+        }
+    }
+
+    /// Score the horizons that have passed. Once the window has moved on its quotes are
+    /// gone, so horizons still open are logged unscored.
+    pub fn tick(&self) {
+        let now_ms = self.polymarket.clock().now_ms();
+        let condition_id = self.polymarket.get_active_market().condition_id;
+        let quotes = self.polymarket.get_quote_state();
+        let mut pending = self.pending.lock();
+        for signal in pending.iter_mut() {
+            let same_window = signal.condition_id == condition_id;
+            let elapsed_ms = now_ms - signal.event.t_recv_ms;
+            let (due, later): (Vec<i64>, Vec<i64>) = signal
+                .horizons_left
+                .iter()
+                .partition(|h| !same_window || elapsed_ms >= **h * 1000);
+            for horizon in due {
+                let exit = if same_window { side_mid(&quotes, &signal.event.suggested_side) } else { None };
+                self.score(signal, format!("{}s", horizon), now_ms, exit);
+            }
+            signal.horizons_left = later;
+        }
+        pending.retain(|s| s.condition_id == condition_id || now_ms - s.event.t_recv_ms < MAX_PENDING_MS);
+    }
+
+    /// Score the window's signals against how it resolved ("UP" / "DOWN")
+    pub fn on_settlement(&self, condition_id: &str, winner: &str) {
+        let now_ms = self.polymarket.clock().now_ms();
+        let mut pending = self.pending.lock();
+        let (settled, rest): (Vec<_>, Vec<_>) = pending.drain(..).partition(|s| s.condition_id == condition_id);
+        *pending = rest;
+        for signal in settled {
+            for horizon in &signal.horizons_left {
+                self.score(&signal, format!("{}s", horizon), now_ms, None);
+            }
+            let won = (signal.event.suggested_side == "YES") == (winner == "UP");
+            self.score(&signal, "settlement".to_string(), now_ms, Some(if won { 1.0 } else { 0.0 }));
+        }
+    }
+}
+
+/// `signal_scores.jsonl` for each day in `from..=to` that exists under `log_dir`
+pub fn score_files(log_dir: &Path, from: NaiveDate, to: NaiveDate) -> Vec<PathBuf> {
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| log_dir.join(day.format("%Y-%m-%d").to_string()).join("signal_scores.jsonl"))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+pub fn read_scores(path: &Path) -> Result<Vec<SignalScore>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut scores = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(score) = serde_json::from_str::<SignalScore>(&line?) {
+            scores.push(score);
+        }
+    }
+    Ok(scores)
+}
+
+/// One confidence bucket of one strategy at one horizon
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CalibrationRow {
+    pub strategy: String,
+    pub horizon: String,
+    pub bucket: f64, // lower edge of the confidence bucket
+    pub signals: usize,
+    pub hits: usize,
+    pub mean_confidence: f64,
+    pub mean_move: f64,
+}
+
+impl CalibrationRow {
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 / self.signals.max(1) as f64
+    }
+}
+
+/// Realized hit rate by confidence bucket, per strategy and horizon; unscored entries are skipped
+pub fn calibration(scores: &[SignalScore]) -> Vec<CalibrationRow> {
+    let horizon_rank = |h: &str| h.trim_end_matches('s').parse::<i64>().unwrap_or(i64::MAX);
+    let mut rows: BTreeMap<(String, i64, String, i64), CalibrationRow> = BTreeMap::new();
+    for score in scores {
+        let Some(hit) = score.hit else { continue };
+        let bucket = ((score.confidence / BUCKET_WIDTH + 1e-9).floor() as i64).min((1.0 / BUCKET_WIDTH) as i64 - 1);
+        let key = (score.strategy.clone(), horizon_rank(&score.horizon), score.horizon.clone(), bucket);
+        let row = rows.entry(key).or_insert_with(|| CalibrationRow {
+            strategy: score.strategy.clone(),
+            horizon: score.horizon.clone(),
+            bucket: bucket as f64 * BUCKET_WIDTH,
+            ..Default::default()
+        });
+        row.signals += 1;
+        row.hits += hit as usize;
+        row.mean_confidence += score.confidence;
+        row.mean_move += score.price_move.unwrap_or(0.0);
+    }
+    rows.into_values()
+        .map(|mut row| {
+            row.mean_confidence /= row.signals as f64;
+            row.mean_move /= row.signals as f64;
+            row
+        })
+        .collect()
+}
+
+/// Calibration rows as a table, one block per strategy and horizon
+pub fn format_calibration(rows: &[CalibrationRow]) -> String {
+    let mut out = String::new();
+    let mut current = None;
+    for row in rows {
+        if current != Some((&row.strategy, &row.horizon)) {
+            current = Some((&row.strategy, &row.horizon));
+            out.push_str(&format!(
+                "\n{} @ {}\n{:<11} {:>7} {:>9} {:>9} {:>9}\n",
+                row.strategy, row.horizon, "CONFIDENCE", "SIGNALS", "MEAN CONF", "HIT RATE", "MEAN MOVE"
+            ));
+        }
+        out.push_str(&format!(
+            "{:<11} {:>7} {:>9.2} {:>8.1}% {:>+9.4}\n",
+            format!("{:.1}-{:.1}", row.bucket, row.bucket + BUCKET_WIDTH),
+            row.signals,
+            row.mean_confidence,
+            row.hit_rate() * 100.0,
+            row.mean_move
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::{book_msg, poly_config};
+    use crate::services::clock::Clock;
+    use crate::events::SignalFeatures;
+    use crate::services::ActiveMarket;
+
+    /// Books for an UP (bid, ask), with DOWN priced at the complement
+    fn book(up: (f64, f64)) -> String {
+        let complement = |p: f64| ((1.0 - p) * 100.0).round() / 100.0;
+        book_msg(up, (complement(up.1), complement(up.0)))
+    }
+
+    fn market(condition_id: &str) -> ActiveMarket {
+        ActiveMarket {
+            up_token_id: "UP".to_string(),
+            down_token_id: "DOWN".to_string(),
+            condition_id: condition_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn setup(dir: &Path) -> (Clock, Arc<PolymarketService>, SignalScorer) {
+        let logger = JsonlLogger::new(dir.to_str().unwrap()).unwrap();
+        let clock = Clock::simulated(1_000_000);
+        let poly = Arc::new(
+            PolymarketService::new(poly_config())
+            .with_clock(clock.clone()),
+        );
+        poly.set_active_market(market("0xcond"));
+        poly.handle_message(&book((0.48, 0.50)));
+        let scorer = SignalScorer::new(poly.clone(), logger);
+        (clock, poly, scorer)
+    }
+
+    /// A lag signal in window 0xcond, seen with YES at 0.48 / 0.50
+    fn signal(t_recv_ms: i64, side: &str) -> SignalEvent {
+        SignalEvent {
+            t_recv_ms,
+            strategy: "lag".to_string(),
+            suggested_side: side.to_string(),
+            confidence: 0.85,
+            reasons: Vec::new(),
+            binance_ret_1s: 0.0,
+            binance_ret_3s: 0.0,
+            poly_lag_ms: 0,
+            features: SignalFeatures {
+                condition_id: "0xcond".to_string(),
+                yes_bid: Some(0.48),
+                yes_ask: Some(0.50),
+                no_bid: Some(0.50),
+                no_ask: Some(0.52),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn scores(dir: &Path) -> Vec<SignalScore> {
+        let today = chrono::Local::now().date_naive();
+        score_files(dir, today, today).first().map(|path| read_scores(path).unwrap()).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_scores_horizons_and_settlement() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clock, poly, scorer) = setup(dir.path());
+        // The book has moved by the time the scorer sees the signal; entry is what the strategy saw
+        poly.handle_message(&book((0.50, 0.52)));
+        scorer.on_signal(&signal(1_000_000, "YES"));
+
+        // Up 3 cents after 5s, back below entry after 30s
+        clock.set_ms(1_005_000);
+        poly.handle_message(&book((0.51, 0.53)));
+        scorer.tick();
+        clock.set_ms(1_030_000);
+        poly.handle_message(&book((0.46, 0.48)));
+        scorer.tick();
+        scorer.on_settlement("0xcond", "UP");
+
+        let scores = scores(dir.path());
+        let by_horizon: BTreeMap<_, _> = scores.iter().map(|s| (s.horizon.as_str(), s)).collect();
+        assert_eq!(scores.len(), 4);
+        assert_eq!(by_horizon["5s"].hit, Some(true));
+        assert!((by_horizon["5s"].price_move.unwrap() - 0.03).abs() < 1e-9);
+        assert_eq!(by_horizon["30s"].hit, Some(false));
+        assert_eq!(by_horizon["60s"].hit, None);
+        assert_eq!(by_horizon["settlement"].hit, Some(true));
+
+        let rows = calibration(&scores);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].horizon, "5s");
+        assert!((rows[0].bucket - 0.8).abs() < 1e-9);
+        assert_eq!(rows[2].horizon, "settlement");
+        assert_eq!(rows[2].hit_rate(), 1.0);
+    }
+
+    #[test]
+    fn test_repeated_calls_scored_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clock, poly, scorer) = setup(dir.path());
+        scorer.on_signal(&signal(1_000_000, "YES"));
+        // The same call again after the cooldown is the same episode
+        clock.set_ms(1_006_000);
+        scorer.on_signal(&signal(1_006_000, "YES"));
+        // Flipping side is a new call, and so is flipping back
+        scorer.on_signal(&signal(1_006_000, "NO"));
+        scorer.on_signal(&signal(1_006_000, "YES"));
+        scorer.on_settlement("0xcond", "UP");
+
+        let settled: Vec<_> = scores(dir.path())
+            .into_iter()
+            .filter(|s| s.horizon == "settlement")
+            .map(|s| (s.t_signal_ms, s.suggested_side))
+            .collect();
+        assert_eq!(
+            settled,
+            vec![
+                (1_000_000, "YES".to_string()),
+                (1_006_000, "NO".to_string()),
+                (1_006_000, "YES".to_string())
+            ]
+        );
+
+        // The next window starts a new episode
+        poly.set_active_market(market("0xnext"));
+        let mut next = signal(1_010_000, "YES");
+        next.features.condition_id = "0xnext".to_string();
+        scorer.on_signal(&next);
+        assert_eq!(scorer.pending.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_flat_move_is_not_a_hit() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clock, _poly, scorer) = setup(dir.path());
+        scorer.on_signal(&signal(1_000_000, "YES"));
+        clock.set_ms(1_005_000);
+        scorer.tick();
+
+        let scores = scores(dir.path());
+        assert_eq!(scores.len(), 1);
+        assert_eq!((scores[0].price_move, scores[0].hit), (Some(0.0), None));
+        assert!(calibration(&scores).is_empty());
+    }
+
+    #[test]
+    fn test_unsettled_windows_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let (clock, poly, scorer) = setup(dir.path());
+        scorer.on_signal(&signal(1_000_000, "YES"));
+
+        // The window rolls over without settling: open horizons are logged unscored
+        poly.set_active_market(market("0xnext"));
+        clock.set_ms(1_001_000);
+        scorer.tick();
+        assert_eq!(scores(dir.path()).iter().filter(|s| s.hit.is_none()).count(), 3);
+        assert_eq!(scorer.pending.lock().len(), 1);
+
+        // Still waiting on its settlement until MAX_PENDING_MS, then forgotten
+        clock.set_ms(1_000_000 + MAX_PENDING_MS);
+        scorer.tick();
+        assert!(scorer.pending.lock().is_empty());
+        scorer.on_settlement("0xcond", "UP");
+        assert_eq!(scores(dir.path()).len(), 3);
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::Result;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::SettlementConfig;
+use crate::events::{SettledPosition, SettlementEvent};
+use crate::logger::JsonlLogger;
+use super::gamma::GammaClient;
+use super::polymarket::QuoteState;
+use super::{ActiveMarket, TradeService};
+
+/// Windows remembered as settled; the live loop only ever looks back one or two
+const SETTLED_HISTORY: usize = 16;
+
+/// Settles each window once at its end, and records how it resolved to `settlements.jsonl`:
+/// target vs Chainlink close, winning side, the last quotes seen and our positions.
+pub struct SettlementTracker {
+    config: SettlementConfig,
+    trade: Arc<TradeService>,
+    logger: Arc<JsonlLogger>,
+    gamma: GammaClient,
+    last_quotes: Mutex<Option<(String, QuoteState)>>,
+    settled: Mutex<VecDeque<String>>,
+}
+
+impl SettlementTracker {
+    pub fn new(config: SettlementConfig, trade: Arc<TradeService>, logger: Arc<JsonlLogger>, gamma_url: &str) -> Self {
+        Self {
+            config,
+            trade,
+            logger,
+            gamma: GammaClient::new(gamma_url),
+            last_quotes: Mutex::new(None),
+            settled: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Remember the window's latest quotes, so they can be recorded after it rolls over
+    pub fn observe(&self, condition_id: &str, quotes: &QuoteState) {
+        *self.last_quotes.lock() = Some((condition_id.to_string(), quotes.clone()));
+    }
+
+    pub fn is_settled(&self, condition_id: &str) -> bool {
+        self.settled.lock().iter().any(|id| id == condition_id)
+    }
+
+    /// Settle `market` against `close_price` and log it; None if it was already settled
+    /// or there's no target or close to settle on
+    pub fn settle(&self, market: &ActiveMarket, close_price: Option<f64>) -> Result<Option<SettlementEvent>> {
+        if market.condition_id.is_empty() || self.is_settled(&market.condition_id) {
+            return Ok(None);
+        }
+        let (Some(target), Some(close)) = (market.target_price, close_price) else {
+            return Ok(None);
+        };
+
+        let held: Vec<_> = self
+            .trade
+            .get_positions()
+            .into_iter()
+            .filter(|p| p.condition_id == market.condition_id && !p.settled)
+            .collect();
+        let realized_pnl = self.trade.settle_window(&market.condition_id, target, close)?;
+        let after = self.trade.get_positions();
+        let positions = held
+            .into_iter()
+            .map(|before| {
+                let settled = after
+                    .iter()
+                    .find(|p| p.condition_id == before.condition_id && p.token_id == before.token_id);
+                SettledPosition {
+                    token_id: before.token_id.clone(),
+                    side: before.side.clone(),
+                    size: before.size,
+                    avg_cost: before.avg_cost,
+                    payout: settled.and_then(|p| p.settlement_price),
+                    realized_pnl: settled.map(|p| p.realized_pnl).unwrap_or(before.realized_pnl),
+                    fees_paid: before.fees_paid,
+                }
+            })
+            .collect();
+
+        let quotes = match &*self.last_quotes.lock() {
+            Some((id, quotes)) if *id == market.condition_id => quotes.clone(),
+            _ => QuoteState::default(),
+        };
+        let winner = if close >= target { "UP" } else { "DOWN" };
+        let event = SettlementEvent {
+            t_recv_ms: chrono::Utc::now().timestamp_millis(),
+            source: "chainlink".to_string(),
+            condition_id: market.condition_id.clone(),
+            slug: market.slug.clone(),
+            end_date: market.end_date.clone(),
+            target_price: Some(target),
+            close_price: Some(close),
+            winner: Some(winner.to_string()),
+            yes_bid: quotes.yes_bid,
+            yes_ask: quotes.yes_ask,
+            no_bid: quotes.no_bid,
+            no_ask: quotes.no_ask,
+            positions,
+            realized_pnl,
+            agrees: None,
+        };
+        self.logger.log_settlement(event.clone())?;
+
+        let mut settled = self.settled.lock();
+        settled.push_back(market.condition_id.clone());
+        if settled.len() > SETTLED_HISTORY {
+            settled.pop_front();
+        }
+        Ok(Some(event))
+    }
+
+    /// Settle and, if configured, confirm against Gamma in the background
+    pub fn settle_and_confirm(self: &Arc<Self>, market: &ActiveMarket, close_price: Option<f64>) -> Option<SettlementEvent> {
+        match self.settle(market, close_price) {
+            Ok(Some(event)) => {
+                if self.config.confirm_with_gamma && !event.slug.is_empty() {
+                    tokio::spawn(self.clone().confirm(event.clone()));
+                }
+                Some(event)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::error!("Failed to settle window: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Gamma's record of how a window resolved. If it disagrees with our settlement, the
+    /// ledger is re-settled on Gamma's outcome, which is what the market pays out on.
+    fn resolve(&self, settlement: SettlementEvent, winner: String) -> SettlementEvent {
+        let agrees = settlement.winner.as_deref() == Some(winner.as_str());
+        let mut realized_pnl = settlement.realized_pnl;
+        let mut positions = settlement.positions.clone();
+        if !agrees {
+            tracing::warn!(
+                "Gamma resolved {} {} but Chainlink settlement said {:?}",
+                settlement.slug,
+                winner,
+                settlement.winner
+            );
+            match self.trade.resettle_window(&settlement.condition_id, &winner) {
+                Ok(delta) => {
+                    realized_pnl += delta;
+                    let after = self.trade.get_positions();
+                    for position in &mut positions {
+                        if let Some(p) = after
+                            .iter()
+                            .find(|p| p.condition_id == settlement.condition_id && p.token_id == position.token_id)
+                        {
+                            position.payout = p.settlement_price;
+                            position.realized_pnl = p.realized_pnl;
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("Failed to re-settle {}: {:?}", settlement.condition_id, e),
+            }
+        }
+        SettlementEvent {
+            t_recv_ms: chrono::Utc::now().timestamp_millis(),
+            source: "gamma".to_string(),
+            winner: Some(winner),
+            agrees: Some(agrees),
+            realized_pnl,
+            positions,
+            ..settlement
+        }
+    }
+
+    /// Poll Gamma until the market shows its resolution, then log it next to ours
+    pub async fn confirm(self: Arc<Self>, settlement: SettlementEvent) {
+        let poll = Duration::from_secs(self.config.confirm_poll_secs.max(5));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(self.config.confirm_timeout_secs);
+        loop {
+            tokio::time::sleep(poll).await;
+            match self.gamma.get_resolution(&settlement.slug).await {
+                Ok(Some(winner)) => {
+                    let event = self.resolve(settlement, winner);
+                    if let Err(e) = self.logger.log_settlement(event) {
+                        tracing::error!("Failed to log settlement: {:?}", e);
+                    }
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::debug!("Gamma resolution for {} not available: {:?}", settlement.slug, e),
+            }
+            if tokio::time::Instant::now() >= deadline {
+                tracing::warn!("Gamma hasn't resolved {} yet, giving up", settlement.slug);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::{book_msg, poly_config, trading_config};
+    use crate::events::TradeSide;
+    use crate::services::PolymarketService;
+    use std::io::BufRead;
+
+    #[tokio::test]
+    async fn test_settlement_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = JsonlLogger::new(dir.path().to_str().unwrap()).unwrap();
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let market = ActiveMarket {
+            up_token_id: "UP".to_string(),
+            down_token_id: "DOWN".to_string(),
+            condition_id: "0xcond".to_string(),
+            slug: "btc-updown-15m-test".to_string(),
+            target_price: Some(100_000.0),
+            ..Default::default()
+        };
+        poly.set_active_market(market.clone());
+        poly.handle_message(&book_msg((0.48, 0.50), (0.48, 0.50)));
+        let trade = Arc::new(TradeService::new(trading_config(), poly.clone(), None, logger.clone(), true));
+        trade.place_order(TradeSide::No).await.unwrap();
+
+        let tracker = SettlementTracker::new(SettlementConfig::default(), trade, logger, "https://test");
+        tracker.observe(&market.condition_id, &poly.get_quote_state());
+        assert!(tracker.settle(&market, None).unwrap().is_none());
+
+        // Down wins: 10 NO shares bought at 0.50 pay out 1.00
+        let event = tracker.settle(&market, Some(99_990.0)).unwrap().unwrap();
+        assert_eq!(event.winner.as_deref(), Some("DOWN"));
+        assert_eq!(event.yes_ask, Some(0.50));
+        assert_eq!(event.positions.len(), 1);
+        assert_eq!((event.positions[0].side.as_str(), event.positions[0].size), ("NO", 10.0));
+        assert_eq!(event.positions[0].payout, Some(1.0));
+        assert!((event.realized_pnl - 5.0).abs() < 1e-9);
+
+        // Only once per window
+        assert!(tracker.settle(&market, Some(99_990.0)).unwrap().is_none());
+        let today = chrono::Local::now().date_naive();
+        let path = dir.path().join(today.format("%Y-%m-%d").to_string()).join("settlements.jsonl");
+        let lines = std::io::BufReader::new(std::fs::File::open(path).unwrap()).lines().count();
+        assert_eq!(lines, 1);
+
+        // Gamma says Up won after all: the NO shares are re-settled as worthless
+        let resolved = tracker.resolve(event, "UP".to_string());
+        assert_eq!((resolved.source.as_str(), resolved.agrees), ("gamma", Some(false)));
+        assert_eq!(resolved.positions[0].payout, Some(0.0));
+        assert!((resolved.realized_pnl + 5.0).abs() < 1e-9);
+        assert!((tracker.trade.total_realized_pnl() + 5.0).abs() < 1e-9);
+        let agreed = tracker.resolve(resolved, "UP".to_string());
+        assert_eq!(agreed.agrees, Some(true));
+        assert!((tracker.trade.total_realized_pnl() + 5.0).abs() < 1e-9);
+    }
+}
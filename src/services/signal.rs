@@ -1,8 +1,11 @@
 use crate::config::SignalConfig;
 use crate::events::{SignalEvent, TradeSide};
-use crate::services::{BinanceBookService, PolymarketService};
-use parking_lot::RwLock;
+use crate::services::{ChainlinkService, IndexService, PolymarketService, SpotBook};
+use super::strategy::{build_strategy, Intent, MarketView, Strategy};
+use anyhow::{bail, Result};
+use parking_lot::{Mutex, RwLock};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 
 #[derive(Debug, Clone)]
@@ -13,6 +16,10 @@ pub struct SignalState {
     pub binance_ret_1s: f64,
     pub binance_ret_3s: f64,
     pub poly_lag_ms: i64,
+    pub fee_per_share: f64, // taker fee at the suggested side's ask
+    pub fair_up: Option<f64>,  // P(BTC closes at or above the target)
+    pub edge_yes: Option<f64>, // fair value - ask - taker fee, per share
+    pub edge_no: Option<f64>,
 }
 
 impl Default for SignalState {
@@ -24,129 +31,228 @@ impl Default for SignalState {
             binance_ret_1s: 0.0,
             binance_ret_3s: 0.0,
             poly_lag_ms: 0,
+            fee_per_share: 0.0,
+            fair_up: None,
+            edge_yes: None,
+            edge_no: None,
         }
     }
 }
 
+/// One configured strategy with its own event stream
+struct StrategySlot {
+    name: String,
+    strategy: Mutex<Box<dyn Strategy>>,
+    tx: broadcast::Sender<SignalEvent>,
+    // Intent from the latest evaluation, published or not
+    current: RwLock<Option<Intent>>,
+    // Last event published
+    last: RwLock<Option<SignalEvent>>,
+    cooldown_ms: i64,
+}
+
+impl StrategySlot {
+    /// Evaluate and publish the intent if it is a new call: a different side from the last
+    /// one published, or the same side once `cooldown_ms` has passed since
+    fn evaluate(&self, view: &MarketView) -> Option<SignalEvent> {
+        let intent = self.strategy.lock().evaluate(view);
+        *self.current.write() = intent.clone();
+        let intent = intent?;
+
+        let side = intent.side.to_string();
+        let mut last = self.last.write();
+        if last
+            .as_ref()
+            .is_some_and(|e| e.suggested_side == side && view.t_ms - e.t_recv_ms < self.cooldown_ms)
+        {
+            return None;
+        }
+        let event = SignalEvent {
+            t_recv_ms: view.t_ms,
+            strategy: self.name.clone(),
+            suggested_side: side,
+            confidence: intent.score,
+            reasons: intent.reasons,
+            binance_ret_1s: view.binance_ret_1s.unwrap_or(0.0),
+            binance_ret_3s: view.binance_ret_3s.unwrap_or(0.0),
+            poly_lag_ms: view.poly_stale_ms,
+            features: view.features(),
+        };
+        *last = Some(event.clone());
+        let _ = self.tx.send(event.clone());
+        Some(event)
+    }
+}
+
 pub struct SignalService {
     config: SignalConfig,
-    binance: Arc<BinanceBookService>,
+    // Spot book the binance_* inputs are read from (Binance or the configured venues)
+    spot: Arc<dyn SpotBook>,
+    index: Option<Arc<IndexService>>,
     polymarket: Arc<PolymarketService>,
+    chainlink: Arc<ChainlinkService>,
+    strategies: Vec<Arc<StrategySlot>>,
     signal_state: Arc<RwLock<SignalState>>,
-    signal_tx: broadcast::Sender<SignalEvent>,
 }
 
 impl SignalService {
+    /// Fails if `config.strategies` names a strategy that doesn't exist
     pub fn new(
         config: SignalConfig,
-        binance: Arc<BinanceBookService>,
+        spot: Arc<dyn SpotBook>,
         polymarket: Arc<PolymarketService>,
-    ) -> Self {
-        let (tx, _) = broadcast::channel(100);
-        Self {
+        chainlink: Arc<ChainlinkService>,
+    ) -> Result<Self> {
+        let mut strategies = Vec::new();
+        for name in &config.strategies {
+            if strategies.iter().any(|s: &Arc<StrategySlot>| &s.name == name) {
+                bail!("Strategy '{}' is listed twice", name);
+            }
+            let (strategy_tx, _) = broadcast::channel(100);
+            strategies.push(Arc::new(StrategySlot {
+                name: name.clone(),
+                strategy: Mutex::new(build_strategy(name, &config)?),
+                tx: strategy_tx,
+                current: RwLock::new(None),
+                last: RwLock::new(None),
+                cooldown_ms: config.strategy_cooldown_ms as i64,
+            }));
+        }
+
+        Ok(Self {
             config,
-            binance,
+            spot,
+            index: None,
             polymarket,
+            chainlink,
+            strategies,
             signal_state: Arc::new(RwLock::new(SignalState::default())),
-            signal_tx: tx,
-        }
+        })
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<SignalEvent> {
-        self.signal_tx.subscribe()
+    /// Give the strategies the composite index alongside the spot book
+    pub fn with_index(mut self, index: Arc<IndexService>) -> Self {
+        self.index = Some(index);
+        self
     }
 
     pub fn get_signal_state(&self) -> SignalState {
         self.signal_state.read().clone()
     }
 
-    pub fn compute_signal(&self) -> SignalState {
-        let mut state = SignalState::default();
-        let mut reasons = Vec::new();
-
-        // Get Binance returns
-        let ret_1s = self.binance.get_returns(1000).unwrap_or(0.0);
-        let ret_3s = self.binance.get_returns(3000).unwrap_or(0.0);
-        let _ret_10s = self.binance.get_returns(10000).unwrap_or(0.0);
-
-        state.binance_ret_1s = ret_1s;
-        state.binance_ret_3s = ret_3s;
-
-        // Get Polymarket staleness
-        let poly_stale_ms = self.polymarket.get_staleness_ms();
-        state.poly_lag_ms = poly_stale_ms;
-
-        // Check for significant Binance move
-        let significant_up_1s = ret_1s > self.config.binance_return_threshold_1s;
-        let significant_down_1s = ret_1s < -self.config.binance_return_threshold_1s;
-        let significant_up_3s = ret_3s > self.config.binance_return_threshold_3s;
-        let significant_down_3s = ret_3s < -self.config.binance_return_threshold_3s;
-
-        // Check if Polymarket might be lagging
-        let poly_lagging = poly_stale_ms > self.config.poly_lag_threshold_ms as i64;
-
-        // Compute signal
-        let mut score = 0.0;
-        let mut suggested_side: Option<TradeSide> = None;
-
-        // Strong signal: 1s move with poly lag
-        if significant_up_1s && poly_lagging {
-            score += 0.5;
-            suggested_side = Some(TradeSide::Yes);
-            reasons.push(format!(
-                "BTC up {:.4}% in 1s, Poly lag {}ms",
-                ret_1s * 100.0,
-                poly_stale_ms
-            ));
-        } else if significant_down_1s && poly_lagging {
-            score += 0.5;
-            suggested_side = Some(TradeSide::No);
-            reasons.push(format!(
-                "BTC down {:.4}% in 1s, Poly lag {}ms",
-                ret_1s.abs() * 100.0,
-                poly_stale_ms
-            ));
-        }
+    /// Names of the configured strategies, in config order
+    pub fn strategy_names(&self) -> Vec<String> {
+        self.strategies.iter().map(|s| s.name.clone()).collect()
+    }
 
-        // Additional confidence from 3s confirmation
-        if significant_up_3s && matches!(suggested_side, Some(TradeSide::Yes)) {
-            score += 0.3;
-            reasons.push(format!("3s uptrend confirms: {:.4}%", ret_3s * 100.0));
-        } else if significant_down_3s && matches!(suggested_side, Some(TradeSide::No)) {
-            score += 0.3;
-            reasons.push(format!("3s downtrend confirms: {:.4}%", ret_3s.abs() * 100.0));
-        }
+    /// Event stream of one configured strategy
+    pub fn subscribe_strategy(&self, name: &str) -> Option<broadcast::Receiver<SignalEvent>> {
+        self.strategies.iter().find(|s| s.name == name).map(|s| s.tx.subscribe())
+    }
+
+    /// Most recent intent published by each configured strategy
+    pub fn strategy_signals(&self) -> Vec<(String, Option<SignalEvent>)> {
+        self.strategies
+            .iter()
+            .map(|s| (s.name.clone(), s.last.read().clone()))
+            .collect()
+    }
 
-        // Only signal if above threshold
-        if score < self.config.min_confidence {
-            suggested_side = None;
-            score = 0.0;
-            reasons.clear();
+    /// Snapshot of everything the strategies look at
+    pub fn market_view(&self) -> MarketView {
+        let market = self.polymarket.get_active_market();
+        MarketView {
+            t_ms: self.polymarket.clock().now_ms(),
+            binance_mid: self.spot.get_mid_price(),
+            binance_ret_1s: self.spot.get_returns(1000),
+            binance_ret_3s: self.spot.get_returns(3000),
+            binance_ret_10s: self.spot.get_returns(10000),
+            binance_std_5m: self.spot.get_std_dev(300_000),
+            binance_obi_top5: self.spot.get_current_update().map(|u| u.imbalance_top5),
+            binance_flow_1s: self.spot.get_flow(1000),
+            binance_flow_3s: self.spot.get_flow(3000),
+            binance_flow_10s: self.spot.get_flow(10000),
+            index_price: self.index.as_ref().and_then(|i| i.get_price()),
+            index_ret_1s: self.index.as_ref().and_then(|i| i.get_returns(1000)),
+            index_ret_3s: self.index.as_ref().and_then(|i| i.get_returns(3000)),
+            index_ret_10s: self.index.as_ref().and_then(|i| i.get_returns(10000)),
+            index_std_5m: self.index.as_ref().and_then(|i| i.get_std_dev(300_000)),
+            quotes: self.polymarket.get_quote_state(),
+            poly_stale_ms: self.polymarket.get_staleness_ms(),
+            fees: self.polymarket.fee_model(),
+            chainlink_price: self.chainlink.get_btc_price(),
+            target_price: market.target_price,
+            remaining_secs: self.polymarket.get_remaining_secs(),
+            condition_id: market.condition_id,
         }
+    }
 
-        state.suggested_side = suggested_side;
-        state.confidence = score;
-        state.reasons = reasons;
+    /// P(BTC closes at or above the window's target) from the distance to the target,
+    /// recent volatility and time left; None until all three are known
+    pub fn fair_value(&self) -> Option<f64> {
+        self.market_view().fair_up(self.config.vol_floor_per_sec)
+    }
 
-        // Update internal state and emit
-        *self.signal_state.write() = state.clone();
+    /// Evaluate every configured strategy against one view, returning the new calls
+    /// published to their streams
+    pub fn evaluate_strategies(&self, view: &MarketView) -> Vec<SignalEvent> {
+        self.strategies.iter().filter_map(|s| s.evaluate(view)).collect()
+    }
+
+    /// Run each configured strategy in its own task on `strategy_interval_ms`
+    pub async fn run_strategies(self: Arc<Self>) {
+        let period = Duration::from_millis(self.config.strategy_interval_ms.max(10));
+        let handles: Vec<_> = self
+            .strategies
+            .iter()
+            .map(|slot| {
+                let slot = slot.clone();
+                let service = self.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(period);
+                    loop {
+                        interval.tick().await;
+                        slot.evaluate(&service.market_view());
+                    }
+                })
+            })
+            .collect();
+        tracing::info!("Running strategies: {}", self.strategy_names().join(", "));
+        futures_util::future::join_all(handles).await;
+    }
 
-        if state.suggested_side.is_some() {
-            let event = SignalEvent {
-                t_recv_ms: chrono::Utc::now().timestamp_millis(),
-                suggested_side: state
-                    .suggested_side
-                    .map(|s| s.to_string())
-                    .unwrap_or("NONE".to_string()),
-                confidence: state.confidence,
-                reasons: state.reasons.clone(),
-                binance_ret_1s: state.binance_ret_1s,
-                binance_ret_3s: state.binance_ret_3s,
-                poly_lag_ms: state.poly_lag_ms,
-            };
-            let _ = self.signal_tx.send(event);
+    /// The primary signal shown in the TUI and snapshots: the lag strategy's current intent
+    /// (when it is configured), plus fair value and edges
+    pub fn compute_signal(&self) -> SignalState {
+        self.process_view(&self.market_view())
+    }
+
+    /// `compute_signal` on a view built elsewhere (e.g. from recorded snapshots). Reads the
+    /// lag strategy's last evaluation rather than evaluating it again.
+    pub fn process_view(&self, view: &MarketView) -> SignalState {
+        let mut state = SignalState {
+            binance_ret_1s: view.binance_ret_1s.unwrap_or(0.0),
+            binance_ret_3s: view.binance_ret_3s.unwrap_or(0.0),
+            poly_lag_ms: view.poly_stale_ms,
+            ..Default::default()
+        };
+
+        let lag = self.strategies.iter().find(|s| s.name == "lag");
+        if let Some(intent) = lag.and_then(|s| s.current.read().clone()) {
+            state.fee_per_share = view.ask(intent.side).map(|a| view.fees.fee_per_share(a)).unwrap_or(0.0);
+            state.suggested_side = Some(intent.side);
+            state.confidence = intent.score;
+            state.reasons = intent.reasons;
+        }
+
+        // Edge of buying each side at its ask against the model, after taker fees
+        if let Some(fair_up) = view.fair_up(self.config.vol_floor_per_sec) {
+            state.fair_up = Some(fair_up);
+            state.edge_yes = view.edge(TradeSide::Yes, fair_up);
+            state.edge_no = view.edge(TradeSide::No, fair_up);
         }
 
+        *self.signal_state.write() = state.clone();
         state
     }
 }
@@ -154,36 +260,11 @@ impl SignalService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{BinanceConfig, PolymarketConfig};
-
-    fn make_signal_config() -> SignalConfig {
-        SignalConfig {
-            binance_return_threshold_1s: 0.001,
-            binance_return_threshold_3s: 0.002,
-            poly_lag_threshold_ms: 500,
-            min_confidence: 0.5,
-        }
-    }
+    use crate::services::testing::{binance_config, poly_config, signal_config};
+    use crate::services::BinanceBookService;
 
-    fn make_binance_config() -> BinanceConfig {
-        BinanceConfig {
-            ws_url: "wss://test".to_string(),
-            rest_url: "https://test".to_string(),
-            symbol: "BTCUSD".to_string(),
-            snapshot_limit: 100,
-        }
-    }
-
-    fn make_poly_config() -> PolymarketConfig {
-        PolymarketConfig {
-            ws_url: "wss://test".to_string(),
-            rest_url: "https://test".to_string(),
-            gamma_url: "https://gamma-api.polymarket.com".to_string(),
-            btc_15m_event_id: "194059".to_string(),
-            yes_token_id: "yes".to_string(),
-            no_token_id: "no".to_string(),
-            condition_id: "cond".to_string(),
-        }
+    fn chainlink() -> Arc<ChainlinkService> {
+        Arc::new(ChainlinkService::new("wss://test"))
     }
 
     #[test]
@@ -196,9 +277,9 @@ mod tests {
 
     #[test]
     fn test_signal_service_creation() {
-        let binance = Arc::new(BinanceBookService::new(make_binance_config()));
-        let poly = Arc::new(PolymarketService::new(make_poly_config()));
-        let signal = SignalService::new(make_signal_config(), binance, poly);
+        let binance = Arc::new(BinanceBookService::new(binance_config()));
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let signal = SignalService::new(signal_config(), binance, poly, chainlink()).unwrap();
 
         let state = signal.get_signal_state();
         assert!(state.suggested_side.is_none());
@@ -206,13 +287,74 @@ mod tests {
 
     #[test]
     fn test_compute_signal_no_data() {
-        let binance = Arc::new(BinanceBookService::new(make_binance_config()));
-        let poly = Arc::new(PolymarketService::new(make_poly_config()));
-        let signal = SignalService::new(make_signal_config(), binance, poly);
+        let binance = Arc::new(BinanceBookService::new(binance_config()));
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let signal = SignalService::new(signal_config(), binance, poly, chainlink()).unwrap();
 
         // With no data, should return no signal
         let state = signal.compute_signal();
         assert!(state.suggested_side.is_none());
         assert_eq!(state.confidence, 0.0);
+        assert!(state.fair_up.is_none() && state.edge_yes.is_none());
+    }
+
+    #[test]
+    fn test_strategies_from_config() {
+        let binance = Arc::new(BinanceBookService::new(binance_config()));
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let signal = SignalService::new(signal_config(), binance.clone(), poly.clone(), chainlink()).unwrap();
+        assert_eq!(signal.strategy_names(), vec!["lag", "fair_value"]);
+        assert!(signal.subscribe_strategy("momentum").is_none());
+
+        // Each strategy publishes on its own stream
+        let mut fv_rx = signal.subscribe_strategy("fair_value").unwrap();
+        let mut view = signal.market_view();
+        view.target_price = Some(100_000.0);
+        view.binance_mid = Some(100_100.0);
+        view.binance_std_5m = Some(50.0);
+        view.remaining_secs = Some(60);
+        view.quotes.yes_ask = Some(0.60);
+        let events = signal.evaluate_strategies(&view);
+        assert_eq!(events.len(), 1);
+        let event = fv_rx.try_recv().unwrap();
+        assert_eq!((event.strategy.as_str(), event.suggested_side.as_str()), ("fair_value", "YES"));
+        assert!(signal.strategy_signals()[0].1.is_none());
+
+        let mut config = signal_config();
+        config.strategies = vec!["lag".to_string(), "nope".to_string()];
+        assert!(SignalService::new(config, binance, poly, chainlink()).is_err());
+    }
+
+    #[test]
+    fn test_strategies_publish_new_calls_only() {
+        let binance = Arc::new(BinanceBookService::new(binance_config()));
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let signal = SignalService::new(signal_config(), binance, poly, chainlink()).unwrap();
+        let mut view = signal.market_view();
+        view.t_ms = 1_000;
+        view.binance_ret_1s = Some(0.0015);
+        view.binance_ret_3s = Some(0.0025);
+        view.poly_stale_ms = 800;
+        view.quotes.yes_ask = Some(0.50);
+        view.quotes.no_ask = Some(0.50);
+        assert_eq!(signal.evaluate_strategies(&view).len(), 1);
+        assert_eq!(signal.process_view(&view).suggested_side, Some(TradeSide::Yes));
+
+        // Still calling YES on the next ticks: nothing new until the cooldown is up
+        view.t_ms += 250;
+        assert!(signal.evaluate_strategies(&view).is_empty());
+        assert_eq!(signal.process_view(&view).suggested_side, Some(TradeSide::Yes));
+        view.t_ms += 5_000;
+        assert_eq!(signal.evaluate_strategies(&view).len(), 1);
+
+        // Flipping side is a new call straight away; standing aside clears the primary signal
+        view.t_ms += 250;
+        view.binance_ret_1s = Some(-0.0015);
+        view.binance_ret_3s = Some(-0.0025);
+        assert_eq!(signal.evaluate_strategies(&view)[0].suggested_side, "NO");
+        view.poly_stale_ms = 100;
+        assert!(signal.evaluate_strategies(&view).is_empty());
+        assert!(signal.process_view(&view).suggested_side.is_none());
+        assert_eq!(signal.strategy_signals()[0].1.as_ref().unwrap().suggested_side, "NO");
     }
 }
@@ -0,0 +1,643 @@
+use anyhow::{Context, Result};
+use k256::ecdsa::SigningKey;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+
+/// Polygon mainnet chain ID (the only chain the production CLOB settles on)
+pub const POLYGON_CHAIN_ID: u64 = 137;
+
+// CTF Exchange contracts on Polygon mainnet
+// Must match: https://github.com/Polymarket/py-clob-client/blob/main/py_clob_client/config.py
+const CTF_EXCHANGE: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+const NEG_RISK_CTF_EXCHANGE: &str = "0xC5d563A36AE78145C8a4D4E9a7b82E3DF0f1C6c4";
+// CTF Exchange contracts on Amoy testnet
+const AMOY_CHAIN_ID: u64 = 80002;
+const AMOY_CTF_EXCHANGE: &str = "0xdFE02Eb6733538f8Ea35D585af8DE5958AD99E40";
+const AMOY_NEG_RISK_CTF_EXCHANGE: &str = "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296";
+
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+const EXCHANGE_DOMAIN_NAME: &str = "Polymarket CTF Exchange";
+const EXCHANGE_DOMAIN_VERSION: &str = "1";
+
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const ORDER_TYPE: &str = "Order(uint256 salt,address maker,address signer,address taker,uint256 tokenId,uint256 makerAmount,uint256 takerAmount,uint256 expiration,uint256 nonce,uint256 feeRateBps,uint8 side,uint8 signatureType)";
+
+// L1 auth message signed to create/derive CLOB API keys
+// Must match: https://github.com/Polymarket/py-clob-client/blob/main/py_clob_client/signing/eip712.py
+const CLOB_AUTH_DOMAIN_NAME: &str = "ClobAuthDomain";
+const CLOB_AUTH_DOMAIN_VERSION: &str = "1";
+const CLOB_AUTH_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId)";
+const CLOB_AUTH_TYPE: &str = "ClobAuth(address address,string timestamp,uint256 nonce,string message)";
+const CLOB_AUTH_MESSAGE: &str = "This message attests that I control the given wallet";
+
+/// Collateral (USDC) and conditional tokens both use 6 decimals on-chain
+const TOKEN_DECIMALS: u32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        }
+    }
+
+    fn as_u8(&self) -> u8 {
+        match self {
+            OrderSide::Buy => 0,
+            OrderSide::Sell => 1,
+        }
+    }
+}
+
+impl std::str::FromStr for OrderSide {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "BUY" => Ok(OrderSide::Buy),
+            "SELL" => Ok(OrderSide::Sell),
+            other => anyhow::bail!("Unknown order side: {}", other),
+        }
+    }
+}
+
+/// How the exchange verifies the order signature.
+/// EOA = the signer holds the funds; the proxy variants sign for a Polymarket proxy/safe wallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignatureType {
+    #[default]
+    Eoa,
+    PolyProxy,
+    PolyGnosisSafe,
+}
+
+impl SignatureType {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            SignatureType::Eoa => 0,
+            SignatureType::PolyProxy => 1,
+            SignatureType::PolyGnosisSafe => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for SignatureType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(SignatureType::Eoa),
+            1 => Ok(SignatureType::PolyProxy),
+            2 => Ok(SignatureType::PolyGnosisSafe),
+            other => anyhow::bail!("Unknown signature type: {}", other),
+        }
+    }
+}
+
+/// Decimal places used when converting price/size into on-chain amounts, per tick size.
+#[derive(Debug, Clone, Copy)]
+struct RoundConfig {
+    price: u32,
+    size: u32,
+    amount: u32,
+}
+
+fn round_config(tick_size: Decimal) -> Result<RoundConfig> {
+    let price = tick_size.normalize().scale();
+    if !(1..=4).contains(&price) || tick_size != Decimal::new(1, price) {
+        anyhow::bail!("Unsupported tick size: {}", tick_size);
+    }
+    Ok(RoundConfig { price, size: 2, amount: price + 2 })
+}
+
+fn round_down(value: Decimal, dp: u32) -> Decimal {
+    value.round_dp_with_strategy(dp, rust_decimal::RoundingStrategy::ToZero)
+}
+
+fn round_up(value: Decimal, dp: u32) -> Decimal {
+    value.round_dp_with_strategy(dp, rust_decimal::RoundingStrategy::AwayFromZero)
+}
+
+fn to_token_decimals(value: Decimal) -> Result<u128> {
+    let scaled = (value * Decimal::from(10u64.pow(TOKEN_DECIMALS))).round();
+    u128::try_from(scaled).context("Amount out of range")
+}
+
+/// Convert a limit price and share size into (makerAmount, takerAmount) in 6-decimal fixed point.
+/// BUY: maker gives USDC (size * price), taker gives shares. SELL is the reverse.
+/// Mirrors `get_order_amounts` in py-clob-client's order builder.
+pub fn order_amounts(side: OrderSide, size: Decimal, price: Decimal, tick_size: Decimal) -> Result<(u128, u128)> {
+    let rc = round_config(tick_size)?;
+    let price = price.round_dp(rc.price);
+    if price < tick_size || price > Decimal::ONE - tick_size {
+        anyhow::bail!("Price {} outside valid range for tick size {}", price, tick_size);
+    }
+
+    let shares = round_down(size, rc.size);
+    let mut notional = shares * price;
+    if notional.normalize().scale() > rc.amount {
+        notional = round_up(notional, rc.amount + 4);
+        if notional.normalize().scale() > rc.amount {
+            notional = round_down(notional, rc.amount);
+        }
+    }
+
+    let shares = to_token_decimals(shares)?;
+    let notional = to_token_decimals(notional)?;
+    match side {
+        OrderSide::Buy => Ok((notional, shares)),
+        OrderSide::Sell => Ok((shares, notional)),
+    }
+}
+
+/// Unsigned CTF Exchange order struct.
+#[derive(Debug, Clone)]
+pub struct ExchangeOrder {
+    pub salt: u64,
+    pub maker: String,
+    pub signer: String,
+    pub taker: String,
+    pub token_id: String,
+    pub maker_amount: u128,
+    pub taker_amount: u128,
+    pub expiration: u64,
+    pub nonce: u64,
+    pub fee_rate_bps: u64,
+    pub side: OrderSide,
+    pub signature_type: SignatureType,
+}
+
+impl ExchangeOrder {
+    fn struct_hash(&self) -> Result<[u8; 32]> {
+        let mut encoded = Vec::with_capacity(13 * 32);
+        encoded.extend_from_slice(&keccak256(ORDER_TYPE.as_bytes()));
+        encoded.extend_from_slice(&encode_uint(self.salt as u128));
+        encoded.extend_from_slice(&encode_address(&self.maker)?);
+        encoded.extend_from_slice(&encode_address(&self.signer)?);
+        encoded.extend_from_slice(&encode_address(&self.taker)?);
+        encoded.extend_from_slice(&encode_uint_str(&self.token_id)?);
+        encoded.extend_from_slice(&encode_uint(self.maker_amount));
+        encoded.extend_from_slice(&encode_uint(self.taker_amount));
+        encoded.extend_from_slice(&encode_uint(self.expiration as u128));
+        encoded.extend_from_slice(&encode_uint(self.nonce as u128));
+        encoded.extend_from_slice(&encode_uint(self.fee_rate_bps as u128));
+        encoded.extend_from_slice(&encode_uint(self.side.as_u8() as u128));
+        encoded.extend_from_slice(&encode_uint(self.signature_type.as_u8() as u128));
+        Ok(keccak256(&encoded))
+    }
+}
+
+/// Signed order in the JSON shape the CLOB `POST /order` endpoint expects.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedOrder {
+    pub salt: u64,
+    pub maker: String,
+    pub signer: String,
+    pub taker: String,
+    pub token_id: String,
+    pub maker_amount: String,
+    pub taker_amount: String,
+    pub expiration: String,
+    pub nonce: String,
+    pub fee_rate_bps: String,
+    pub side: String,
+    pub signature_type: u8,
+    pub signature: String,
+}
+
+/// Signs CTF Exchange orders with the wallet private key.
+pub struct OrderSigner {
+    key: SigningKey,
+    address: String,
+    chain_id: u64,
+    signature_type: SignatureType,
+    funder: String,
+}
+
+impl OrderSigner {
+    pub fn from_private_key(private_key: &str, chain_id: u64) -> Result<Self> {
+        let key_hex = private_key.trim().trim_start_matches("0x");
+        let key_bytes = hex::decode(key_hex).context("Private key is not valid hex")?;
+        let key = SigningKey::from_slice(&key_bytes).context("Invalid secp256k1 private key")?;
+
+        let public = key.verifying_key().to_encoded_point(false);
+        // Address = last 20 bytes of keccak256(uncompressed pubkey without 0x04 prefix)
+        let hash = keccak256(&public.as_bytes()[1..]);
+        let address = to_checksum_address(&hash[12..]);
+
+        Ok(Self {
+            key,
+            funder: address.clone(),
+            address,
+            chain_id,
+            signature_type: SignatureType::Eoa,
+        })
+    }
+
+    /// Sign on behalf of a proxy/safe wallet that holds the funds.
+    pub fn with_funder(mut self, funder: &str, signature_type: SignatureType) -> Self {
+        if signature_type != SignatureType::Eoa && !funder.is_empty() {
+            self.funder = funder.to_string();
+        }
+        self.signature_type = signature_type;
+        self
+    }
+
+    /// Checksummed EOA address of the signing key
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Build an unsigned exchange order from a limit price and share size.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_order(
+        &self,
+        token_id: &str,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+        tick_size: Decimal,
+        fee_rate_bps: u64,
+        expiration: u64,
+    ) -> Result<ExchangeOrder> {
+        let (maker_amount, taker_amount) = order_amounts(side, size, price, tick_size)?;
+
+        Ok(ExchangeOrder {
+            salt: generate_salt(),
+            maker: self.funder.clone(),
+            signer: self.address.clone(),
+            taker: ZERO_ADDRESS.to_string(),
+            token_id: token_id.to_string(),
+            maker_amount,
+            taker_amount,
+            expiration,
+            nonce: 0,
+            fee_rate_bps,
+            side,
+            signature_type: self.signature_type,
+        })
+    }
+
+    /// Produce the EIP-712 signature for an order against the (neg-risk) CTF Exchange domain.
+    pub fn sign_order(&self, order: &ExchangeOrder, neg_risk: bool) -> Result<SignedOrder> {
+        let exchange = exchange_address(self.chain_id, neg_risk)?;
+        let domain = exchange_domain_separator(self.chain_id, exchange)?;
+        let signature = self.sign_typed_data(&domain, &order.struct_hash()?)?;
+
+        Ok(SignedOrder {
+            salt: order.salt,
+            maker: order.maker.clone(),
+            signer: order.signer.clone(),
+            taker: order.taker.clone(),
+            token_id: order.token_id.clone(),
+            maker_amount: order.maker_amount.to_string(),
+            taker_amount: order.taker_amount.to_string(),
+            expiration: order.expiration.to_string(),
+            nonce: order.nonce.to_string(),
+            fee_rate_bps: order.fee_rate_bps.to_string(),
+            side: order.side.as_str().to_string(),
+            signature_type: order.signature_type.as_u8(),
+            signature,
+        })
+    }
+
+    /// Sign the L1 `ClobAuth` message that proves control of the wallet to the CLOB
+    pub fn sign_clob_auth(&self, timestamp: i64, nonce: u64) -> Result<String> {
+        let mut domain = Vec::with_capacity(4 * 32);
+        domain.extend_from_slice(&keccak256(CLOB_AUTH_DOMAIN_TYPE.as_bytes()));
+        domain.extend_from_slice(&keccak256(CLOB_AUTH_DOMAIN_NAME.as_bytes()));
+        domain.extend_from_slice(&keccak256(CLOB_AUTH_DOMAIN_VERSION.as_bytes()));
+        domain.extend_from_slice(&encode_uint(self.chain_id as u128));
+
+        let mut message = Vec::with_capacity(5 * 32);
+        message.extend_from_slice(&keccak256(CLOB_AUTH_TYPE.as_bytes()));
+        message.extend_from_slice(&encode_address(&self.address)?);
+        message.extend_from_slice(&keccak256(timestamp.to_string().as_bytes()));
+        message.extend_from_slice(&encode_uint(nonce as u128));
+        message.extend_from_slice(&keccak256(CLOB_AUTH_MESSAGE.as_bytes()));
+
+        self.sign_typed_data(&keccak256(&domain), &keccak256(&message))
+    }
+
+    /// Sign keccak256("\x19\x01" || domainSeparator || structHash), returning 0x-prefixed r||s||v.
+    pub fn sign_typed_data(&self, domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> Result<String> {
+        let mut message = Vec::with_capacity(66);
+        message.extend_from_slice(&[0x19, 0x01]);
+        message.extend_from_slice(domain_separator);
+        message.extend_from_slice(struct_hash);
+        let digest = keccak256(&message);
+
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(&digest)
+            .context("Failed to sign EIP-712 digest")?;
+
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        Ok(format!("0x{}", hex::encode(bytes)))
+    }
+}
+
+fn exchange_address(chain_id: u64, neg_risk: bool) -> Result<&'static str> {
+    match (chain_id, neg_risk) {
+        (POLYGON_CHAIN_ID, false) => Ok(CTF_EXCHANGE),
+        (POLYGON_CHAIN_ID, true) => Ok(NEG_RISK_CTF_EXCHANGE),
+        (AMOY_CHAIN_ID, false) => Ok(AMOY_CTF_EXCHANGE),
+        (AMOY_CHAIN_ID, true) => Ok(AMOY_NEG_RISK_CTF_EXCHANGE),
+        _ => anyhow::bail!("No CTF Exchange deployment for chain {}", chain_id),
+    }
+}
+
+fn exchange_domain_separator(chain_id: u64, verifying_contract: &str) -> Result<[u8; 32]> {
+    let mut encoded = Vec::with_capacity(5 * 32);
+    encoded.extend_from_slice(&keccak256(EIP712_DOMAIN_TYPE.as_bytes()));
+    encoded.extend_from_slice(&keccak256(EXCHANGE_DOMAIN_NAME.as_bytes()));
+    encoded.extend_from_slice(&keccak256(EXCHANGE_DOMAIN_VERSION.as_bytes()));
+    encoded.extend_from_slice(&encode_uint(chain_id as u128));
+    encoded.extend_from_slice(&encode_address(verifying_contract)?);
+    Ok(keccak256(&encoded))
+}
+
+fn generate_salt() -> u64 {
+    // py-clob-client uses round(timestamp * random()); any unique value below 2^53 works
+    rand::random::<u64>() >> 11
+}
+
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub(crate) fn encode_uint(value: u128) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[16..].copy_from_slice(&value.to_be_bytes());
+    out
+}
+
+/// ABI-encode a decimal string as uint256 (token IDs exceed u128).
+fn encode_uint_str(value: &str) -> Result<[u8; 32]> {
+    let mut out = [0u8; 32];
+    if value.is_empty() {
+        anyhow::bail!("Empty uint256 value");
+    }
+    for c in value.chars() {
+        let digit = c.to_digit(10).with_context(|| format!("Invalid uint256 digit in {}", value))?;
+        // out = out * 10 + digit, big-endian
+        let mut carry = digit;
+        for byte in out.iter_mut().rev() {
+            let v = (*byte as u32) * 10 + carry;
+            *byte = (v & 0xff) as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            anyhow::bail!("uint256 overflow: {}", value);
+        }
+    }
+    Ok(out)
+}
+
+pub(crate) fn encode_address(address: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))
+        .with_context(|| format!("Invalid address: {}", address))?;
+    if bytes.len() != 20 {
+        anyhow::bail!("Address must be 20 bytes: {}", address);
+    }
+    let mut out = [0u8; 32];
+    out[12..].copy_from_slice(&bytes);
+    Ok(out)
+}
+
+/// EIP-55 mixed-case checksum encoding
+fn to_checksum_address(bytes: &[u8]) -> String {
+    let lower = hex::encode(bytes);
+    let hash = hex::encode(keccak256(lower.as_bytes()));
+    let checksummed: String = lower
+        .chars()
+        .zip(hash.chars())
+        .map(|(c, h)| if h >= '8' { c.to_ascii_uppercase() } else { c })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    // Hardhat account #0, the same key py-clob-client and py-order-utils use in their test suites
+    const TEST_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const TEST_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    fn test_order(token_id: &str, maker_amount: u128, taker_amount: u128, side: OrderSide) -> ExchangeOrder {
+        ExchangeOrder {
+            salt: 12345,
+            maker: TEST_ADDRESS.to_string(),
+            signer: TEST_ADDRESS.to_string(),
+            taker: ZERO_ADDRESS.to_string(),
+            token_id: token_id.to_string(),
+            maker_amount,
+            taker_amount,
+            expiration: 0,
+            nonce: 0,
+            fee_rate_bps: 0,
+            side,
+            signature_type: SignatureType::Eoa,
+        }
+    }
+
+    #[test]
+    fn test_address_from_private_key() {
+        let signer = OrderSigner::from_private_key(TEST_KEY, POLYGON_CHAIN_ID).unwrap();
+        assert_eq!(signer.address(), TEST_ADDRESS);
+
+        let one = format!("0x{}1", "0".repeat(63));
+        let signer = OrderSigner::from_private_key(&one, POLYGON_CHAIN_ID).unwrap();
+        assert_eq!(signer.address(), "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+    }
+
+    #[test]
+    fn test_eip712_spec_example() {
+        // "Ether Mail" example from the EIP-712 specification, signed with keccak256("cow")
+        let key = hex::encode(keccak256(b"cow"));
+        let signer = OrderSigner::from_private_key(&key, 1).unwrap();
+        let domain: [u8; 32] = hex::decode("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let mail: [u8; 32] = hex::decode("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        let signature = signer.sign_typed_data(&domain, &mail).unwrap();
+        assert_eq!(
+            signature,
+            "0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d\
+             07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c"
+        );
+    }
+
+    #[test]
+    fn test_sign_clob_auth_reference() {
+        // Expected value from py-clob-client tests/signing/test_eip712.py
+        let signer = OrderSigner::from_private_key(TEST_KEY, AMOY_CHAIN_ID).unwrap();
+        assert_eq!(
+            signer.sign_clob_auth(10000000, 23).unwrap(),
+            "0xf62319a987514da40e57e2f4d7529f7bac38f0355bd88bb5adbb3768d80de6c1\
+             682518e0af677d5260366425f4361e7b70c25ae232aff0ab2331e2b164a1aedc1b"
+        );
+    }
+
+    #[test]
+    fn test_order_amounts_match_reference() {
+        let tick = dec("0.01");
+        // (side, size, price) -> (makerAmount, takerAmount) as produced by py-clob-client
+        let cases = [
+            (OrderSide::Buy, "21.04", "0.58", (12_203_200, 21_040_000)),
+            (OrderSide::Sell, "21.04", "0.58", (21_040_000, 12_203_200)),
+            (OrderSide::Buy, "10", "0.567", (5_700_000, 10_000_000)),
+            (OrderSide::Buy, "5.129", "0.33", (1_689_600, 5_120_000)),
+            (OrderSide::Sell, "100", "0.55", (100_000_000, 55_000_000)),
+            (OrderSide::Buy, "100", "0.95", (95_000_000, 100_000_000)),
+        ];
+        for (side, size, price, expected) in cases {
+            assert_eq!(
+                order_amounts(side, dec(size), dec(price), tick).unwrap(),
+                expected,
+                "{:?} {} @ {}",
+                side,
+                size,
+                price
+            );
+        }
+
+        assert_eq!(
+            order_amounts(OrderSide::Buy, dec("7.5"), dec("0.123"), dec("0.001")).unwrap(),
+            (922_500, 7_500_000)
+        );
+    }
+
+    #[test]
+    fn test_order_amounts_rejects_out_of_range_price() {
+        assert!(order_amounts(OrderSide::Buy, dec("10"), dec("1.00"), dec("0.01")).is_err());
+        assert!(order_amounts(OrderSide::Buy, dec("10"), dec("0.001"), dec("0.01")).is_err());
+        assert!(order_amounts(OrderSide::Buy, dec("10"), dec("0.5"), dec("0.05")).is_err());
+    }
+
+    #[test]
+    fn test_encode_uint_str() {
+        let token = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        assert_eq!(
+            hex::encode(encode_uint_str(token).unwrap()),
+            "9dae480511c4c0cb5d6c7937924c1db5be221e758b7135fec2a1977a1c130af3"
+        );
+        assert_eq!(encode_uint_str("1234").unwrap(), encode_uint(1234));
+        assert!(encode_uint_str("12a4").is_err());
+    }
+
+    #[test]
+    fn test_sign_order_amoy_reference() {
+        // Same fields as the py-order-utils order builder test
+        let signer = OrderSigner::from_private_key(TEST_KEY, AMOY_CHAIN_ID).unwrap();
+        let mut order = test_order("1234", 100_000_000, 50_000_000, OrderSide::Buy);
+        order.salt = 479249096354;
+        order.fee_rate_bps = 100;
+
+        assert_eq!(
+            hex::encode(order.struct_hash().unwrap()),
+            "26eb80b08612a9945a9a2af3af152f3bcdccd154f9708a68d2f261f44b66a11d"
+        );
+        let signed = signer.sign_order(&order, false).unwrap();
+        assert_eq!(
+            signed.signature,
+            "0x302cd9abd0b5fcaa202a344437ec0b6660da984e24ae9ad915a592a90facf5a5\
+             1bb8a873cd8d270f070217fea1986531d5eec66f1162a81f66e026db653bf7ce1c"
+        );
+    }
+
+    #[test]
+    fn test_sign_order_polygon_reference() {
+        let signer = OrderSigner::from_private_key(TEST_KEY, POLYGON_CHAIN_ID).unwrap();
+        let token = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+
+        let buy = test_order(token, 12_203_200, 21_040_000, OrderSide::Buy);
+        let signed = signer.sign_order(&buy, false).unwrap();
+        assert_eq!(
+            signed.signature,
+            "0xfbf0a44ab1282dd80b4812778f15df5f119479fec2556feba1f23cd2e9f9c010\
+             500a23cf4a069bb720f633cbb3c889bedea2b3ab0e308079b3bedb47e8fe79921b"
+        );
+
+        let sell = test_order(token, 21_040_000, 12_203_200, OrderSide::Sell);
+        let signed = signer.sign_order(&sell, true).unwrap();
+        assert_eq!(
+            signed.signature,
+            "0xb3e99fa4dd7e9c5b489ecf6c8e75f3af1128f0440a66bf54fea30772832e2753\
+             41657932ad1a169c0837ec5b9d561078f6e01fc2c64caa9c817c21787d667b2e1c"
+        );
+    }
+
+    #[test]
+    fn test_build_order_and_json_shape() {
+        let signer = OrderSigner::from_private_key(TEST_KEY, POLYGON_CHAIN_ID).unwrap();
+        let order = signer
+            .build_order("1234", OrderSide::Buy, dec("0.58"), dec("21.04"), dec("0.01"), 0, 0)
+            .unwrap();
+        assert_eq!(order.maker, TEST_ADDRESS);
+        assert_eq!(order.maker_amount, 12_203_200);
+        assert_eq!(order.taker_amount, 21_040_000);
+
+        let json = serde_json::to_value(signer.sign_order(&order, false).unwrap()).unwrap();
+        assert_eq!(json["tokenId"], "1234");
+        assert_eq!(json["makerAmount"], "12203200");
+        assert_eq!(json["side"], "BUY");
+        assert_eq!(json["signatureType"], 0);
+        assert!(json["salt"].is_u64());
+        assert_eq!(json["signature"].as_str().unwrap().len(), 132);
+    }
+
+    #[test]
+    fn test_proxy_funder_is_maker() {
+        let proxy = "0x1111111111111111111111111111111111111111";
+        let signer = OrderSigner::from_private_key(TEST_KEY, POLYGON_CHAIN_ID)
+            .unwrap()
+            .with_funder(proxy, SignatureType::PolyGnosisSafe);
+        let order = signer
+            .build_order("1234", OrderSide::Sell, dec("0.40"), dec("5"), dec("0.01"), 0, 0)
+            .unwrap();
+        assert_eq!(order.maker, proxy);
+        assert_eq!(order.signer, TEST_ADDRESS);
+        assert_eq!(order.signature_type, SignatureType::PolyGnosisSafe);
+    }
+
+    #[test]
+    fn test_exchange_addresses() {
+        let cases = [
+            (POLYGON_CHAIN_ID, false, "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E"),
+            (POLYGON_CHAIN_ID, true, "0xC5d563A36AE78145C8a4D4E9a7b82E3DF0f1C6c4"),
+            (80002, false, "0xdFE02Eb6733538f8Ea35D585af8DE5958AD99E40"),
+            (80002, true, "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296"),
+        ];
+        for (chain_id, neg_risk, address) in cases {
+            assert_eq!(exchange_address(chain_id, neg_risk).unwrap(), address, "chain {} neg_risk {}", chain_id, neg_risk);
+        }
+        assert!(exchange_address(1, false).is_err());
+    }
+}
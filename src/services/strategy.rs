@@ -0,0 +1,385 @@
+use anyhow::{bail, Result};
+
+use crate::config::SignalConfig;
+use crate::events::{FlowStats, SignalFeatures, TradeSide};
+use super::fair_value;
+use super::fees::FeeModel;
+use super::polymarket::QuoteState;
+
+/// Everything a strategy gets to look at on one evaluation
+#[derive(Debug, Clone, Default)]
+pub struct MarketView {
+    pub t_ms: i64,
+    // Binance
+    pub binance_mid: Option<f64>,
+    pub binance_ret_1s: Option<f64>,
+    pub binance_ret_3s: Option<f64>,
+    pub binance_ret_10s: Option<f64>,
+    pub binance_std_5m: Option<f64>,
+    pub binance_obi_top5: Option<f64>,
+    pub binance_flow_1s: Option<FlowStats>,
+    pub binance_flow_3s: Option<FlowStats>,
+    pub binance_flow_10s: Option<FlowStats>,
+    // Composite index over the spot venues
+    pub index_price: Option<f64>,
+    pub index_ret_1s: Option<f64>,
+    pub index_ret_3s: Option<f64>,
+    pub index_ret_10s: Option<f64>,
+    pub index_std_5m: Option<f64>,
+    // Polymarket
+    pub quotes: QuoteState,
+    pub poly_stale_ms: i64,
+    pub fees: FeeModel,
+    // Window
+    pub chainlink_price: Option<f64>,
+    pub target_price: Option<f64>,
+    pub remaining_secs: Option<i64>,
+    pub condition_id: String,
+}
+
+impl MarketView {
+    /// What gets logged alongside a signal
+    pub fn features(&self) -> SignalFeatures {
+        SignalFeatures {
+            condition_id: self.condition_id.clone(),
+            binance_mid: self.binance_mid,
+            binance_ret_10s: self.binance_ret_10s,
+            binance_std_5m: self.binance_std_5m,
+            binance_obi_top5: self.binance_obi_top5,
+            binance_flow_3s: self.binance_flow_3s.clone(),
+            index_price: self.index_price,
+            index_ret_10s: self.index_ret_10s,
+            index_std_5m: self.index_std_5m,
+            yes_bid: self.quotes.yes_bid,
+            yes_ask: self.quotes.yes_ask,
+            no_bid: self.quotes.no_bid,
+            no_ask: self.quotes.no_ask,
+            chainlink_price: self.chainlink_price,
+            target_price: self.target_price,
+            remaining_secs: self.remaining_secs,
+        }
+    }
+
+    pub fn ask(&self, side: TradeSide) -> Option<f64> {
+        match side {
+            TradeSide::Yes => self.quotes.yes_ask,
+            TradeSide::No => self.quotes.no_ask,
+        }
+    }
+
+    /// P(BTC closes at or above the target); None until target, price, volatility and
+    /// time left are all known. Volatility is floored at `vol_floor_per_sec`.
+    pub fn fair_up(&self, vol_floor_per_sec: f64) -> Option<f64> {
+        let target = self.target_price?;
+        let remaining = self.remaining_secs?;
+        let price = self.binance_mid?;
+        let vol = fair_value::vol_per_sec_from_std(self.binance_std_5m?, fair_value::STD_WINDOW_SECS)
+            .max(vol_floor_per_sec);
+        Some(fair_value::prob_close_above(price, target, vol, remaining as f64))
+    }
+
+    /// Edge per share of buying `side` at its ask, after the taker fee
+    pub fn edge(&self, side: TradeSide, fair_up: f64) -> Option<f64> {
+        let fair = match side {
+            TradeSide::Yes => fair_up,
+            TradeSide::No => 1.0 - fair_up,
+        };
+        self.ask(side).map(|ask| fair - self.fees.buy_cost(ask))
+    }
+}
+
+/// What a strategy would like to do, with a 0..1 score
+#[derive(Debug, Clone)]
+pub struct Intent {
+    pub side: TradeSide,
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+/// A signal source evaluated against the live market view
+pub trait Strategy: Send {
+    fn name(&self) -> &'static str;
+
+    /// Return an intent, or None to stand aside
+    fn evaluate(&mut self, view: &MarketView) -> Option<Intent>;
+}
+
+/// Strategy names accepted in `signal.strategies`
+pub const STRATEGY_NAMES: &[&str] = &["lag", "fair_value", "momentum"];
+
+/// Build a strategy from its configured name
+pub fn build_strategy(name: &str, config: &SignalConfig) -> Result<Box<dyn Strategy>> {
+    Ok(match name {
+        "lag" => Box::new(LagStrategy::new(config.clone())),
+        "fair_value" => Box::new(FairValueStrategy {
+            min_edge: config.fair_value_min_edge,
+            vol_floor_per_sec: config.vol_floor_per_sec,
+        }),
+        "momentum" => Box::new(MomentumStrategy {
+            threshold_10s: config.momentum_threshold_10s,
+            min_confidence: config.min_confidence,
+        }),
+        other => bail!("Unknown strategy '{}' (expected one of {:?})", other, STRATEGY_NAMES),
+    })
+}
+
+/// Binance moved but the Polymarket book hasn't caught up yet
+pub struct LagStrategy {
+    config: SignalConfig,
+}
+
+impl LagStrategy {
+    pub fn new(config: SignalConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Strategy for LagStrategy {
+    fn name(&self) -> &'static str {
+        "lag"
+    }
+
+    fn evaluate(&mut self, view: &MarketView) -> Option<Intent> {
+        let ret_1s = view.binance_ret_1s.unwrap_or(0.0);
+        let ret_3s = view.binance_ret_3s.unwrap_or(0.0);
+        let poly_stale_ms = view.poly_stale_ms;
+        let mut reasons = Vec::new();
+
+        // Check for significant Binance move
+        let significant_up_1s = ret_1s > self.config.binance_return_threshold_1s;
+        let significant_down_1s = ret_1s < -self.config.binance_return_threshold_1s;
+        let significant_up_3s = ret_3s > self.config.binance_return_threshold_3s;
+        let significant_down_3s = ret_3s < -self.config.binance_return_threshold_3s;
+
+        // Check if Polymarket might be lagging
+        let poly_lagging = poly_stale_ms > self.config.poly_lag_threshold_ms as i64;
+
+        let mut score = 0.0;
+        let side;
+
+        // Strong signal: 1s move with poly lag
+        if significant_up_1s && poly_lagging {
+            score += 0.5;
+            side = TradeSide::Yes;
+            reasons.push(format!(
+                "BTC up {:.4}% in 1s, Poly lag {}ms",
+                ret_1s * 100.0,
+                poly_stale_ms
+            ));
+        } else if significant_down_1s && poly_lagging {
+            score += 0.5;
+            side = TradeSide::No;
+            reasons.push(format!(
+                "BTC down {:.4}% in 1s, Poly lag {}ms",
+                ret_1s.abs() * 100.0,
+                poly_stale_ms
+            ));
+        } else {
+            return None;
+        }
+
+        // Additional confidence from 3s confirmation
+        if significant_up_3s && side == TradeSide::Yes {
+            score += 0.3;
+            reasons.push(format!("3s uptrend confirms: {:.4}%", ret_3s * 100.0));
+        } else if significant_down_3s && side == TradeSide::No {
+            score += 0.3;
+            reasons.push(format!("3s downtrend confirms: {:.4}%", ret_3s.abs() * 100.0));
+        }
+
+        // The stale ask has to be cheap enough to pay for the taker fee: edge against the
+        // fair value once the model has its inputs, else against the most a share can pay
+        if let Some(ask) = view.ask(side) {
+            let fee = view.fees.fee_per_share(ask);
+            let edge = match view.fair_up(self.config.vol_floor_per_sec) {
+                Some(fair_up) => view.edge(side, fair_up)?,
+                None => 1.0 - view.fees.buy_cost(ask),
+            };
+            if edge <= self.config.lag_min_edge {
+                return None;
+            }
+            reasons.push(format!("Edge {:+.3} after {:.2}¢ taker fee at {:.2} ask", edge, fee * 100.0, ask));
+        }
+
+        // Only signal if above threshold
+        if score < self.config.min_confidence {
+            return None;
+        }
+        Some(Intent { side, score, reasons })
+    }
+}
+
+/// Buy whichever side the fair-value model prices above its ask (after fees) by `min_edge`
+pub struct FairValueStrategy {
+    min_edge: f64,
+    vol_floor_per_sec: f64,
+}
+
+impl Strategy for FairValueStrategy {
+    fn name(&self) -> &'static str {
+        "fair_value"
+    }
+
+    fn evaluate(&mut self, view: &MarketView) -> Option<Intent> {
+        let fair_up = view.fair_up(self.vol_floor_per_sec)?;
+        let (side, edge) = [TradeSide::Yes, TradeSide::No]
+            .into_iter()
+            .filter_map(|side| view.edge(side, fair_up).map(|edge| (side, edge)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        if edge < self.min_edge {
+            return None;
+        }
+
+        // Score is the model's probability that the chosen side wins
+        let p_win = if side == TradeSide::Yes { fair_up } else { 1.0 - fair_up };
+        Some(Intent {
+            side,
+            score: p_win,
+            reasons: vec![format!(
+                "Fair {} {:.3} vs ask {:.2}: edge {:+.3} after fees",
+                side,
+                p_win,
+                view.ask(side).unwrap_or_default(),
+                edge
+            )],
+        })
+    }
+}
+
+/// Follow a sustained 10s move that the 3s return and book imbalance agree with
+pub struct MomentumStrategy {
+    threshold_10s: f64,
+    min_confidence: f64,
+}
+
+impl Strategy for MomentumStrategy {
+    fn name(&self) -> &'static str {
+        "momentum"
+    }
+
+    fn evaluate(&mut self, view: &MarketView) -> Option<Intent> {
+        let ret_10s = view.binance_ret_10s?;
+        if self.threshold_10s <= 0.0 || ret_10s.abs() <= self.threshold_10s {
+            return None;
+        }
+        let side = if ret_10s > 0.0 { TradeSide::Yes } else { TradeSide::No };
+        let sign = ret_10s.signum();
+        let mut score = 0.5;
+        let mut reasons = vec![format!("BTC {:+.4}% in 10s", ret_10s * 100.0)];
+
+        if view.binance_ret_3s.is_some_and(|r| r * sign > 0.0) {
+            score += 0.25;
+            reasons.push("3s return agrees".to_string());
+        }
+        // Positive imbalance means more size bid than offered
+        if view.binance_obi_top5.is_some_and(|obi| obi * sign > 0.0) {
+            score += 0.25;
+            reasons.push("Book imbalance agrees".to_string());
+        }
+
+        if score < self.min_confidence {
+            return None;
+        }
+        Some(Intent { side, score, reasons })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing::signal_config;
+
+    fn view() -> MarketView {
+        MarketView {
+            quotes: QuoteState {
+                yes_bid: Some(0.48),
+                yes_ask: Some(0.50),
+                no_bid: Some(0.48),
+                no_ask: Some(0.50),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lag_strategy() {
+        let mut lag = build_strategy("lag", &signal_config()).unwrap();
+        let mut v = view();
+        v.binance_ret_1s = Some(0.0015);
+        v.binance_ret_3s = Some(0.0025);
+        // Poly updated recently: no lag, no signal
+        v.poly_stale_ms = 100;
+        assert!(lag.evaluate(&v).is_none());
+
+        v.poly_stale_ms = 800;
+        let intent = lag.evaluate(&v).unwrap();
+        assert_eq!(intent.side, TradeSide::Yes);
+        assert!((intent.score - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lag_needs_edge_after_fee() {
+        let mut lag = build_strategy("lag", &signal_config()).unwrap();
+        let mut v = view();
+        v.binance_ret_1s = Some(0.0015);
+        v.binance_ret_3s = Some(0.0025);
+        v.poly_stale_ms = 800;
+        v.target_price = Some(100_000.0);
+        v.binance_mid = Some(100_010.0);
+        v.binance_std_5m = Some(5.0 * 50f64.sqrt());
+        v.remaining_secs = Some(100);
+
+        // The ask lags fair value by 2c: enough without a fee
+        let fair_up = v.fair_up(1.0).unwrap();
+        v.quotes.yes_ask = Some(fair_up - 0.02);
+        assert_eq!(lag.evaluate(&v).unwrap().side, TradeSide::Yes);
+
+        // A 10% fee rate costs ~5c a share near 50c and eats it
+        v.fees = FeeModel::new(1000);
+        assert!(lag.evaluate(&v).is_none());
+    }
+
+    #[test]
+    fn test_fair_value_strategy() {
+        let mut fv = build_strategy("fair_value", &signal_config()).unwrap();
+        let mut v = view();
+        assert!(fv.evaluate(&v).is_none());
+
+        // $100 above target, $50 to go in sd: Up is worth ~0.977 against a 0.50 ask
+        v.target_price = Some(100_000.0);
+        v.binance_mid = Some(100_100.0);
+        v.binance_std_5m = Some(5.0 * 50f64.sqrt());
+        v.remaining_secs = Some(100);
+        let intent = fv.evaluate(&v).unwrap();
+        assert_eq!(intent.side, TradeSide::Yes);
+        assert!((intent.score - 0.977).abs() < 1e-3);
+
+        // Priced in: no edge left
+        v.quotes.yes_ask = Some(0.97);
+        assert!(fv.evaluate(&v).is_none());
+    }
+
+    #[test]
+    fn test_momentum_strategy() {
+        let mut momentum = build_strategy("momentum", &signal_config()).unwrap();
+        let mut v = view();
+        v.binance_ret_10s = Some(-0.004);
+        v.binance_ret_3s = Some(-0.001);
+        v.binance_obi_top5 = Some(0.7);
+        let intent = momentum.evaluate(&v).unwrap();
+        assert_eq!(intent.side, TradeSide::No);
+        assert!((intent.score - 0.75).abs() < 1e-9);
+
+        v.binance_ret_10s = Some(0.001);
+        assert!(momentum.evaluate(&v).is_none());
+    }
+
+    #[test]
+    fn test_unknown_strategy() {
+        assert!(build_strategy("martingale", &signal_config()).is_err());
+        for name in STRATEGY_NAMES {
+            assert_eq!(build_strategy(name, &signal_config()).unwrap().name(), *name);
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDate};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::{Config, SignalConfig};
+use super::backtest::{run_backtest, BacktestOptions, BacktestReport};
+use super::replay::{RecordedDay, WindowResult};
+
+/// `SignalConfig` fields a sweep can vary, plus `auto_min_confidence` for
+/// `auto_trade.min_confidence`
+pub const SWEEP_PARAMS: &[&str] = &[
+    "binance_return_threshold_1s",
+    "binance_return_threshold_3s",
+    "poly_lag_threshold_ms",
+    "min_confidence",
+    "fair_value_min_edge",
+    "lag_min_edge",
+    "momentum_threshold_10s",
+    "auto_min_confidence",
+];
+
+/// Values to try for one parameter: "name=a,b,c" or "name=lo:hi:step" (inclusive)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamRange {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+impl FromStr for ParamRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, spec) = s.split_once('=').with_context(|| format!("Expected name=values, got '{}'", s))?;
+        let name = name.trim();
+        if !SWEEP_PARAMS.contains(&name) {
+            bail!("Unknown parameter '{}' (expected one of {})", name, SWEEP_PARAMS.join(", "));
+        }
+        let number = |v: &str| v.trim().parse::<f64>().with_context(|| format!("Invalid number '{}' in '{}'", v, s));
+
+        let values = if let [lo, hi, step] = spec.split(':').collect::<Vec<_>>()[..] {
+            let (lo, hi, step) = (number(lo)?, number(hi)?, number(step)?);
+            if step <= 0.0 || hi < lo {
+                bail!("Range '{}' needs lo <= hi and a positive step", spec);
+            }
+            let n = ((hi - lo) / step + 1e-9).floor() as usize + 1;
+            (0..n).map(|i| lo + i as f64 * step).collect()
+        } else {
+            spec.split(',').map(number).collect::<Result<Vec<_>>>()?
+        };
+        if values.is_empty() {
+            bail!("No values for '{}'", name);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            values,
+        })
+    }
+}
+
+/// How parameter sets are drawn from the ranges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    /// Every combination of the listed values
+    Grid,
+    /// Uniform samples between each range's lowest and highest value
+    Random { samples: usize, seed: u64 },
+}
+
+/// What the sweep ranks parameter sets by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Objective {
+    Pnl,
+    Sharpe,
+}
+
+impl Objective {
+    fn score(self, report: &BacktestReport) -> f64 {
+        match self {
+            Self::Pnl => report.total_pnl,
+            Self::Sharpe => report.sharpe.unwrap_or(f64::NEG_INFINITY),
+        }
+    }
+}
+
+pub type ParamSet = BTreeMap<String, f64>;
+
+/// Parameter sets to evaluate, in a stable order
+pub fn param_sets(ranges: &[ParamRange], mode: SearchMode) -> Vec<ParamSet> {
+    match mode {
+        SearchMode::Grid => ranges.iter().fold(vec![ParamSet::new()], |sets, range| {
+            sets.iter()
+                .flat_map(|set| {
+                    range.values.iter().map(move |value| {
+                        let mut set = set.clone();
+                        set.insert(range.name.clone(), *value);
+                        set
+                    })
+                })
+                .collect()
+        }),
+        SearchMode::Random { samples, seed } => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..samples)
+                .map(|_| {
+                    ranges
+                        .iter()
+                        .map(|range| {
+                            let lo = range.values.iter().cloned().fold(f64::INFINITY, f64::min);
+                            let hi = range.values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                            let value = if hi > lo { rng.gen_range(lo..=hi) } else { lo };
+                            // Whole milliseconds; thresholds to 6 decimals so the table stays readable
+                            let value = if range.name == "poly_lag_threshold_ms" {
+                                value.round()
+                            } else {
+                                (value * 1e6).round() / 1e6
+                            };
+                            (range.name.clone(), value)
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
+
+/// Apply a parameter set
+fn apply_params(config: &mut Config, params: &ParamSet) {
+    let signal: &mut SignalConfig = &mut config.signal;
+    for (name, value) in params {
+        match name.as_str() {
+            "binance_return_threshold_1s" => signal.binance_return_threshold_1s = *value,
+            "binance_return_threshold_3s" => signal.binance_return_threshold_3s = *value,
+            "poly_lag_threshold_ms" => signal.poly_lag_threshold_ms = value.max(0.0).round() as u64,
+            "min_confidence" => signal.min_confidence = *value,
+            "fair_value_min_edge" => signal.fair_value_min_edge = *value,
+            "lag_min_edge" => signal.lag_min_edge = *value,
+            "momentum_threshold_10s" => signal.momentum_threshold_10s = *value,
+            "auto_min_confidence" => config.auto_trade.min_confidence = *value,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SweepOptions {
+    pub backtest: BacktestOptions,
+    pub ranges: Vec<ParamRange>,
+    pub mode: SearchMode,
+    pub objective: Objective,
+    // Walk-forward: choose on `train_days` days, evaluate on the next `test_days`, step by `test_days`
+    pub train_days: Option<usize>,
+    pub test_days: usize,
+    pub jobs: usize,
+}
+
+/// One parameter set over every day
+#[derive(Debug, Clone)]
+pub struct SweepRun {
+    pub params: ParamSet,
+    pub report: BacktestReport,
+}
+
+/// One walk-forward step: the set chosen on the train days and how it did on the test days
+#[derive(Debug, Clone)]
+pub struct WalkForwardFold {
+    pub train: (NaiveDate, NaiveDate),
+    pub test: (NaiveDate, NaiveDate),
+    pub params: ParamSet,
+    pub train_report: BacktestReport,
+    pub test_report: BacktestReport,
+}
+
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    pub objective: Objective,
+    pub runs: Vec<SweepRun>, // best first
+    pub folds: Vec<WalkForwardFold>,
+    pub out_of_sample: Option<BacktestReport>, // the folds' test windows strung together
+}
+
+/// Day a window belongs to: the recording day it ended in
+fn window_day(window: &WindowResult) -> Option<NaiveDate> {
+    chrono::DateTime::from_timestamp_millis(window.end_ms - 1).map(|t| t.with_timezone(&Local).date_naive())
+}
+
+/// Backtest every parameter set over `days` on `options.jobs` threads, rank them and,
+/// with `train_days` set, walk forward through the days choosing on each train span.
+/// Each set runs through all days in one engine, logging under `<out_dir>/runs/<n>`.
+pub fn run_sweep(config: &Config, days: &[RecordedDay], options: &SweepOptions) -> Result<SweepResult> {
+    if options.ranges.is_empty() {
+        bail!("Nothing to sweep: give at least one --param ({})", SWEEP_PARAMS.join(", "));
+    }
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        bail!("Nothing to sweep over");
+    };
+    let sets = param_sets(&options.ranges, options.mode);
+    tracing::info!("Sweeping {} parameter sets over {} days on {} threads", sets.len(), days.len(), options.jobs);
+
+    let next = AtomicUsize::new(0);
+    let results: Vec<Result<(usize, BacktestReport)>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..options.jobs.max(1))
+            .map(|_| {
+                scope.spawn(|| -> Vec<Result<(usize, BacktestReport)>> {
+                    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                        Ok(runtime) => runtime,
+                        Err(e) => return vec![Err(e.into())],
+                    };
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(params) = sets.get(index) else { break };
+                        let mut config = config.clone();
+                        apply_params(&mut config, params);
+                        let mut backtest = options.backtest.clone();
+                        backtest.out_dir = PathBuf::from(&options.backtest.out_dir)
+                            .join("runs")
+                            .join(index.to_string())
+                            .to_string_lossy()
+                            .into_owned();
+                        let report = runtime.block_on(run_backtest(&config, days, &backtest));
+                        done.push(report.map(|r| (index, r)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap_or_else(|_| vec![Err(anyhow::anyhow!("Sweep worker panicked"))]))
+            .collect()
+    });
+
+    let mut reports: Vec<Option<BacktestReport>> = vec![None; sets.len()];
+    for result in results {
+        let (index, report) = result?;
+        reports[index] = Some(report);
+    }
+    let mut runs: Vec<SweepRun> = sets
+        .into_iter()
+        .zip(reports)
+        .filter_map(|(params, report)| report.map(|report| SweepRun { params, report }))
+        .collect();
+
+    // Walk forward over the un-ranked runs, then rank
+    let folds = match options.train_days {
+        Some(train_days) => walk_forward(&runs, days, train_days, options.test_days.max(1), &options.backtest, options.objective),
+        None => Vec::new(),
+    };
+    let out_of_sample = (!folds.is_empty()).then(|| {
+        let windows = folds.iter().flat_map(|f| f.test_report.windows.clone()).collect();
+        BacktestReport::new(&options.backtest.strategy, folds[0].test.0, folds[folds.len() - 1].test.1, &options.backtest, windows)
+    });
+
+    let objective = options.objective;
+    runs.sort_by(|a, b| objective.score(&b.report).total_cmp(&objective.score(&a.report)));
+    tracing::info!("Swept {}..{}", first.date, last.date);
+    Ok(SweepResult {
+        objective,
+        runs,
+        folds,
+        out_of_sample,
+    })
+}
+
+/// Windows of `report` that fall on `dates`, as their own report
+fn slice_report(report: &BacktestReport, dates: &[NaiveDate], options: &BacktestOptions) -> BacktestReport {
+    let windows = report
+        .windows
+        .iter()
+        .filter(|w| window_day(w).is_some_and(|day| dates.contains(&day)))
+        .cloned()
+        .collect();
+    BacktestReport::new(&report.strategy, dates[0], dates[dates.len() - 1], options, windows)
+}
+
+fn walk_forward(
+    runs: &[SweepRun],
+    days: &[RecordedDay],
+    train_days: usize,
+    test_days: usize,
+    options: &BacktestOptions,
+    objective: Objective,
+) -> Vec<WalkForwardFold> {
+    let dates: Vec<NaiveDate> = days.iter().map(|d| d.date).collect();
+    let mut folds = Vec::new();
+    let mut start = 0;
+    while start + train_days + test_days <= dates.len() && train_days > 0 {
+        let train = &dates[start..start + train_days];
+        let test = &dates[start + train_days..start + train_days + test_days];
+        let best = runs
+            .iter()
+            .map(|run| (run, slice_report(&run.report, train, options)))
+            .max_by(|(_, a), (_, b)| objective.score(a).total_cmp(&objective.score(b)));
+        if let Some((run, train_report)) = best {
+            folds.push(WalkForwardFold {
+                train: (train[0], train[train.len() - 1]),
+                test: (test[0], test[test.len() - 1]),
+                params: run.params.clone(),
+                train_report,
+                test_report: slice_report(&run.report, test, options),
+            });
+        }
+        start += test_days;
+    }
+    folds
+}
+
+fn format_params(params: &ParamSet) -> String {
+    params.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join(" ")
+}
+
+fn format_opt(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.3}", v)).unwrap_or("-".to_string())
+}
+
+impl SweepResult {
+    /// Ranked table, one tab-separated row per parameter set
+    pub fn to_tsv(&self) -> String {
+        let names: Vec<&String> = self.runs.first().map(|r| r.params.keys().collect()).unwrap_or_default();
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "rank\t{}\ttrades\ttraded_windows\twin_rate\tpnl\tfees\tmax_drawdown\tsharpe",
+            names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join("\t")
+        );
+        for (rank, run) in self.runs.iter().enumerate() {
+            let r = &run.report;
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{:.4}\t{:.4}\t{:.4}\t{}",
+                rank + 1,
+                run.params.values().map(|v| v.to_string()).collect::<Vec<_>>().join("\t"),
+                r.trades,
+                r.traded_windows,
+                format_opt(r.win_rate),
+                r.total_pnl,
+                r.fees_paid,
+                r.max_drawdown,
+                format_opt(r.sharpe)
+            );
+        }
+        out
+    }
+
+    /// Top `limit` sets and the walk-forward steps, for the terminal
+    pub fn format_table(&self, limit: usize) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{} parameter sets ranked by {:?}", self.runs.len(), self.objective);
+        let _ = writeln!(out, "{:>4} {:>6} {:>7} {:>9} {:>8} {:>7}  PARAMS", "RANK", "TRADES", "WIN%", "PNL", "MAX DD", "SHARPE");
+        for (rank, run) in self.runs.iter().take(limit).enumerate() {
+            let r = &run.report;
+            let _ = writeln!(
+                out,
+                "{:>4} {:>6} {:>7} {:>+9.2} {:>8.2} {:>7}  {}",
+                rank + 1,
+                r.trades,
+                r.win_rate.map(|w| format!("{:.1}", w * 100.0)).unwrap_or("-".to_string()),
+                r.total_pnl,
+                r.max_drawdown,
+                format_opt(r.sharpe),
+                format_params(&run.params)
+            );
+        }
+
+        if !self.folds.is_empty() {
+            let _ = writeln!(out, "\nWalk-forward:");
+            for fold in &self.folds {
+                let _ = writeln!(
+                    out,
+                    "  train {}..{} {:>+9.2} → test {}..{} {:>+9.2}  {}",
+                    fold.train.0,
+                    fold.train.1,
+                    fold.train_report.total_pnl,
+                    fold.test.0,
+                    fold.test.1,
+                    fold.test_report.total_pnl,
+                    format_params(&fold.params)
+                );
+            }
+        }
+        if let Some(oos) = &self.out_of_sample {
+            let _ = writeln!(
+                out,
+                "Out of sample: {} trades, PnL {:+.2}, max drawdown {:.2}, Sharpe {}",
+                oos.trades,
+                oos.total_pnl,
+                oos.max_drawdown,
+                format_opt(oos.sharpe)
+            );
+        }
+        out
+    }
+}
+
+/// Number of sweep threads to use by default
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::testing;
+
+    #[test]
+    fn test_param_ranges_and_sets() {
+        let range: ParamRange = "binance_return_threshold_1s=0.001:0.002:0.0005".parse().unwrap();
+        assert_eq!(range.values.len(), 3);
+        assert!((range.values[2] - 0.002).abs() < 1e-12);
+        let lag: ParamRange = "poly_lag_threshold_ms=300,500".parse().unwrap();
+        assert_eq!(lag.values, vec![300.0, 500.0]);
+        assert!("unknown=1".parse::<ParamRange>().is_err());
+        assert!("min_confidence=0.9:0.5:0.1".parse::<ParamRange>().is_err());
+
+        let grid = param_sets(&[range.clone(), lag.clone()], SearchMode::Grid);
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid[5]["poly_lag_threshold_ms"], 500.0);
+
+        let random = param_sets(&[range, lag], SearchMode::Random { samples: 20, seed: 7 });
+        assert_eq!(random.len(), 20);
+        assert!(random.iter().all(|set| {
+            let ms = set["poly_lag_threshold_ms"];
+            (300.0..=500.0).contains(&ms) && ms.fract() == 0.0
+        }));
+
+        let mut config = testing::config();
+        apply_params(&mut config, &grid[5]);
+        assert_eq!(config.signal.poly_lag_threshold_ms, 500);
+
+        // The signal and auto-trade thresholds are swept independently
+        let confidence = ParamSet::from([("min_confidence".to_string(), 0.6)]);
+        apply_params(&mut config, &confidence);
+        assert_eq!((config.signal.min_confidence, config.auto_trade.min_confidence), (0.6, 0.8));
+        apply_params(&mut config, &ParamSet::from([("auto_min_confidence".to_string(), 0.7)]));
+        assert_eq!((config.signal.min_confidence, config.auto_trade.min_confidence), (0.6, 0.7));
+    }
+
+    #[test]
+    fn test_walk_forward_picks_on_train_days() {
+        let dates: Vec<NaiveDate> = (14..=17).map(|d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap()).collect();
+        let days: Vec<RecordedDay> = dates
+            .iter()
+            .map(|date| RecordedDay {
+                date: *date,
+                recording: super::super::replay::Recording::Snapshots(Vec::new()),
+            })
+            .collect();
+        let options = BacktestOptions {
+            strategy: "lag".to_string(),
+            latency_ms: 0,
+            fee_rate_bps: None,
+            size: None,
+            book_depth: 1000.0,
+            out_dir: String::new(),
+        };
+        // One traded window at local noon per day
+        let run = |threshold: f64, pnls: [f64; 4]| {
+            let windows = dates
+                .iter()
+                .zip(pnls)
+                .map(|(date, pnl)| WindowResult {
+                    condition_id: date.to_string(),
+                    end_ms: date.and_hms_opt(12, 0, 0).unwrap().and_local_timezone(Local).unwrap().timestamp_millis(),
+                    trades: 1,
+                    outcome: None,
+                    pnl,
+                    fees: 0.0,
+                })
+                .collect();
+            SweepRun {
+                params: ParamSet::from([("min_confidence".to_string(), threshold)]),
+                report: BacktestReport::new("lag", dates[0], dates[3], &options, windows),
+            }
+        };
+        let runs = vec![run(0.5, [5.0, 5.0, -1.0, -1.0]), run(0.8, [1.0, 1.0, 2.0, 3.0])];
+
+        // Train on 2 days, test on the next one
+        let folds = walk_forward(&runs, &days, 2, 1, &options, Objective::Pnl);
+        assert_eq!(folds.len(), 2);
+        assert_eq!(folds[0].params["min_confidence"], 0.5);
+        assert_eq!(folds[0].test, (dates[2], dates[2]));
+        assert_eq!(folds[0].test_report.total_pnl, -1.0);
+        // Days 15-16: 0.5 makes 4.0, 0.8 makes 3.0
+        assert_eq!(folds[1].params["min_confidence"], 0.5);
+        assert_eq!(folds[1].train_report.total_pnl, 4.0);
+    }
+}
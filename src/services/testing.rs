@@ -0,0 +1,156 @@
+//! Helpers shared by the services' unit tests
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+use crate::config::{
+    AutoTradeConfig, BinanceConfig, Config, GeneralConfig, LoggingConfig, PolymarketConfig, SignalConfig,
+    TradingConfig,
+};
+
+/// Serve `body` as a JSON 200 response to every request on a local port, recording
+/// each request line. Returns the base URL and the requests seen so far.
+pub async fn serve_json(body: impl Into<String>) -> (String, Arc<Mutex<Vec<String>>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let body = body.into();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = [0u8; 8192];
+            let n = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..n]).to_string();
+            seen.lock().push(request.lines().next().unwrap_or_default().to_string());
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (format!("http://{}", addr), requests)
+}
+
+/// Full configuration built from the fixtures below, so tests never read config/ or the
+/// environment: dry run, Binance as the only spot venue, no recording
+pub fn config() -> Config {
+    Config {
+        general: GeneralConfig { dry_run: true, snapshot_rate_hz: 1 },
+        binance: binance_config(),
+        coinbase: Default::default(),
+        kraken: Default::default(),
+        spot: Default::default(),
+        index: Default::default(),
+        polymarket: poly_config(),
+        trading: trading_config(),
+        signal: signal_config(),
+        auto_trade: AutoTradeConfig {
+            enabled: false,
+            strategy: "lag".to_string(),
+            min_confidence: 0.8,
+            cooldown_secs: 30,
+            max_trades_per_window: 3,
+            max_position_per_window: 30.0,
+            min_remaining_secs: 60,
+        },
+        settlement: Default::default(),
+        logging: LoggingConfig {
+            log_dir: String::new(),
+            rotation: "daily".to_string(),
+            record_frames: false,
+            frames_dir: String::new(),
+        },
+    }
+}
+
+/// Binance settings pointing nowhere, without the trade stream
+pub fn binance_config() -> BinanceConfig {
+    BinanceConfig {
+        ws_url: "wss://test".to_string(),
+        rest_url: "https://test".to_string(),
+        symbol: "BTCUSD".to_string(),
+        snapshot_limit: 100,
+        trade_stream: String::new(),
+        large_trade_qty: 0.0,
+    }
+}
+
+/// Signal thresholds as shipped in config/default.toml, running lag and fair_value
+pub fn signal_config() -> SignalConfig {
+    SignalConfig {
+        binance_return_threshold_1s: 0.001,
+        binance_return_threshold_3s: 0.002,
+        poly_lag_threshold_ms: 500,
+        min_confidence: 0.5,
+        vol_floor_per_sec: 1.0,
+        strategies: vec!["lag".to_string(), "fair_value".to_string()],
+        strategy_interval_ms: 250,
+        fair_value_min_edge: 0.02,
+        lag_min_edge: 0.01,
+        momentum_threshold_10s: 0.003,
+        strategy_cooldown_ms: 5_000,
+    }
+}
+
+/// Polymarket settings pointing nowhere, with no fee
+pub fn poly_config() -> PolymarketConfig {
+    PolymarketConfig {
+        ws_url: "wss://test".to_string(),
+        user_ws_url: "wss://test".to_string(),
+        rest_url: "https://test".to_string(),
+        gamma_url: "https://test".to_string(),
+        rtds_url: "wss://test".to_string(),
+        event_url: "https://test".to_string(),
+        btc_15m_event_id: "194059".to_string(),
+        fee_rate_bps: 0,
+        api_key: String::new(),
+        api_secret: String::new(),
+        passphrase: String::new(),
+        wallet_address: String::new(),
+        private_key: String::new(),
+        signature_type: 0,
+        yes_token_id: "yes".to_string(),
+        no_token_id: "no".to_string(),
+        condition_id: "cond".to_string(),
+    }
+}
+
+/// Trading limits: 10 shares a click up to 100, taking the ask, in-memory positions
+pub fn trading_config() -> TradingConfig {
+    TradingConfig {
+        default_size: 10.0,
+        max_size: 100.0,
+        max_price_yes: 0.95,
+        max_price_no: 0.95,
+        min_price_yes: 0.05,
+        min_price_no: 0.05,
+        max_spread: 0.10,
+        stale_quote_threshold_ms: 5000,
+        post_only: false,
+        cancel_before_close_secs: 30,
+        positions_file: String::new(),
+    }
+}
+
+/// Initial books for the UP and DOWN tokens of market 0xcond, one level of 100 shares
+/// at each (bid, ask)
+pub fn book_msg(up: (f64, f64), down: (f64, f64)) -> String {
+    format!(
+        r#"[{{"event_type":"book","asset_id":"UP","market":"0xcond",
+        "bids":[{{"price":"{}","size":"100"}}],"asks":[{{"price":"{}","size":"100"}}]}},
+        {{"event_type":"book","asset_id":"DOWN","market":"0xcond",
+        "bids":[{{"price":"{}","size":"100"}}],"asks":[{{"price":"{}","size":"100"}}]}}]"#,
+        up.0, up.1, down.0, down.1
+    )
+}
+
+/// Initial UP book as sent on subscription, several levels deep: bids ascending, asks
+/// descending (best last)
+pub const LADDER_BOOK_MSG: &str = r#"[{"event_type":"book","asset_id":"UP","market":"0xcond",
+    "bids":[{"price":"0.48","size":"100"},{"price":"0.49","size":"50"},{"price":"0.50","size":"20"}],
+    "asks":[{"price":"0.54","size":"300"},{"price":"0.53","size":"40"},{"price":"0.52","size":"10"}],
+    "timestamp":"1769961600000","hash":"abc"}]"#;
@@ -1,14 +1,21 @@
 use anyhow::{anyhow, Result};
 use parking_lot::RwLock;
-use std::collections::VecDeque;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::config::TradingConfig;
-use crate::events::{TradeEvent, TradeSide};
+use crate::events::{FillInfo, TradeEvent, TradeSide};
 use crate::logger::JsonlLogger;
 use crate::services::PolymarketService;
-use super::clob::{ClobClient, ClobCredentials, OrderRequest};
+use super::clob::{CancelResponse, ClobClient, ClobCredentials, OrderRequest};
+use super::fees::FeeModel;
+use super::orders::{OrderState, OrderStore};
+use super::paper::{PaperExchange, PaperOrder, PaperResult};
+use super::signing::OrderSide;
+use super::positions::{Position, PositionLedger};
+use super::user_channel::UserEvent;
 
 /// A single user action for display in the TUI action log.
 #[derive(Debug, Clone)]
@@ -35,21 +42,30 @@ impl ActionLogEntry {
 
 const ACTION_LOG_CAP: usize = 100;
 
+/// Don't re-send lifecycle cancels for a market more often than this
+const LIFECYCLE_RETRY_MS: i64 = 5_000;
+
 #[derive(Debug, Clone)]
 pub struct TradingState {
     pub kill_switch_active: bool,
+    pub auto_trade_active: bool,
     pub current_size: f64,
     pub max_price_yes: f64,
     pub max_price_no: f64,
+    pub min_price_yes: f64,
+    pub min_price_no: f64,
 }
 
 impl TradingState {
     pub fn new(config: &TradingConfig) -> Self {
         Self {
             kill_switch_active: false,
+            auto_trade_active: false,
             current_size: config.default_size,
             max_price_yes: config.max_price_yes,
             max_price_no: config.max_price_no,
+            min_price_yes: config.min_price_yes,
+            min_price_no: config.min_price_no,
         }
     }
 }
@@ -60,13 +76,24 @@ pub enum RiskCheckResult {
     Rejected(String),
 }
 
+/// Error returned by `submit_order` when the order fails a risk check
+#[derive(Debug, thiserror::Error)]
+#[error("Order rejected: {0}")]
+pub struct RiskRejected(pub String);
+
 pub struct TradeService {
     config: TradingConfig,
     polymarket: Arc<PolymarketService>,
     clob_client: ClobClient,
+    orders: Arc<OrderStore>,
+    positions: Arc<PositionLedger>,
+    paper: PaperExchange, // dry-run matching against the live book
+    booked_fills: RwLock<HashMap<(String, String), f64>>, // (trade_id, order_id) -> size booked into the ledger
+    lifecycle_cancels: RwLock<HashMap<String, i64>>,   // condition_id -> last lifecycle cancel (ms)
     logger: Arc<JsonlLogger>,
     state: Arc<RwLock<TradingState>>,
     action_log: Arc<RwLock<VecDeque<ActionLogEntry>>>,
+    auto_trader: RwLock<bool>, // an AutoTrader is consuming signals, so auto mode can be on
     dry_run: bool,
     credentials_debug: Option<ClobCredentials>,
 }
@@ -81,14 +108,35 @@ impl TradeService {
     ) -> Self {
         let state = TradingState::new(&config);
         let credentials_debug = credentials.clone();
-        let clob_client = ClobClient::new(credentials);
+        let clob_client = ClobClient::new(&polymarket.config().rest_url, credentials);
+        let positions = if config.positions_file.is_empty() {
+            PositionLedger::in_memory()
+        } else {
+            // Paper fills get their own ledger next to the live one
+            let path = if dry_run {
+                std::path::Path::new(&config.positions_file).with_extension("paper.json")
+            } else {
+                config.positions_file.clone().into()
+            };
+            PositionLedger::load(path).unwrap_or_else(|e| {
+                // Don't overwrite a ledger we couldn't read
+                tracing::error!("Failed to load positions, not persisting this session: {:?}", e);
+                PositionLedger::in_memory()
+            })
+        };
         Self {
             config,
             polymarket,
             clob_client,
+            orders: Arc::new(OrderStore::new()),
+            positions: Arc::new(positions),
+            paper: PaperExchange::new(),
+            booked_fills: RwLock::new(HashMap::new()),
+            lifecycle_cancels: RwLock::new(HashMap::new()),
             logger,
             state: Arc::new(RwLock::new(state)),
             action_log: Arc::new(RwLock::new(VecDeque::with_capacity(ACTION_LOG_CAP))),
+            auto_trader: RwLock::new(false),
             dry_run,
             credentials_debug,
         }
@@ -121,21 +169,475 @@ impl TradeService {
         self.action_log.read().iter().cloned().collect()
     }
 
+    /// Order-state store; shared with the user channel, which applies updates and fills
+    pub fn order_store(&self) -> Arc<OrderStore> {
+        self.orders.clone()
+    }
+
+    pub fn get_positions(&self) -> Vec<Position> {
+        self.positions.get_positions()
+    }
+
+    /// Open positions in the active window with mark-to-market PnL at the current bids
+    pub fn get_window_positions(&self) -> Vec<(Position, Option<f64>)> {
+        let market = self.polymarket.get_active_market();
+        let quotes = self.polymarket.get_quote_state();
+        self.positions
+            .window_positions(&market.condition_id)
+            .into_iter()
+            .map(|p| {
+                let bid = if p.side == "YES" { quotes.yes_bid } else { quotes.no_bid };
+                // Marked at what a taker sell would actually net
+                let fees = self.fee_model_for(&p.token_id);
+                let upnl = p.unrealized_pnl(bid.map(|b| fees.sell_proceeds(b)));
+                (p, upnl)
+            })
+            .collect()
+    }
+
+    pub fn total_realized_pnl(&self) -> f64 {
+        self.positions.total_realized_pnl()
+    }
+
+    pub fn total_fees_paid(&self) -> f64 {
+        self.positions.get_positions().iter().map(|p| p.fees_paid).sum()
+    }
+
+    /// Taker fees for a token: its fetched market params, else the active market's model
+    fn fee_model_for(&self, token_id: &str) -> FeeModel {
+        self.clob_client
+            .cached_market_params(token_id)
+            .map(|p| FeeModel::new(p.fee_rate_bps))
+            .unwrap_or_else(|| self.polymarket.fee_model())
+    }
+
+    /// Taker fees for one side of the active market
+    pub fn fee_model(&self, side: TradeSide) -> FeeModel {
+        let market = self.polymarket.get_active_market();
+        match side {
+            TradeSide::Yes => self.fee_model_for(&market.up_token_id),
+            TradeSide::No => self.fee_model_for(&market.down_token_id),
+        }
+    }
+
+    /// Fetch tick size, neg-risk flag and fee rate for the active market's tokens
+    /// and publish the fee rate to the Polymarket service
+    pub async fn refresh_market_params(&self) -> Result<()> {
+        let market = self.polymarket.get_active_market();
+        if market.up_token_id.is_empty() || market.fee_rate_bps.is_some() {
+            return Ok(());
+        }
+        let params = self.clob_client.get_market_params(&market.up_token_id).await?;
+        if !market.down_token_id.is_empty() {
+            self.clob_client.get_market_params(&market.down_token_id).await?;
+        }
+        self.polymarket.set_fee_rate_bps(&market.condition_id, params.fee_rate_bps);
+        tracing::info!("Taker fee rate for {}: {} bps", market.condition_id, params.fee_rate_bps);
+        Ok(())
+    }
+
+    /// Taker fee owed on a fill; makers pay nothing
+    fn fill_fee(&self, fill: &FillInfo, order: &OrderState) -> f64 {
+        if fill.liquidity.as_deref() == Some("MAKER") {
+            return 0.0;
+        }
+        self.fee_model_for(&order.asset_id).taker_fee(fill.price, fill.size)
+    }
+
+    /// Settle a finished window: target price vs the closing Chainlink price
+    pub fn settle_window(&self, condition_id: &str, target_price: f64, close_price: f64) -> Result<f64> {
+        if self.positions.window_positions(condition_id).iter().all(|p| p.settled) {
+            return Ok(0.0);
+        }
+        let pnl = self.positions.settle(condition_id, target_price, close_price)?;
+        let winner = if close_price >= target_price { "UP" } else { "DOWN" };
+        self.record_action(ActionLogEntry::now(format!(
+            "Settled {} ({} ${:.2} vs target ${:.2}) → PnL {:+.2}",
+            &condition_id[..condition_id.len().min(10)],
+            winner,
+            close_price,
+            target_price,
+            pnl
+        )));
+        tracing::info!("Settled window {}: {} wins, realized PnL {:+.2}", condition_id, winner, pnl);
+        Ok(pnl)
+    }
+
+    /// Re-settle a window whose resolution (Gamma's) differs from the Chainlink settlement
+    pub fn resettle_window(&self, condition_id: &str, winner: &str) -> Result<f64> {
+        let delta = self.positions.resettle(condition_id, winner == "UP")?;
+        self.record_action(ActionLogEntry::now(format!(
+            "Re-settled {} ({} per Gamma) → PnL {:+.2}",
+            &condition_id[..condition_id.len().min(10)],
+            winner,
+            delta
+        )));
+        tracing::warn!("Re-settled window {}: {} wins, realized PnL {:+.2}", condition_id, winner, delta);
+        Ok(delta)
+    }
+
+    /// Book a fill into the position ledger once; undo it if the trade later fails on chain
+    fn book_fill(&self, fill: &FillInfo, order: &OrderState, side: &str) -> Result<()> {
+        let key = (
+            fill.trade_id.clone().unwrap_or_default(),
+            fill.order_id.clone().unwrap_or_else(|| order.order_id.clone()),
+        );
+        let condition_id = if order.market.is_empty() {
+            self.polymarket.get_active_market().condition_id
+        } else {
+            order.market.clone()
+        };
+
+        let fee = fill.fee.unwrap_or(0.0);
+
+        if fill.status.as_deref() == Some("FAILED") {
+            if let Some(size) = self.booked_fills.write().remove(&key) {
+                self.positions
+                    .revert_fill(&condition_id, &order.asset_id, &order.side, fill.price, size, fee)?;
+            }
+            return Ok(());
+        }
+
+        if let Entry::Vacant(entry) = self.booked_fills.write().entry(key) {
+            // The ledger only sells what it holds; remember that, not the fill, for a revert
+            let size = if order.side.eq_ignore_ascii_case("SELL") {
+                let held = self
+                    .positions
+                    .window_positions(&condition_id)
+                    .iter()
+                    .find(|p| p.token_id == order.asset_id)
+                    .map_or(0.0, |p| p.size);
+                fill.size.min(held)
+            } else {
+                fill.size
+            };
+            self.positions
+                .apply_fill(&condition_id, &order.asset_id, side, &order.side, fill.price, fill.size, fee)?;
+            entry.insert(size);
+        }
+        Ok(())
+    }
+
+    /// "YES"/"NO" for the active market's tokens, else the outcome name from the exchange
+    fn side_label(&self, order: &OrderState) -> String {
+        let market = self.polymarket.get_active_market();
+        if order.asset_id == market.up_token_id {
+            TradeSide::Yes.to_string()
+        } else if order.asset_id == market.down_token_id {
+            TradeSide::No.to_string()
+        } else {
+            order.outcome.to_uppercase()
+        }
+    }
+
+    /// Trade log entry for something that happened to an existing order
+    fn order_event(&self, order: &OrderState, api_status: String, fills: Option<Vec<FillInfo>>) -> TradeEvent {
+        TradeEvent {
+            t_send_ms: order.created_ms,
+            t_resp_ms: Some(self.polymarket.clock().now_ms()),
+            client_order_id: order.client_order_id.clone().unwrap_or_else(|| order.order_id.clone()),
+            side: self.side_label(order),
+            order_side: Some(order.side.clone()),
+            size: fills.as_ref().map(|f| f[0].size).unwrap_or(order.remaining_size()),
+            limit_price: order.price,
+            post_only: false,
+            mode: if self.dry_run { "dry_run".to_string() } else { "live".to_string() },
+            risk_reject_reason: None,
+            api_status: Some(api_status),
+            fills,
+        }
+    }
+
+    /// Log fills and cancellations reported by the user channel
+    pub fn handle_user_event(&self, event: &UserEvent) -> Result<()> {
+        let (order, api_status, fills) = match event {
+            UserEvent::Fill { fill, order } => (
+                order,
+                format!("fill: {}", fill.status.as_deref().unwrap_or("UNKNOWN")),
+                Some(vec![FillInfo {
+                    fee: Some(self.fill_fee(fill, order)),
+                    ..fill.clone()
+                }]),
+            ),
+            UserEvent::Order { kind, order } if kind == "CANCELLATION" => {
+                (order, "cancelled".to_string(), None)
+            }
+            // Placements are logged by place_order, updates are covered by fills
+            UserEvent::Order { .. } => return Ok(()),
+        };
+
+        let side = self.side_label(order);
+        if let Some(fill) = fills.as_ref().and_then(|f| f.first()) {
+            if let Err(e) = self.book_fill(fill, order, &side) {
+                tracing::error!("Failed to update positions: {:?}", e);
+            }
+        }
+
+        let trade_event = self.order_event(order, api_status, fills);
+
+        match event {
+            UserEvent::Fill { fill, .. } if fill.status.as_deref() == Some("MATCHED") => {
+                self.record_action(ActionLogEntry::now(format!(
+                    "Fill {} {} {:.0} @ {:.2} ({:.0}/{:.0} filled)",
+                    order.side, side, fill.size, fill.price, order.size_matched, order.original_size
+                )));
+            }
+            UserEvent::Fill { fill, .. } if fill.status.as_deref() == Some("FAILED") => {
+                self.record_action(ActionLogEntry::now(format!(
+                    "Fill {} {} {:.0} @ {:.2} → FAILED on chain",
+                    order.side, side, fill.size, fill.price
+                )));
+            }
+            UserEvent::Order { .. } => {
+                self.record_action(ActionLogEntry::now(format!(
+                    "Cancelled {} {} @ {:.2} ({:.0}/{:.0} filled)",
+                    order.side, side, order.price, order.size_matched, order.original_size
+                )));
+            }
+            _ => {}
+        }
+
+        self.logger.log_trade(trade_event)
+    }
+
     pub fn get_state(&self) -> TradingState {
         self.state.read().clone()
     }
 
-    pub fn toggle_kill_switch(&self) {
-        let mut state = self.state.write();
-        state.kill_switch_active = !state.kill_switch_active;
-        let label = if state.kill_switch_active { "ON" } else { "OFF" };
-        self.record_action(ActionLogEntry::now(format!("Kill switch → {}", label)));
-        tracing::info!("Kill switch: {}", if state.kill_switch_active { "ACTIVE" } else { "OFF" });
+    /// Flip the kill switch; engaging it also cancels every resting order. Returns true if
+    /// it is now engaged.
+    pub async fn toggle_kill_switch(&self) -> Result<bool> {
+        if self.state.read().kill_switch_active {
+            self.set_kill_switch(false);
+            return Ok(false);
+        }
+        self.engage_kill_switch().await?;
+        Ok(true)
+    }
+
+    /// Block new entries and pull every resting order; returns how many were cancelled
+    pub async fn engage_kill_switch(&self) -> Result<usize> {
+        self.set_kill_switch(true);
+        self.cancel_all_orders("kill switch").await
+    }
+
+    /// Record that an AutoTrader is running; until then auto mode can't be switched on
+    pub fn attach_auto_trader(&self) {
+        *self.auto_trader.write() = true;
     }
 
-    pub fn set_kill_switch(&self, active: bool) {
+    /// Flip automated trading; returns true if it is now on. Refused while no AutoTrader
+    /// is running, since nothing would act on the signals.
+    pub fn toggle_auto_trade(&self) -> bool {
         let mut state = self.state.write();
-        state.kill_switch_active = active;
+        if !state.auto_trade_active && !*self.auto_trader.read() {
+            self.record_action(ActionLogEntry::now("Auto trading unavailable: no auto trader running"));
+            tracing::warn!("Auto trading not switched on: no auto trader is running");
+            return false;
+        }
+        state.auto_trade_active = !state.auto_trade_active;
+        let label = if state.auto_trade_active { "ON" } else { "OFF" };
+        self.record_action(ActionLogEntry::now(format!("Auto trading → {}", label)));
+        tracing::info!("Auto trading: {}", label);
+        state.auto_trade_active
+    }
+
+    /// Switch automated trading on or off; stays off while no AutoTrader is running
+    pub fn set_auto_trade(&self, active: bool) {
+        self.state.write().auto_trade_active = active && *self.auto_trader.read();
+    }
+
+    /// Shares held in the active window plus what our resting buys there could still add
+    pub fn window_exposure(&self) -> f64 {
+        let market = self.polymarket.get_active_market();
+        if market.condition_id.is_empty() {
+            return 0.0;
+        }
+        let held: f64 = self
+            .positions
+            .window_positions(&market.condition_id)
+            .iter()
+            .filter(|p| !p.settled)
+            .map(|p| p.size)
+            .sum();
+        let pending: f64 = self
+            .orders
+            .open_orders()
+            .iter()
+            .filter(|o| o.side == "BUY" && (o.asset_id == market.up_token_id || o.asset_id == market.down_token_id))
+            .map(|o| o.remaining_size())
+            .sum();
+        held + pending
+    }
+
+    /// Load resting orders from the exchange into the order store (e.g. left over from a previous run)
+    pub async fn refresh_open_orders(&self) -> Result<usize> {
+        if self.dry_run {
+            return Ok(0);
+        }
+        let open = self.clob_client.get_open_orders(None).await?;
+        for order in &open {
+            self.orders.upsert(order.to_order_state(), false);
+        }
+        Ok(open.len())
+    }
+
+    /// Cancel one order by id
+    pub async fn cancel_order(&self, order_id: &str, reason: &str) -> Result<usize> {
+        let response = if self.dry_run {
+            self.simulate_cancel(|o| o.order_id == order_id)
+        } else {
+            self.clob_client.cancel_order(order_id).await
+        };
+        self.finish_cancel(&format!("Cancel {}", &order_id[..order_id.len().min(10)]), reason, response)
+    }
+
+    /// Cancel every open order for our API key
+    pub async fn cancel_all_orders(&self, reason: &str) -> Result<usize> {
+        let response = if self.dry_run {
+            self.simulate_cancel(|_| true)
+        } else {
+            self.clob_client.cancel_all().await
+        };
+        self.finish_cancel("Cancel all", reason, response)
+    }
+
+    /// Cancel every open order in one market (condition id)
+    pub async fn cancel_market_orders(&self, condition_id: &str, reason: &str) -> Result<usize> {
+        let response = if self.dry_run {
+            self.simulate_cancel(|o| o.market == condition_id)
+        } else {
+            self.clob_client.cancel_market_orders(condition_id).await
+        };
+        self.finish_cancel("Cancel market", reason, response)
+    }
+
+    /// Window lifecycle rule, run periodically: pull our orders on windows that have rolled
+    /// over, and on the active window once it is within `cancel_before_close_secs` of end_date.
+    /// A failed cancel is logged and retried later without holding up the other markets.
+    pub async fn enforce_window_lifecycle(&self) {
+        let market = self.polymarket.get_active_market();
+        let now = self.polymarket.clock().now_ms();
+
+        let mut targets: Vec<(String, &str)> = Vec::new();
+        let mut open_markets: Vec<String> = self
+            .orders
+            .open_orders()
+            .into_iter()
+            .map(|o| o.market)
+            .filter(|m| !m.is_empty())
+            .collect();
+        open_markets.sort();
+        open_markets.dedup();
+
+        for condition_id in open_markets {
+            if condition_id != market.condition_id {
+                targets.push((condition_id, "rollover"));
+            } else if let Some(remaining) = self.polymarket.get_remaining_secs() {
+                if remaining <= self.config.cancel_before_close_secs as i64 && self.config.cancel_before_close_secs > 0 {
+                    targets.push((condition_id, "window close"));
+                }
+            }
+        }
+
+        for (condition_id, reason) in targets {
+            {
+                let mut last = self.lifecycle_cancels.write();
+                if last.get(&condition_id).is_some_and(|t| now - t < LIFECYCLE_RETRY_MS) {
+                    continue;
+                }
+                last.insert(condition_id.clone(), now);
+            }
+            tracing::info!("Cancelling orders on {} ({})", condition_id, reason);
+            if let Err(e) = self.cancel_market_orders(&condition_id, reason).await {
+                tracing::error!("Window lifecycle cancel on {} failed: {:?}", condition_id, e);
+            }
+        }
+    }
+
+    /// Dry run: cancel matching orders in the local store and the paper exchange
+    fn simulate_cancel(&self, filter: impl Fn(&OrderState) -> bool) -> Result<CancelResponse> {
+        let canceled: Vec<String> = self
+            .orders
+            .open_orders()
+            .into_iter()
+            .filter(|o| filter(o))
+            .map(|o| o.order_id)
+            .collect();
+        for order_id in &canceled {
+            self.paper.cancel(order_id);
+        }
+        Ok(CancelResponse {
+            canceled,
+            ..Default::default()
+        })
+    }
+
+    /// Dry run: re-check resting paper orders against the latest books
+    pub fn match_paper_orders(&self) -> Result<()> {
+        if !self.dry_run {
+            return Ok(());
+        }
+        let market = self.polymarket.get_active_market();
+        for (side, token_id) in [(TradeSide::Yes, &market.up_token_id), (TradeSide::No, &market.down_token_id)] {
+            if token_id.is_empty() {
+                continue;
+            }
+            for fill in self.paper.on_book(token_id, &self.polymarket.get_book(side)) {
+                self.apply_paper_fill(fill)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Route a paper fill through the same path as a user-channel fill
+    fn apply_paper_fill(&self, fill: FillInfo) -> Result<()> {
+        let Some(order_id) = fill.order_id.clone() else { return Ok(()) };
+        if let Some((order, true)) = self.orders.apply_fill(&order_id, fill.clone()) {
+            self.handle_user_event(&UserEvent::Fill { fill, order })?;
+        }
+        Ok(())
+    }
+
+    /// Apply a cancel result to the order store, log each cancelled order and summarize in the action log
+    fn finish_cancel(&self, label: &str, reason: &str, response: Result<CancelResponse>) -> Result<usize> {
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                self.record_action(ActionLogEntry::now(format!("{} ({}) → error: {}", label, reason, e)));
+                tracing::error!("{} failed: {:?}", label, e);
+                return Err(e);
+            }
+        };
+
+        for order_id in &response.canceled {
+            if let Some(order) = self.orders.mark_cancelled(order_id) {
+                let event = self.order_event(&order, format!("cancelled: {}", reason), None);
+                self.logger.log_trade(event)?;
+            }
+        }
+        for (order_id, why) in &response.not_canceled {
+            tracing::warn!("Order {} not cancelled: {}", order_id, why);
+        }
+
+        self.record_action(ActionLogEntry::now(format!(
+            "{} ({}) → {} cancelled{}",
+            label,
+            reason,
+            response.canceled.len(),
+            if response.not_canceled.is_empty() {
+                String::new()
+            } else {
+                format!(", {} failed", response.not_canceled.len())
+            }
+        )));
+        Ok(response.canceled.len())
+    }
+
+    fn set_kill_switch(&self, active: bool) {
+        self.state.write().kill_switch_active = active;
+        self.record_action(ActionLogEntry::now(format!("Kill switch → {}", if active { "ON" } else { "OFF" })));
+        tracing::info!("Kill switch: {}", if active { "ACTIVE" } else { "OFF" });
     }
 
     pub fn adjust_size(&self, delta: f64) {
@@ -177,22 +679,16 @@ impl TradeService {
         }
     }
 
-    fn check_risk(&self, side: TradeSide, size: f64, limit_price: f64) -> RiskCheckResult {
+    /// Checks shared by entries and exits: kill switch, quote staleness and spread. Orders
+    /// that only reduce what we hold skip the kill switch and spread, so we can always get out.
+    fn check_market_risk(&self, side: TradeSide, reducing: bool) -> RiskCheckResult {
         let state = self.state.read();
 
         // Kill switch check
-        if state.kill_switch_active {
+        if state.kill_switch_active && !reducing {
             return RiskCheckResult::Rejected("Kill switch is active".to_string());
         }
 
-        // Size limit
-        if size > self.config.max_size {
-            return RiskCheckResult::Rejected(format!(
-                "Size {} exceeds max size {}",
-                size, self.config.max_size
-            ));
-        }
-
         // Get quote state
         let quotes = self.polymarket.get_quote_state();
 
@@ -205,10 +701,43 @@ impl TradeService {
             ));
         }
 
+        let (bid, ask) = match side {
+            TradeSide::Yes => (quotes.yes_bid, quotes.yes_ask),
+            TradeSide::No => (quotes.no_bid, quotes.no_ask),
+        };
+
+        // Spread check
+        if let (Some(b), Some(a), false) = (bid, ask, reducing) {
+            let spread = a - b;
+            if spread > self.config.max_spread {
+                return RiskCheckResult::Rejected(format!(
+                    "Spread {} exceeds max spread {}",
+                    spread, self.config.max_spread
+                ));
+            }
+        }
+
+        RiskCheckResult::Allowed
+    }
+
+    fn check_risk(&self, side: TradeSide, size: f64, limit_price: f64) -> RiskCheckResult {
+        if let RiskCheckResult::Rejected(reason) = self.check_market_risk(side, false) {
+            return RiskCheckResult::Rejected(reason);
+        }
+
+        // Size limit
+        if size > self.config.max_size {
+            return RiskCheckResult::Rejected(format!(
+                "Size {} exceeds max size {}",
+                size, self.config.max_size
+            ));
+        }
+
         // Price checks
-        let (bid, ask, max_price) = match side {
-            TradeSide::Yes => (quotes.yes_bid, quotes.yes_ask, state.max_price_yes),
-            TradeSide::No => (quotes.no_bid, quotes.no_ask, state.max_price_no),
+        let state = self.state.read();
+        let max_price = match side {
+            TradeSide::Yes => state.max_price_yes,
+            TradeSide::No => state.max_price_no,
         };
 
         if limit_price > max_price {
@@ -218,13 +747,13 @@ impl TradeService {
             ));
         }
 
-        // Spread check
-        if let (Some(b), Some(a)) = (bid, ask) {
-            let spread = a - b;
-            if spread > self.config.max_spread {
+        // Max price is all-in: a taker buy pays the fee on top (post-only orders never take)
+        if !self.config.post_only {
+            let cost = self.fee_model(side).buy_cost(limit_price);
+            if cost > max_price + 1e-9 {
                 return RiskCheckResult::Rejected(format!(
-                    "Spread {} exceeds max spread {}",
-                    spread, self.config.max_spread
+                    "Limit price {} plus taker fee ({:.4} all-in) exceeds max price {}",
+                    limit_price, cost, max_price
                 ));
             }
         }
@@ -232,39 +761,164 @@ impl TradeService {
         RiskCheckResult::Allowed
     }
 
-    pub async fn place_order(&self, side: TradeSide) -> Result<TradeEvent> {
-        let t_send_ms = chrono::Utc::now().timestamp_millis();
-        let client_order_id = Uuid::new_v4().to_string();
+    fn check_sell_risk(&self, side: TradeSide, size: f64, limit_price: Option<f64>) -> RiskCheckResult {
+        if size <= 0.0 {
+            return RiskCheckResult::Rejected(format!("No {} position to sell", side));
+        }
+
+        // Selling no more than we hold only reduces the position
+        let reducing = size <= self.sellable_size(side) + 1e-9;
+        if let RiskCheckResult::Rejected(reason) = self.check_market_risk(side, reducing) {
+            return RiskCheckResult::Rejected(reason);
+        }
+
         let state = self.state.read();
-        let size = state.current_size;
-        let max_price_yes = state.max_price_yes;
-        let max_price_no = state.max_price_no;
-        drop(state);
+        let min_price = match side {
+            TradeSide::Yes => state.min_price_yes,
+            TradeSide::No => state.min_price_no,
+        };
+
+        // The floor applies to what we net after the taker fee
+        let fees = self.fee_model(side);
+        match limit_price {
+            None => RiskCheckResult::Rejected("No bid to sell into".to_string()),
+            Some(price) if fees.sell_proceeds(price) < min_price - 1e-9 => RiskCheckResult::Rejected(format!(
+                "Bid {} ({:.4} after fee) below min sell price {}",
+                price,
+                fees.sell_proceeds(price),
+                min_price
+            )),
+            Some(_) => RiskCheckResult::Allowed,
+        }
+    }
+
+    /// Shares of `side` held in the active window that aren't already committed to an open SELL
+    pub fn sellable_size(&self, side: TradeSide) -> f64 {
+        let market = self.polymarket.get_active_market();
+        let token_id = match side {
+            TradeSide::Yes => &market.up_token_id,
+            TradeSide::No => &market.down_token_id,
+        };
+        if token_id.is_empty() {
+            return 0.0;
+        }
+
+        let held: f64 = self
+            .positions
+            .window_positions(&market.condition_id)
+            .iter()
+            .filter(|p| &p.token_id == token_id && !p.settled)
+            .map(|p| p.size)
+            .sum();
+        let pending: f64 = self
+            .orders
+            .open_orders()
+            .iter()
+            .filter(|o| &o.asset_id == token_id && o.side == "SELL")
+            .map(|o| o.remaining_size())
+            .sum();
+
+        // Shares trade in hundredths; never try to sell more than we hold
+        ((held - pending).max(0.0) * 100.0).floor() / 100.0
+    }
+
+    pub async fn place_order(&self, side: TradeSide) -> Result<TradeEvent> {
+        let (size, max_price_yes, max_price_no) = {
+            let state = self.state.read();
+            (state.current_size, state.max_price_yes, state.max_price_no)
+        };
 
-        // Use current market (best ask) as order price, capped by max price
+        // Use current market (best ask, or best bid when post-only) as order price, capped by
+        // the highest price whose all-in cost stays within max price
         let quotes = self.polymarket.get_quote_state();
+        let (yes_price, no_price) = if self.config.post_only {
+            (quotes.yes_bid, quotes.no_bid)
+        } else {
+            (quotes.yes_ask, quotes.no_ask)
+        };
+        let cap = |max_price: f64| {
+            if self.config.post_only {
+                max_price
+            } else {
+                self.fee_model(side).max_buy_price(max_price)
+            }
+        };
         let limit_price = match side {
-            TradeSide::Yes => quotes
-                .yes_ask
-                .map(|ask| ask.min(max_price_yes))
-                .unwrap_or(max_price_yes),
-            TradeSide::No => quotes
-                .no_ask
-                .map(|ask| ask.min(max_price_no))
-                .unwrap_or(max_price_no),
+            TradeSide::Yes => yes_price
+                .map(|p| p.min(cap(max_price_yes)))
+                .unwrap_or(cap(max_price_yes)),
+            TradeSide::No => no_price
+                .map(|p| p.min(cap(max_price_no)))
+                .unwrap_or(cap(max_price_no)),
         };
 
-        // Risk check
         let risk_result = self.check_risk(side, size, limit_price);
+        // GTC: rests at our limit if the ask moves away
+        self.submit_order(side, OrderSide::Buy, size, limit_price, "GTC", risk_result).await
+    }
+
+    /// Sell up to the order size of our `side` shares at the current bid
+    pub async fn sell_position(&self, side: TradeSide) -> Result<TradeEvent> {
+        let size = self.sellable_size(side).min(self.state.read().current_size);
+        self.sell(side, size).await
+    }
+
+    /// Exit everything we hold in the active window at the bids. A rejected side
+    /// doesn't stop the other one from being sold.
+    pub async fn flatten_position(&self) -> Result<Vec<TradeEvent>> {
+        let mut events = Vec::new();
+        for side in [TradeSide::Yes, TradeSide::No] {
+            let size = self.sellable_size(side);
+            if size > 0.0 {
+                match self.sell(side, size).await {
+                    Ok(event) => events.push(event),
+                    Err(e) => tracing::warn!("Flatten {} failed: {:?}", side, e),
+                }
+            }
+        }
+        if events.is_empty() {
+            self.record_action(ActionLogEntry::now("Flatten → nothing to sell"));
+        }
+        Ok(events)
+    }
+
+    async fn sell(&self, side: TradeSide, size: f64) -> Result<TradeEvent> {
+        let quotes = self.polymarket.get_quote_state();
+        let bid = match side {
+            TradeSide::Yes => quotes.yes_bid,
+            TradeSide::No => quotes.no_bid,
+        };
+
+        let risk_result = self.check_sell_risk(side, size, bid);
+        // FAK: take what the bid offers, never leave a resting sell below it
+        self.submit_order(side, OrderSide::Sell, size, bid.unwrap_or(0.0), "FAK", risk_result).await
+    }
+
+    async fn submit_order(
+        &self,
+        side: TradeSide,
+        order_side: OrderSide,
+        size: f64,
+        limit_price: f64,
+        order_type: &str,
+        risk_result: RiskCheckResult,
+    ) -> Result<TradeEvent> {
+        let t_send_ms = self.polymarket.clock().now_ms();
+        let client_order_id = Uuid::new_v4().to_string();
+        let verb = match order_side {
+            OrderSide::Buy => "Buy",
+            OrderSide::Sell => "Sell",
+        };
 
         let mut trade_event = TradeEvent {
             t_send_ms,
             t_resp_ms: None,
             client_order_id,
             side: side.to_string(),
+            order_side: Some(order_side.as_str().to_string()),
             size,
             limit_price,
-            post_only: true,
+            post_only: order_side == OrderSide::Buy && self.config.post_only,
             mode: if self.dry_run { "dry_run".to_string() } else { "live".to_string() },
             risk_reject_reason: None,
             api_status: None,
@@ -274,37 +928,17 @@ impl TradeService {
         match risk_result {
             RiskCheckResult::Rejected(reason) => {
                 trade_event.risk_reject_reason = Some(reason.clone());
-                trade_event.t_resp_ms = Some(chrono::Utc::now().timestamp_millis());
+                trade_event.t_resp_ms = Some(self.polymarket.clock().now_ms());
                 self.record_action(ActionLogEntry::now(format!(
-                    "Buy {} @ {:.2} size {:.0} → rejected: {}",
-                    side, limit_price, size, reason
+                    "{} {} @ {:.2} size {:.0} → rejected: {}",
+                    verb, side, limit_price, size, reason
                 )));
                 self.logger.log_trade(trade_event.clone())?;
-                return Err(anyhow!("Order rejected: {}", reason));
+                return Err(RiskRejected(reason).into());
             }
             RiskCheckResult::Allowed => {}
         }
 
-        if self.dry_run {
-            // Dry run - just log the intent
-            trade_event.api_status = Some("dry_run_success".to_string());
-            trade_event.t_resp_ms = Some(chrono::Utc::now().timestamp_millis());
-            self.record_action(ActionLogEntry::now(format!(
-                "Buy {} @ {:.2} size {:.0} → dry_run",
-                side, limit_price, size
-            )));
-            self.logger.log_trade(trade_event.clone())?;
-            tracing::info!(
-                "[DRY RUN] Order: {} {} @ {} (size: {})",
-                side,
-                trade_event.client_order_id,
-                limit_price,
-                size
-            );
-            return Ok(trade_event);
-        }
-
-        // Live mode - place order via CLOB API
         let active_market = self.polymarket.get_active_market();
 
         // Get the appropriate token ID based on side
@@ -316,27 +950,88 @@ impl TradeService {
 
         if token_id.is_empty() {
             trade_event.api_status = Some("no_active_market".to_string());
-            trade_event.t_resp_ms = Some(chrono::Utc::now().timestamp_millis());
+            trade_event.t_resp_ms = Some(self.polymarket.clock().now_ms());
             self.record_action(ActionLogEntry::now(format!(
-                "Buy {} @ {:.2} size {:.0} → no active market",
-                side, limit_price, size
+                "{} {} @ {:.2} size {:.0} → no active market",
+                verb, side, limit_price, size
             )));
             self.logger.log_trade(trade_event.clone())?;
             return Err(anyhow!("No active market - token ID not available"));
         }
 
-        // Live: send BUY for the chosen token (Yes=Up, No=Down). We never send SELL.
+        if self.dry_run {
+            // Dry run - match against the live book instead of the exchange
+            let order_id = format!("paper-{}", trade_event.client_order_id);
+            let paper_order = PaperOrder {
+                order_id: order_id.clone(),
+                asset_id: token_id.clone(),
+                side: order_side.as_str().to_string(),
+                price: limit_price,
+                size,
+                order_type: order_type.to_string(),
+                post_only: trade_event.post_only,
+            };
+            let result = self.paper.submit(&paper_order, &self.polymarket.get_book(side));
+            trade_event.t_resp_ms = Some(self.polymarket.clock().now_ms());
+
+            let (fills, resting) = match result {
+                PaperResult::Rejected(reason) => {
+                    trade_event.api_status = Some(format!("dry_run_rejected: {}", reason));
+                    self.record_action(ActionLogEntry::now(format!(
+                        "{} {} @ {:.2} size {:.0} → dry_run rejected: {}",
+                        verb, side, limit_price, size, reason
+                    )));
+                    self.logger.log_trade(trade_event.clone())?;
+                    return Ok(trade_event);
+                }
+                PaperResult::Accepted { fills, resting } => (fills, resting),
+            };
+
+            trade_event.api_status = Some("dry_run_success".to_string());
+            self.record_action(ActionLogEntry::now(format!(
+                "{} {} @ {:.2} size {:.0} → dry_run",
+                verb, side, limit_price, size
+            )));
+            self.logger.log_trade(trade_event.clone())?;
+            tracing::info!(
+                "[DRY RUN] {} order: {} {} @ {} (size: {}, {} fills)",
+                order_side.as_str(),
+                side,
+                trade_event.client_order_id,
+                limit_price,
+                size,
+                fills.len()
+            );
+
+            let mut order = OrderState::new(&order_id, token_id, order_side.as_str(), limit_price, size);
+            order.market = active_market.condition_id.clone();
+            order.client_order_id = Some(trade_event.client_order_id.clone());
+            order.created_ms = t_send_ms;
+            self.orders.register(order);
+            for fill in fills {
+                self.apply_paper_fill(fill)?;
+            }
+            // FAK/FOK remainder is killed rather than left resting
+            if !resting && self.orders.get(&order_id).is_some_and(|o| o.status.is_open()) {
+                self.orders.mark_cancelled(&order_id);
+            }
+            return Ok(trade_event);
+        }
+
+        // Live: BUY or SELL the chosen token (Yes=Up, No=Down)
         let order_request = OrderRequest {
             token_id: token_id.clone(),
             price: format!("{:.2}", limit_price),
-            size: format!("{:.0}", size),
-            side: "BUY".to_string(),
-            order_type: "GTC".to_string(), // Good Till Cancelled
+            size: format!("{:.2}", size),
+            side: order_side.as_str().to_string(),
+            order_type: order_type.to_string(),
             expiration: None,
+            post_only: trade_event.post_only,
         };
 
         tracing::info!(
-            "[LIVE] Placing BUY order: side={} @ {} size {} (token {}...)",
+            "[LIVE] Placing {} order: side={} @ {} size {} (token {}...)",
+            order_side.as_str(),
             side,
             limit_price,
             size,
@@ -345,16 +1040,21 @@ impl TradeService {
 
         match self.clob_client.place_order(order_request).await {
             Ok(response) => {
-                trade_event.t_resp_ms = Some(chrono::Utc::now().timestamp_millis());
+                trade_event.t_resp_ms = Some(self.polymarket.clock().now_ms());
 
                 if response.success {
                     trade_event.api_status = Some("success".to_string());
                     self.record_action(ActionLogEntry::now(format!(
-                        "Buy {} @ {:.2} size {:.0} → success",
-                        side, limit_price, size
+                        "{} {} @ {:.2} size {:.0} → success",
+                        verb, side, limit_price, size
                     )));
                     if let Some(order_id) = &response.order_id {
                         tracing::info!("[LIVE] Order placed successfully: {}", order_id);
+                        let mut order = OrderState::new(order_id, token_id, order_side.as_str(), limit_price, size);
+                        order.market = active_market.condition_id.clone();
+                        order.client_order_id = Some(trade_event.client_order_id.clone());
+                        order.created_ms = t_send_ms;
+                        self.orders.register(order);
                     }
                 } else {
                     let error_msg = if let Some(msg) = &response.error_msg {
@@ -381,8 +1081,8 @@ impl TradeService {
                     };
                     trade_event.api_status = Some(format!("error: {}", error_msg));
                     self.record_action(ActionLogEntry::now(format!(
-                        "Buy {} @ {:.2} size {:.0} → error: {}",
-                        side, limit_price, size, error_msg
+                        "{} {} @ {:.2} size {:.0} → error: {}",
+                        verb, side, limit_price, size, error_msg
                     )));
                     self.record_action(ActionLogEntry::now(self.credentials_debug_string()));
                     tracing::error!("[LIVE] Order failed: {}", error_msg);
@@ -392,11 +1092,11 @@ impl TradeService {
                 Ok(trade_event)
             }
             Err(e) => {
-                trade_event.t_resp_ms = Some(chrono::Utc::now().timestamp_millis());
+                trade_event.t_resp_ms = Some(self.polymarket.clock().now_ms());
                 trade_event.api_status = Some(format!("error: {}", e));
                 self.record_action(ActionLogEntry::now(format!(
-                    "Buy {} @ {:.2} size {:.0} → error: {}",
-                    side, limit_price, size, e
+                    "{} {} @ {:.2} size {:.0} → error: {}",
+                    verb, side, limit_price, size, e
                 )));
                 self.record_action(ActionLogEntry::now(self.credentials_debug_string()));
                 self.logger.log_trade(trade_event.clone())?;
@@ -410,35 +1110,13 @@ impl TradeService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::PolymarketConfig;
+    use crate::services::testing::{book_msg, poly_config, trading_config};
+    use crate::services::ActiveMarket;
     use tempfile::tempdir;
 
-    fn make_test_config() -> TradingConfig {
-        TradingConfig {
-            default_size: 10.0,
-            max_size: 100.0,
-            max_price_yes: 0.95,
-            max_price_no: 0.95,
-            max_spread: 0.10,
-            stale_quote_threshold_ms: 5000,
-        }
-    }
-
-    fn make_poly_config() -> PolymarketConfig {
-        PolymarketConfig {
-            ws_url: "wss://test".to_string(),
-            rest_url: "https://test".to_string(),
-            gamma_url: "https://gamma-api.polymarket.com".to_string(),
-            btc_15m_event_id: "194059".to_string(),
-            yes_token_id: "yes".to_string(),
-            no_token_id: "no".to_string(),
-            condition_id: "cond".to_string(),
-        }
-    }
-
     #[test]
     fn test_trading_state_new() {
-        let config = make_test_config();
+        let config = trading_config();
 
         let state = TradingState::new(&config);
         assert!(!state.kill_switch_active);
@@ -447,16 +1125,29 @@ mod tests {
     }
 
     #[test]
-    fn test_kill_switch_toggle() {
+    fn test_auto_trade_needs_trader() {
+        let (trade, _dir) = make_trade_with_market();
+        assert!(!trade.toggle_auto_trade());
+        trade.set_auto_trade(true);
+        assert!(!trade.get_state().auto_trade_active);
+        assert!(trade.get_action_log().last().unwrap().description.contains("no auto trader"));
+
+        trade.attach_auto_trader();
+        assert!(trade.toggle_auto_trade());
+        assert!(!trade.toggle_auto_trade());
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_toggle() {
         let dir = tempdir().unwrap();
         let logger = crate::logger::JsonlLogger::new(dir.path().to_str().unwrap()).unwrap();
-        let poly = Arc::new(PolymarketService::new(make_poly_config()));
-        let trade = TradeService::new(make_test_config(), poly, None, logger, true);
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let trade = TradeService::new(trading_config(), poly, None, logger, true);
 
         assert!(!trade.get_state().kill_switch_active);
-        trade.toggle_kill_switch();
+        assert!(trade.toggle_kill_switch().await.unwrap());
         assert!(trade.get_state().kill_switch_active);
-        trade.toggle_kill_switch();
+        assert!(!trade.toggle_kill_switch().await.unwrap());
         assert!(!trade.get_state().kill_switch_active);
     }
 
@@ -464,8 +1155,8 @@ mod tests {
     fn test_size_adjustment() {
         let dir = tempdir().unwrap();
         let logger = crate::logger::JsonlLogger::new(dir.path().to_str().unwrap()).unwrap();
-        let poly = Arc::new(PolymarketService::new(make_poly_config()));
-        let trade = TradeService::new(make_test_config(), poly, None, logger, true);
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let trade = TradeService::new(trading_config(), poly, None, logger, true);
 
         assert_eq!(trade.get_state().current_size, 10.0);
         trade.adjust_size(5.0);
@@ -476,12 +1167,279 @@ mod tests {
         assert_eq!(trade.get_state().current_size, 100.0);
     }
 
+    #[test]
+    fn test_user_fill_logged_as_trade_event() {
+        let dir = tempdir().unwrap();
+        let logger = crate::logger::JsonlLogger::new(dir.path().to_str().unwrap()).unwrap();
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        poly.set_active_market(ActiveMarket {
+            up_token_id: "UP".to_string(),
+            down_token_id: "DOWN".to_string(),
+            condition_id: "0xcond".to_string(),
+            ..Default::default()
+        });
+        let trade = TradeService::new(trading_config(), poly, None, logger, true);
+
+        let mut order = OrderState::new("0xabc", "UP", "BUY", 0.55, 10.0);
+        order.client_order_id = Some("client-1".to_string());
+        trade.order_store().register(order);
+
+        let fill = FillInfo {
+            price: 0.55,
+            size: 4.0,
+            trade_id: Some("t1".to_string()),
+            order_id: Some("0xabc".to_string()),
+            status: Some("MATCHED".to_string()),
+            t_fill_ms: Some(1_000),
+            liquidity: Some("TAKER".to_string()),
+            fee: None,
+        };
+        let (order, _) = trade.order_store().apply_fill("0xabc", fill.clone()).unwrap();
+        trade.handle_user_event(&UserEvent::Fill { fill, order }).unwrap();
+
+        let today = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let trades = std::fs::read_to_string(dir.path().join(today).join("trades.jsonl")).unwrap();
+        let event: TradeEvent = serde_json::from_str(trades.lines().last().unwrap()).unwrap();
+        assert_eq!(event.client_order_id, "client-1");
+        assert_eq!(event.api_status.as_deref(), Some("fill: MATCHED"));
+        let fills = event.fills.unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].size, 4.0);
+        assert_eq!(fills[0].trade_id.as_deref(), Some("t1"));
+        assert!(trade.get_action_log().last().unwrap().description.starts_with("Fill BUY"));
+
+        // Booked once into the ledger even as the trade settles on chain
+        let (order, _) = trade.order_store().apply_fill("0xabc", FillInfo {
+            status: Some("CONFIRMED".to_string()),
+            ..fills[0].clone()
+        }).unwrap();
+        let fill = order.fills[0].clone();
+        trade.handle_user_event(&UserEvent::Fill { fill, order }).unwrap();
+        let positions = trade.get_positions();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].size, 4.0);
+        assert_eq!(positions[0].side, "YES");
+        assert_eq!(positions[0].condition_id, "0xcond");
+
+        // Close above target: Up pays 1.0
+        assert!((trade.settle_window("0xcond", 100.0, 101.0).unwrap() - 1.8).abs() < 1e-9);
+        assert!((trade.total_realized_pnl() - 1.8).abs() < 1e-9);
+    }
+
+    fn make_trade_with_market() -> (TradeService, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let logger = crate::logger::JsonlLogger::new(dir.path().to_str().unwrap()).unwrap();
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        poly.set_active_market(ActiveMarket {
+            up_token_id: "UP".to_string(),
+            down_token_id: "DOWN".to_string(),
+            condition_id: "0xcond".to_string(),
+            ..Default::default()
+        });
+        poly.handle_message(&book_msg((0.60, 0.62), (0.03, 0.05)));
+        (TradeService::new(trading_config(), poly, None, logger, true), dir)
+    }
+
+    #[tokio::test]
+    async fn test_sell_requires_position() {
+        let (trade, _dir) = make_trade_with_market();
+        let err = trade.sell_position(TradeSide::Yes).await.unwrap_err();
+        assert!(err.to_string().contains("No YES position"));
+        assert!(trade.flatten_position().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sell_and_flatten_at_bid() {
+        let (trade, _dir) = make_trade_with_market();
+        trade.positions.apply_fill("0xcond", "UP", "YES", "BUY", 0.50, 25.0, 0.0).unwrap();
+        trade.positions.apply_fill("0xcond", "DOWN", "NO", "BUY", 0.40, 5.0, 0.0).unwrap();
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 25.0);
+
+        // Sell is capped at the order size (10) and priced at the bid
+        let event = trade.sell_position(TradeSide::Yes).await.unwrap();
+        assert_eq!(event.order_side.as_deref(), Some("SELL"));
+        assert_eq!(event.size, 10.0);
+        assert_eq!(event.limit_price, 0.60);
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 15.0);
+
+        // NO bid (0.03) is under the 0.05 floor; the rest of YES still gets sold
+        let events = trade.flatten_position().await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].side.as_str(), events[0].size), ("YES", 15.0));
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 0.0);
+        assert!((trade.total_realized_pnl() - 2.5).abs() < 1e-9);
+        let last = trade.get_action_log().last().unwrap().description.clone();
+        assert!(last.starts_with("Sell NO") && last.contains("below min sell price"));
+    }
+
+    #[test]
+    fn test_failed_sell_reverts_booked_size() {
+        let (trade, _dir) = make_trade_with_market();
+        trade.positions.apply_fill("0xcond", "UP", "YES", "BUY", 0.50, 6.0, 0.0).unwrap();
+        trade.order_store().register(OrderState::new("0xsell", "UP", "SELL", 0.60, 10.0));
+
+        // The sell fills 10 but only the 6 held are booked
+        let fill = FillInfo {
+            price: 0.60,
+            size: 10.0,
+            trade_id: Some("t1".to_string()),
+            order_id: Some("0xsell".to_string()),
+            status: Some("MATCHED".to_string()),
+            t_fill_ms: Some(1_000),
+            liquidity: Some("MAKER".to_string()),
+            fee: None,
+        };
+        let (order, _) = trade.order_store().apply_fill("0xsell", fill.clone()).unwrap();
+        trade.handle_user_event(&UserEvent::Fill { fill: fill.clone(), order: order.clone() }).unwrap();
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 0.0);
+
+        let failed = FillInfo {
+            status: Some("FAILED".to_string()),
+            ..fill
+        };
+        trade.handle_user_event(&UserEvent::Fill { fill: failed, order }).unwrap();
+        let pos = trade.get_positions()[0].clone();
+        assert_eq!(pos.size, 6.0);
+        assert!(pos.realized_pnl.abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_reducing_sells_skip_kill_switch_and_spread() {
+        let (trade, _dir) = make_trade_with_market();
+        trade.positions.apply_fill("0xcond", "UP", "YES", "BUY", 0.50, 25.0, 0.0).unwrap();
+        trade.engage_kill_switch().await.unwrap();
+        // Ask pulled to 0.95: a 0.35 spread, well over the 0.10 max
+        trade.polymarket.handle_message(
+            r#"{"event_type":"book","asset_id":"UP","market":"0xcond",
+            "bids":[{"price":"0.60","size":"100"}],"asks":[{"price":"0.95","size":"100"}]}"#,
+        );
+
+        assert!(trade.place_order(TradeSide::Yes).await.unwrap_err().to_string().contains("Kill switch"));
+        assert!(matches!(trade.check_sell_risk(TradeSide::Yes, 30.0, Some(0.60)), RiskCheckResult::Rejected(r) if r.contains("Kill switch")));
+
+        let event = trade.sell_position(TradeSide::Yes).await.unwrap();
+        assert_eq!(event.api_status.as_deref(), Some("dry_run_success"));
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 15.0);
+        assert_eq!(trade.flatten_position().await.unwrap().len(), 1);
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_paper_buy_fills_and_rests() {
+        let (trade, dir) = make_trade_with_market();
+        trade.adjust_size(140.0); // capped at max_size 100
+
+        // Takes all 100 shares offered at 0.62
+        let event = trade.place_order(TradeSide::Yes).await.unwrap();
+        assert_eq!(event.api_status.as_deref(), Some("dry_run_success"));
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 100.0);
+        assert!(trade.order_store().open_orders().is_empty());
+
+        // Same book again: the displayed size is gone, so the order rests at 0.62
+        trade.place_order(TradeSide::Yes).await.unwrap();
+        let open = trade.order_store().open_orders();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].size_matched, 0.0);
+
+        // A seller hits 0.62: the resting order fills as the maker
+        trade.polymarket.handle_message(
+            r#"{"event_type":"price_change","market":"0xcond","price_changes":[
+            {"asset_id":"UP","price":"0.61","size":"200","side":"SELL","best_bid":"0.60","best_ask":"0.61"}]}"#,
+        );
+        trade.match_paper_orders().unwrap();
+        assert!(trade.order_store().open_orders().is_empty());
+        assert_eq!(trade.sellable_size(TradeSide::Yes), 200.0);
+
+        let today = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let trades = std::fs::read_to_string(dir.path().join(today).join("trades.jsonl")).unwrap();
+        assert_eq!(trades.lines().filter(|l| l.contains("fill: MATCHED")).count(), 2);
+        assert!(trade.get_action_log().last().unwrap().description.starts_with("Fill BUY YES 100 @ 0.62"));
+    }
+
+    #[tokio::test]
+    async fn test_taker_fees_in_risk_and_pnl() {
+        let (trade, _dir) = make_trade_with_market();
+        trade.polymarket.set_fee_rate_bps("0xcond", 1000);
+
+        // Lifts the 0.62 ask and pays 10% of 0.38 per share
+        trade.place_order(TradeSide::Yes).await.unwrap();
+        let pos = trade.get_positions()[0].clone();
+        assert!((pos.fees_paid - 0.38).abs() < 1e-9);
+        assert!((pos.avg_cost - 0.658).abs() < 1e-9);
+
+        // Max price is all-in: with a 0.63 cap the order is priced at 0.58 and rests
+        trade.state.write().max_price_yes = 0.63;
+        let event = trade.place_order(TradeSide::Yes).await.unwrap();
+        assert_eq!(event.limit_price, 0.58);
+        assert_eq!(trade.order_store().open_orders().len(), 1);
+        assert!(matches!(trade.check_risk(TradeSide::Yes, 10.0, 0.62), RiskCheckResult::Rejected(r) if r.contains("taker fee")));
+
+        // Selling at the 0.60 bid nets 0.56 after fees
+        trade.state.write().min_price_yes = 0.58;
+        let err = trade.sell_position(TradeSide::Yes).await.unwrap_err();
+        assert!(err.to_string().contains("after fee"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_cancels() {
+        let (trade, dir) = make_trade_with_market();
+        for (id, market) in [("0x1", "0xcond"), ("0x2", "0xcond"), ("0x3", "0xother")] {
+            let mut order = OrderState::new(id, "UP", "BUY", 0.50, 10.0);
+            order.market = market.to_string();
+            trade.order_store().register(order);
+        }
+
+        assert_eq!(trade.cancel_order("0x1", "manual").await.unwrap(), 1);
+        assert_eq!(trade.cancel_market_orders("0xcond", "manual").await.unwrap(), 1);
+        assert_eq!(trade.order_store().open_orders().len(), 1);
+
+        // Engaging the kill switch pulls the rest on its own
+        assert_eq!(trade.engage_kill_switch().await.unwrap(), 1);
+        assert!(trade.get_state().kill_switch_active);
+        assert!(trade.order_store().open_orders().is_empty());
+        assert!(trade.get_action_log().last().unwrap().description.contains("Cancel all (kill switch) → 1 cancelled"));
+
+        let today = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let trades = std::fs::read_to_string(dir.path().join(today).join("trades.jsonl")).unwrap();
+        let cancels = trades.lines().filter(|l| l.contains("cancelled: ")).count();
+        assert_eq!(cancels, 3);
+    }
+
+    #[tokio::test]
+    async fn test_window_lifecycle_cancels() {
+        let (trade, dir) = make_trade_with_market();
+        let mut old = OrderState::new("0xold", "OLD_UP", "BUY", 0.50, 10.0);
+        old.market = "0xprev".to_string();
+        trade.order_store().register(old);
+        let mut current = OrderState::new("0xcur", "UP", "BUY", 0.50, 10.0);
+        current.market = "0xcond".to_string();
+        trade.order_store().register(current);
+
+        // Window has no end_date yet: only the previous window's order is pulled
+        trade.enforce_window_lifecycle().await;
+        let open = trade.order_store().open_orders();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].order_id, "0xcur");
+
+        // 20s left with a 30s rule: the current window's order goes too
+        let mut market = trade.polymarket.get_active_market();
+        market.end_date = (chrono::Utc::now() + chrono::Duration::seconds(20)).to_rfc3339();
+        trade.polymarket.set_active_market(market);
+        trade.enforce_window_lifecycle().await;
+        assert!(trade.order_store().open_orders().is_empty());
+
+        let today = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let trades = std::fs::read_to_string(dir.path().join(today).join("trades.jsonl")).unwrap();
+        assert!(trades.contains("cancelled: rollover"));
+        assert!(trades.contains("cancelled: window close"));
+    }
+
     #[test]
     fn test_max_price_adjustment() {
         let dir = tempdir().unwrap();
         let logger = crate::logger::JsonlLogger::new(dir.path().to_str().unwrap()).unwrap();
-        let poly = Arc::new(PolymarketService::new(make_poly_config()));
-        let trade = TradeService::new(make_test_config(), poly, None, logger, true);
+        let poly = Arc::new(PolymarketService::new(poly_config()));
+        let trade = TradeService::new(trading_config(), poly, None, logger, true);
 
         assert!((trade.get_state().max_price_yes - 0.95).abs() < 0.001);
         trade.adjust_max_price(TradeSide::Yes, -0.05);
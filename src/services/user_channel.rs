@@ -0,0 +1,497 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::events::FillInfo;
+use super::clob::ClobCredentials;
+use super::orders::{OrderState, OrderStore};
+
+#[derive(Debug, Clone, Serialize)]
+struct UserSubscribeMessage {
+    auth: UserAuth,
+    #[serde(rename = "type")]
+    msg_type: String,
+    markets: Vec<String>, // Empty = every market the key has activity in
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UserAuth {
+    api_key: String,
+    secret: String,
+    passphrase: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event_type", rename_all = "lowercase")]
+enum UserMessage {
+    Order(OrderMessage),
+    Trade(TradeMessage),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OrderMessage {
+    id: String,
+    asset_id: String,
+    #[serde(default)]
+    market: String,
+    side: String,
+    #[serde(default)]
+    outcome: String,
+    price: String,
+    original_size: String,
+    #[serde(default)]
+    size_matched: Option<String>,
+    #[serde(rename = "type")]
+    kind: String, // PLACEMENT / UPDATE / CANCELLATION
+    timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TradeMessage {
+    id: String,
+    asset_id: String,
+    side: String,
+    price: String,
+    size: String,
+    status: String, // MATCHED / MINED / CONFIRMED / RETRYING / FAILED
+    taker_order_id: String,
+    // Owner of the taker order; `owner` is whoever the event is for, which on our channel is us
+    trade_owner: Option<String>,
+    #[serde(default)]
+    maker_orders: Vec<MakerOrder>,
+    matchtime: Option<String>,
+    timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MakerOrder {
+    order_id: String,
+    asset_id: String,
+    price: String,
+    matched_amount: String,
+    owner: Option<String>,
+    side: Option<String>,
+}
+
+/// What the user channel told us, after it has been applied to the order store
+#[derive(Debug, Clone)]
+pub enum UserEvent {
+    /// PLACEMENT, UPDATE or CANCELLATION
+    Order { kind: String, order: OrderState },
+    /// A new fill, or a settlement status change for one we already saw
+    Fill { fill: FillInfo, order: OrderState },
+}
+
+pub struct UserChannelService {
+    ws_url: String,
+    credentials: ClobCredentials,
+    orders: Arc<OrderStore>,
+    event_tx: broadcast::Sender<UserEvent>,
+    running: Arc<RwLock<bool>>,
+}
+
+impl UserChannelService {
+    pub fn new(ws_url: &str, credentials: ClobCredentials, orders: Arc<OrderStore>) -> Self {
+        let (tx, _) = broadcast::channel(1000);
+        Self {
+            ws_url: ws_url.to_string(),
+            credentials,
+            orders,
+            event_tx: tx,
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UserEvent> {
+        self.event_tx.subscribe()
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        *self.running.write() = true;
+
+        loop {
+            if !*self.running.read() {
+                break;
+            }
+
+            if let Err(e) = self.run_connection().await {
+                tracing::error!("User channel connection error: {:?}, reconnecting...", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run_connection(&self) -> Result<()> {
+        tracing::info!("Connecting to Polymarket user channel...");
+
+        let (ws_stream, _) = connect_async(&self.ws_url)
+            .await
+            .context("Failed to connect to user channel WebSocket")?;
+
+        let (mut write, mut read) = ws_stream.split();
+
+        let subscribe_msg = UserSubscribeMessage {
+            auth: UserAuth {
+                api_key: self.credentials.api_key.clone(),
+                secret: self.credentials.secret.clone(),
+                passphrase: self.credentials.passphrase.clone(),
+            },
+            msg_type: "user".to_string(),
+            markets: Vec::new(),
+        };
+
+        let msg_str = serde_json::to_string(&subscribe_msg)?;
+        write.send(Message::Text(msg_str)).await?;
+        tracing::info!("Subscribed to user channel (orders and fills)");
+
+        // The CLOB WS drops connections that don't send a text PING every ~10s
+        let mut ping_interval = tokio::time::interval(Duration::from_secs(10));
+
+        loop {
+            tokio::select! {
+                msg = read.next() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            self.handle_message(&text);
+                        }
+                        Some(Ok(Message::Ping(data))) => {
+                            if let Err(e) = write.send(Message::Pong(data)).await {
+                                tracing::error!("Failed to send pong: {:?}", e);
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) => {
+                            tracing::warn!("User channel WebSocket closed");
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            tracing::error!("User channel WebSocket error: {:?}", e);
+                            break;
+                        }
+                        None => break,
+                        _ => {}
+                    }
+                }
+                _ = ping_interval.tick() => {
+                    if let Err(e) = write.send(Message::Text("PING".to_string())).await {
+                        tracing::error!("Failed to send ping: {:?}", e);
+                        break;
+                    }
+                }
+            }
+
+            if !*self.running.read() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_message(&self, text: &str) {
+        // PONG replies and auth errors are plain text
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+            if text != "PONG" {
+                tracing::warn!("User channel: {}", text);
+            }
+            return;
+        };
+
+        let items = match value {
+            serde_json::Value::Array(items) => items,
+            other => vec![other],
+        };
+
+        for item in items {
+            match serde_json::from_value::<UserMessage>(item) {
+                Ok(UserMessage::Order(msg)) => self.process_order(msg),
+                Ok(UserMessage::Trade(msg)) => self.process_trade(msg),
+                Err(e) => tracing::debug!("Ignoring user channel message: {}", e),
+            }
+        }
+    }
+
+    fn process_order(&self, msg: OrderMessage) {
+        let mut update = OrderState::new(
+            &msg.id,
+            &msg.asset_id,
+            &msg.side.to_uppercase(),
+            parse_f64(&msg.price),
+            parse_f64(&msg.original_size),
+        );
+        update.market = msg.market;
+        update.outcome = msg.outcome;
+        update.size_matched = msg.size_matched.as_deref().map(parse_f64).unwrap_or(0.0);
+        update.updated_ms = parse_ts_ms(msg.timestamp.as_deref());
+
+        let cancelled = msg.kind.eq_ignore_ascii_case("CANCELLATION");
+        let order = self.orders.upsert(update, cancelled);
+        tracing::info!(
+            "[USER] Order {} {}: {} {} @ {} matched {}/{}",
+            msg.kind,
+            short_id(&order.order_id),
+            order.side,
+            order.outcome,
+            order.price,
+            order.size_matched,
+            order.original_size
+        );
+        let _ = self.event_tx.send(UserEvent::Order { kind: msg.kind.to_uppercase(), order });
+    }
+
+    fn process_trade(&self, msg: TradeMessage) {
+        let t_fill_ms = parse_ts_ms(msg.matchtime.as_deref().or(msg.timestamp.as_deref()));
+        let is_ours = |order_id: &str, owner: Option<&String>| {
+            self.orders.is_known(order_id) || owner.is_some_and(|o| *o == self.credentials.api_key)
+        };
+
+        // (order, asset, side, price, size, liquidity) for every leg of the match that belongs to us
+        let mut legs = Vec::new();
+        if is_ours(&msg.taker_order_id, msg.trade_owner.as_ref()) {
+            legs.push((
+                msg.taker_order_id.clone(),
+                msg.asset_id.clone(),
+                msg.side.to_uppercase(),
+                parse_f64(&msg.price),
+                parse_f64(&msg.size),
+                "TAKER",
+            ));
+        }
+        for maker in msg.maker_orders.iter().filter(|m| is_ours(&m.order_id, m.owner.as_ref())) {
+            let side = match &maker.side {
+                Some(side) => side.to_uppercase(),
+                None => match self.orders.get(&maker.order_id) {
+                    Some(order) => order.side,
+                    None => {
+                        tracing::warn!(
+                            "[USER] Maker fill {} on {} has no side and the order is unknown, skipping",
+                            short_id(&msg.id),
+                            short_id(&maker.order_id)
+                        );
+                        continue;
+                    }
+                },
+            };
+            legs.push((
+                maker.order_id.clone(),
+                maker.asset_id.clone(),
+                side,
+                parse_f64(&maker.price),
+                parse_f64(&maker.matched_amount),
+                "MAKER",
+            ));
+        }
+
+        for (order_id, asset_id, side, price, size, liquidity) in legs {
+            let fill = FillInfo {
+                price,
+                size,
+                trade_id: Some(msg.id.clone()),
+                order_id: Some(order_id.clone()),
+                status: Some(msg.status.to_uppercase()),
+                t_fill_ms: Some(t_fill_ms),
+                liquidity: Some(liquidity.to_string()),
+                fee: None,
+            };
+
+            // Fills can race ahead of the PLACEMENT message
+            if !self.orders.is_known(&order_id) {
+                let mut order = OrderState::new(&order_id, &asset_id, &side, price, 0.0);
+                order.updated_ms = t_fill_ms;
+                self.orders.upsert(order, false);
+            }
+
+            if let Some((order, true)) = self.orders.apply_fill(&order_id, fill.clone()) {
+                tracing::info!(
+                    "[USER] Fill {} {}: {} {} @ {} ({})",
+                    short_id(&msg.id),
+                    short_id(&order_id),
+                    order.side,
+                    size,
+                    price,
+                    msg.status
+                );
+                let _ = self.event_tx.send(UserEvent::Fill { fill, order });
+            }
+        }
+    }
+
+    pub fn stop(&self) {
+        *self.running.write() = false;
+    }
+}
+
+fn parse_f64(s: &str) -> f64 {
+    s.parse().unwrap_or(0.0)
+}
+
+/// User channel timestamps are unix seconds (occasionally millis) as strings
+fn parse_ts_ms(ts: Option<&str>) -> i64 {
+    match ts.and_then(|t| t.parse::<i64>().ok()) {
+        Some(t) if t < 10_000_000_000 => t * 1000,
+        Some(t) => t,
+        None => chrono::Utc::now().timestamp_millis(),
+    }
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(10)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::orders::OrderStatus;
+
+    const API_KEY: &str = "f4f247b7-4ac7-ff29-a152-04fda0a8755a";
+
+    fn make_service() -> (UserChannelService, broadcast::Receiver<UserEvent>) {
+        let credentials = ClobCredentials {
+            api_key: API_KEY.to_string(),
+            secret: String::new(),
+            passphrase: String::new(),
+            wallet_address: String::new(),
+            private_key: String::new(),
+            signature_type: 0,
+        };
+        let service = UserChannelService::new("wss://test", credentials, Arc::new(OrderStore::new()));
+        let rx = service.subscribe();
+        (service, rx)
+    }
+
+    // Payload shapes as captured from the user channel
+    const PLACEMENT: &str = r#"{"asset_id":"UP","associate_trades":null,"event_type":"order",
+        "id":"0xff354cd7ca7539dfa9c28d90943ab5779a4eac34b9b37a757d7b32bdfb11790b",
+        "market":"0xcond","order_owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a","original_size":"10",
+        "outcome":"Up","owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a","price":"0.57","side":"BUY",
+        "size_matched":"0","timestamp":"1769961600","type":"PLACEMENT"}"#;
+
+    const TAKER_TRADE: &str = r#"{"asset_id":"UP","event_type":"trade","id":"28c4d2eb-bbea-40e7-a9f0-b2fdb56b2c2e",
+        "last_update":"1769961605","maker_orders":[{"asset_id":"UP","matched_amount":"4",
+        "order_id":"0xmaker","outcome":"Up","owner":"9180014b-33c8-9240-a14b-bdca11c0a465","price":"0.57"}],
+        "market":"0xcond","matchtime":"1769961605","outcome":"Up","owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a",
+        "price":"0.57","side":"BUY","size":"4","status":"MATCHED",
+        "taker_order_id":"0xff354cd7ca7539dfa9c28d90943ab5779a4eac34b9b37a757d7b32bdfb11790b",
+        "timestamp":"1769961605","trade_owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a","type":"TRADE"}"#;
+
+    #[test]
+    fn test_placement_then_taker_fill() {
+        let (service, mut rx) = make_service();
+        service.handle_message(PLACEMENT);
+
+        match rx.try_recv().unwrap() {
+            UserEvent::Order { kind, order } => {
+                assert_eq!(kind, "PLACEMENT");
+                assert_eq!(order.status, OrderStatus::Live);
+                assert_eq!(order.original_size, 10.0);
+                assert_eq!(order.outcome, "Up");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        service.handle_message(TAKER_TRADE);
+        match rx.try_recv().unwrap() {
+            UserEvent::Fill { fill, order } => {
+                assert_eq!(fill.size, 4.0);
+                assert_eq!(fill.price, 0.57);
+                assert_eq!(fill.status.as_deref(), Some("MATCHED"));
+                assert_eq!(fill.t_fill_ms, Some(1_769_961_605_000));
+                assert_eq!(order.status, OrderStatus::PartiallyFilled);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        // The other side's maker order is not ours
+        assert!(rx.try_recv().is_err());
+        assert!(!service.orders.is_known("0xmaker"));
+
+        // Replayed MATCHED is ignored, CONFIRMED is reported
+        service.handle_message(TAKER_TRADE);
+        assert!(rx.try_recv().is_err());
+        service.handle_message(&TAKER_TRADE.replace("MATCHED", "CONFIRMED"));
+        assert!(matches!(rx.try_recv().unwrap(), UserEvent::Fill { .. }));
+    }
+
+    #[test]
+    fn test_maker_fill_before_placement() {
+        let (service, mut rx) = make_service();
+        // Our resting order gets hit; the trade arrives before we ever saw the order
+        let msg = r#"[{"asset_id":"DOWN","event_type":"trade","id":"t-2","maker_orders":[
+            {"asset_id":"DOWN","matched_amount":"3","order_id":"0xours","outcome":"Down",
+             "owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a","price":"0.41","side":"BUY"},
+            {"asset_id":"DOWN","matched_amount":"7","order_id":"0xtheirs","outcome":"Down",
+             "owner":"someone-else","price":"0.41"}],
+            "market":"0xcond","matchtime":"1769961610","outcome":"Down","owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a",
+            "price":"0.41","side":"SELL","size":"10","status":"MATCHED","taker_order_id":"0xtaker",
+            "timestamp":"1769961610","trade_owner":"9180014b-33c8-9240-a14b-bdca11c0a465","type":"TRADE"}]"#;
+        service.handle_message(msg);
+
+        match rx.try_recv().unwrap() {
+            UserEvent::Fill { fill, order } => {
+                assert_eq!(fill.order_id.as_deref(), Some("0xours"));
+                assert_eq!(fill.size, 3.0);
+                assert_eq!(order.side, "BUY");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        // The event is addressed to us, but the taker leg isn't ours
+        assert!(rx.try_recv().is_err());
+        assert!(!service.orders.is_known("0xtaker"));
+    }
+
+    #[test]
+    fn test_maker_fill_without_side() {
+        let (service, mut rx) = make_service();
+        let msg = |order_id: &str| {
+            format!(
+                r#"{{"asset_id":"UP","event_type":"trade","id":"t-3","maker_orders":[
+                {{"asset_id":"UP","matched_amount":"2","order_id":"{}",
+                 "owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a","price":"0.57"}}],
+                "market":"0xcond","matchtime":"1769961620","owner":"f4f247b7-4ac7-ff29-a152-04fda0a8755a",
+                "price":"0.57","side":"SELL","size":"2","status":"MATCHED","taker_order_id":"0xtaker",
+                "timestamp":"1769961620","trade_owner":"someone-else","type":"TRADE"}}"#,
+                order_id
+            )
+        };
+
+        // Unknown order and no side: nothing to book it on
+        service.handle_message(&msg("0xunknown"));
+        assert!(rx.try_recv().is_err());
+        assert!(!service.orders.is_known("0xunknown"));
+
+        // A known order supplies its side
+        service.handle_message(PLACEMENT);
+        let _ = rx.try_recv();
+        service.handle_message(&msg("0xff354cd7ca7539dfa9c28d90943ab5779a4eac34b9b37a757d7b32bdfb11790b"));
+        match rx.try_recv().unwrap() {
+            UserEvent::Fill { fill, order } => {
+                assert_eq!((fill.size, fill.liquidity.as_deref()), (2.0, Some("MAKER")));
+                assert_eq!(order.side, "BUY");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let (service, mut rx) = make_service();
+        service.handle_message(PLACEMENT);
+        let _ = rx.try_recv();
+        service.handle_message(&PLACEMENT.replace("PLACEMENT", "CANCELLATION"));
+        match rx.try_recv().unwrap() {
+            UserEvent::Order { kind, order } => {
+                assert_eq!(kind, "CANCELLATION");
+                assert_eq!(order.status, OrderStatus::Cancelled);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        service.handle_message("PONG");
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -18,12 +18,15 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 
 use crate::events::TradeSide;
-use crate::services::{BinanceBookService, ChainlinkService, PolymarketService, SignalService, TradeService};
+use crate::services::{ChainlinkService, OrderStatus, PolymarketService, SignalService, SignalState, SpotBook, TradeService};
 use super::log_buffer::TuiLogBuffer;
 
 pub enum TuiCommand {
     BuyYes,
     BuyNo,
+    SellYes,
+    SellNo,
+    Flatten,
     ToggleKillSwitch,
     IncrementSize,
     DecrementSize,
@@ -35,7 +38,7 @@ pub enum TuiCommand {
 }
 
 pub struct App {
-    binance: Arc<BinanceBookService>,
+    spot: Arc<dyn SpotBook>,
     polymarket: Arc<PolymarketService>,
     chainlink: Arc<ChainlinkService>,
     signal: Arc<SignalService>,
@@ -44,11 +47,12 @@ pub struct App {
     command_rx: mpsc::Receiver<TuiCommand>,
     log_buffer: TuiLogBuffer,
     dry_run: bool,
+    selected_order: usize, // index into the open orders list
 }
 
 impl App {
     pub fn new(
-        binance: Arc<BinanceBookService>,
+        spot: Arc<dyn SpotBook>,
         polymarket: Arc<PolymarketService>,
         chainlink: Arc<ChainlinkService>,
         signal: Arc<SignalService>,
@@ -58,7 +62,7 @@ impl App {
     ) -> Self {
         let (tx, rx) = mpsc::channel(100);
         Self {
-            binance,
+            spot,
             polymarket,
             chainlink,
             signal,
@@ -67,6 +71,7 @@ impl App {
             command_rx: rx,
             log_buffer,
             dry_run,
+            selected_order: 0,
         }
     }
 
@@ -112,8 +117,48 @@ impl App {
                                 tracing::error!("Order error: {:?}", e);
                             }
                         }
+                        KeyCode::Char('Y') => {
+                            if let Err(e) = self.trade.sell_position(TradeSide::Yes).await {
+                                tracing::error!("Sell error: {:?}", e);
+                            }
+                        }
+                        KeyCode::Char('N') => {
+                            if let Err(e) = self.trade.sell_position(TradeSide::No).await {
+                                tracing::error!("Sell error: {:?}", e);
+                            }
+                        }
+                        KeyCode::Char('x') => {
+                            if let Err(e) = self.trade.flatten_position().await {
+                                tracing::error!("Flatten error: {:?}", e);
+                            }
+                        }
                         KeyCode::Char('k') => {
-                            self.trade.toggle_kill_switch();
+                            if let Err(e) = self.trade.toggle_kill_switch().await {
+                                tracing::error!("Kill switch cancel error: {:?}", e);
+                            }
+                        }
+                        KeyCode::Char('a') => {
+                            self.trade.toggle_auto_trade();
+                        }
+                        KeyCode::Up => {
+                            self.selected_order = self.selected_order.saturating_sub(1);
+                        }
+                        KeyCode::Down => {
+                            let open = self.trade.order_store().open_orders().len();
+                            self.selected_order = (self.selected_order + 1).min(open.saturating_sub(1));
+                        }
+                        KeyCode::Char('c') => {
+                            let open = self.trade.order_store().open_orders();
+                            if let Some(order) = open.get(self.selected_order) {
+                                if let Err(e) = self.trade.cancel_order(&order.order_id, "manual").await {
+                                    tracing::error!("Cancel error: {:?}", e);
+                                }
+                            }
+                        }
+                        KeyCode::Char('C') => {
+                            if let Err(e) = self.trade.cancel_all_orders("manual").await {
+                                tracing::error!("Cancel all error: {:?}", e);
+                            }
                         }
                         KeyCode::Char('+') | KeyCode::Char('=') => {
                             self.trade.adjust_size(5.0);
@@ -151,24 +196,26 @@ impl App {
             .margin(1)
             .constraints([
                 Constraint::Length(3),   // Header
-                Constraint::Length(8),   // Binance panel
+                Constraint::Length(8),   // Spot book panel
                 Constraint::Length(7),   // Polymarket panel
                 Constraint::Length(6),   // Signal panel
                 Constraint::Length(6),   // Trading config panel
+                Constraint::Length(6),   // Orders panel
                 Constraint::Min(4),      // Actions log (flexible)
                 Constraint::Min(6),      // Logs console (flexible)
-                Constraint::Length(10),  // Hotkeys help
+                Constraint::Length(12),  // Hotkeys help
             ])
             .split(f.size());
 
         self.render_header(f, chunks[0]);
-        self.render_binance_panel(f, chunks[1]);
+        self.render_spot_panel(f, chunks[1]);
         self.render_polymarket_panel(f, chunks[2]);
         self.render_signal_panel(f, chunks[3]);
         self.render_trading_panel(f, chunks[4]);
-        self.render_actions_panel(f, chunks[5]);
-        self.render_logs_panel(f, chunks[6]);
-        self.render_help_panel(f, chunks[7]);
+        self.render_orders_panel(f, chunks[5]);
+        self.render_actions_panel(f, chunks[6]);
+        self.render_logs_panel(f, chunks[7]);
+        self.render_help_panel(f, chunks[8]);
     }
 
     fn render_header(&self, f: &mut Frame, area: Rect) {
@@ -179,21 +226,32 @@ impl App {
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
         };
 
-        let header = Paragraph::new(Line::from(vec![
+        let mut spans = vec![
             Span::raw("Polymarket Monitor | Mode: "),
             Span::styled(mode, mode_style),
-        ]))
-        .block(Block::default().borders(Borders::ALL).title("Status"));
+        ];
+        // Orders can go out without a keypress: make that impossible to miss
+        let mut block = Block::default().borders(Borders::ALL).title("Status");
+        if self.trade.get_state().auto_trade_active {
+            spans.push(Span::raw(" | "));
+            spans.push(Span::styled(
+                " AUTO TRADING ON ('a' to stop) ",
+                Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+            block = block.border_style(Style::default().fg(Color::Red));
+        }
+
+        let header = Paragraph::new(Line::from(spans)).block(block);
 
         f.render_widget(header, area);
     }
 
-    fn render_binance_panel(&self, f: &mut Frame, area: Rect) {
-        let update = self.binance.get_current_update();
-        let ret_1s = self.binance.get_returns(1000);
-        let ret_3s = self.binance.get_returns(3000);
-        let ret_10s = self.binance.get_returns(10000);
-        let std_5m = self.binance.get_std_dev(300_000);
+    fn render_spot_panel(&self, f: &mut Frame, area: Rect) {
+        let update = self.spot.get_current_update();
+        let ret_1s = self.spot.get_returns(1000);
+        let ret_3s = self.spot.get_returns(3000);
+        let ret_10s = self.spot.get_returns(10000);
+        let std_5m = self.spot.get_std_dev(300_000);
 
         let content = if let Some(u) = update {
             let mid: f64 = u.mid.to_string().parse().unwrap_or(0.0);
@@ -215,11 +273,11 @@ impl App {
                 std_5m.unwrap_or(0.0)
             )
         } else {
-            "Connecting to Binance...".to_string()
+            format!("Connecting to {}...", self.spot.venue())
         };
 
         let panel = Paragraph::new(content)
-            .block(Block::default().borders(Borders::ALL).title("Binance BTCUSDT Perp"));
+            .block(Block::default().borders(Borders::ALL).title(format!("BTC spot ({})", self.spot.venue())));
 
         f.render_widget(panel, area);
     }
@@ -336,6 +394,8 @@ impl App {
                 Span::styled(side_str, Style::default().fg(side_color).add_modifier(Modifier::BOLD)),
                 Span::raw(format!(" (confidence: {:.2})", signal.confidence)),
             ]),
+            self.fair_value_line(&signal),
+            self.strategies_line(),
             Line::from(format!("Reasons: {}", reasons)),
         ];
 
@@ -345,6 +405,48 @@ impl App {
         f.render_widget(panel, area);
     }
 
+    /// Model probability of Up and the after-fee edge of buying each side at its ask
+    fn fair_value_line(&self, signal: &SignalState) -> Line<'static> {
+        let Some(fair_up) = signal.fair_up else {
+            return Line::from("Fair Up: -- (needs target, volatility and time left)");
+        };
+        let edge_span = |edge: Option<f64>| match edge {
+            Some(e) => Span::styled(
+                format!("{:+.3}", e),
+                Style::default().fg(if e > 0.0 { Color::Green } else { Color::Red }),
+            ),
+            None => Span::raw("--"),
+        };
+        Line::from(vec![
+            Span::raw(format!("Fair Up: {:.3} | Edge YES: ", fair_up)),
+            edge_span(signal.edge_yes),
+            Span::raw(" | Edge NO: "),
+            edge_span(signal.edge_no),
+        ])
+    }
+
+    /// Latest intent of each configured strategy and how long ago it fired
+    fn strategies_line(&self) -> Line<'static> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let mut spans = vec![Span::raw("Strategies:")];
+        for (name, last) in self.signal.strategy_signals() {
+            spans.push(Span::raw(format!(" {} ", name)));
+            match last {
+                Some(event) => {
+                    let color = if event.suggested_side == "YES" { Color::Green } else { Color::Red };
+                    spans.push(Span::styled(event.suggested_side, Style::default().fg(color)));
+                    spans.push(Span::raw(format!(
+                        " {:.2} {}s ago |",
+                        event.confidence,
+                        (now_ms - event.t_recv_ms) / 1000
+                    )));
+                }
+                None => spans.push(Span::raw("-- |")),
+            }
+        }
+        Line::from(spans)
+    }
+
     fn render_trading_panel(&self, f: &mut Frame, area: Rect) {
         let state = self.trade.get_state();
 
@@ -356,11 +458,17 @@ impl App {
 
         let content = vec![
             Line::from(vec![Span::raw("Kill Switch: "), kill_switch]),
-            Line::from(format!("Size: {:.1}", state.current_size)),
             Line::from(format!(
-                "Max Price YES: {:.2} | Max Price NO: {:.2}",
-                state.max_price_yes, state.max_price_no
+                "Size: {:.1} | Taker fee: {} bps ({:.2}¢/share at 50¢)",
+                state.current_size,
+                self.trade.fee_model(TradeSide::Yes).rate_bps,
+                self.trade.fee_model(TradeSide::Yes).fee_per_share(0.5) * 100.0
             )),
+            Line::from(format!(
+                "Max Price YES: {:.2} | Max Price NO: {:.2} | Min Sell YES: {:.2} | Min Sell NO: {:.2}",
+                state.max_price_yes, state.max_price_no, state.min_price_yes, state.min_price_no
+            )),
+            self.positions_line(),
         ];
 
         let panel = Paragraph::new(content)
@@ -369,6 +477,100 @@ impl App {
         f.render_widget(panel, area);
     }
 
+    /// Holdings in the current window, marked at the bid, plus lifetime realized PnL
+    fn positions_line(&self) -> Line<'static> {
+        let positions = self.trade.get_window_positions();
+        let holding = |side: &str| {
+            positions
+                .iter()
+                .find(|(p, _)| p.side == side && p.size > 0.0)
+                .map(|(p, _)| format!("{:.0} @ {:.3}", p.size, p.avg_cost))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let upnl: f64 = positions.iter().filter_map(|(_, u)| *u).sum();
+        let rpnl = self.trade.total_realized_pnl();
+        let pnl_style = |v: f64| {
+            if v > 0.0 {
+                Style::default().fg(Color::Green)
+            } else if v < 0.0 {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            }
+        };
+
+        Line::from(vec![
+            Span::raw(format!("Position YES: {} | NO: {} | uPnL: ", holding("YES"), holding("NO"))),
+            Span::styled(format!("{:+.2}", upnl), pnl_style(upnl)),
+            Span::raw(" | rPnL: "),
+            Span::styled(format!("{:+.2}", rpnl), pnl_style(rpnl)),
+            Span::raw(format!(" | fees: {:.2}", self.trade.total_fees_paid())),
+        ])
+    }
+
+    fn render_orders_panel(&self, f: &mut Frame, area: Rect) {
+        let active_market = self.polymarket.get_active_market();
+        let visible_height = area.height.saturating_sub(2) as usize; // subtract border
+        let open = self.trade.order_store().open_orders();
+        let selected = self.selected_order.min(open.len().saturating_sub(1));
+
+        // Open orders first (selectable), then recently closed ones to fill the panel
+        let closed: Vec<_> = self
+            .trade
+            .order_store()
+            .recent(visible_height)
+            .into_iter()
+            .filter(|o| !o.status.is_open())
+            .collect();
+
+        let items: Vec<ListItem> = open
+            .iter()
+            .chain(closed.iter())
+            .take(visible_height.max(1))
+            .enumerate()
+            .map(|(i, o)| {
+                let outcome = if o.asset_id == active_market.up_token_id {
+                    "YES".to_string()
+                } else if o.asset_id == active_market.down_token_id {
+                    "NO".to_string()
+                } else {
+                    o.outcome.to_uppercase()
+                };
+                let updated = chrono::DateTime::from_timestamp_millis(o.updated_ms)
+                    .unwrap_or_else(chrono::Utc::now);
+                let marker = if i < open.len() && i == selected { ">" } else { " " };
+                let line = format!(
+                    "{} {} | {} {} @ {:.2} | {:.0}/{:.0} filled | {} | {}",
+                    marker,
+                    updated.format("%H:%M:%S"),
+                    o.side,
+                    outcome,
+                    o.price,
+                    o.size_matched,
+                    o.original_size,
+                    o.status,
+                    &o.order_id[..o.order_id.len().min(12)],
+                );
+                let mut style = match o.status {
+                    OrderStatus::Live => Style::default().fg(Color::White),
+                    OrderStatus::PartiallyFilled => Style::default().fg(Color::Cyan),
+                    OrderStatus::Filled => Style::default().fg(Color::Green),
+                    OrderStatus::Cancelled => Style::default().fg(Color::DarkGray),
+                };
+                if marker == ">" {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                ListItem::new(line).style(style)
+            })
+            .collect();
+
+        let title = format!("Orders ({} open) - ↑/↓ select, c cancel, C cancel all", open.len());
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title));
+
+        f.render_widget(list, area);
+    }
+
     fn render_actions_panel(&self, f: &mut Frame, area: Rect) {
         let entries = self.trade.get_action_log();
         let items: Vec<ListItem> = entries
@@ -379,6 +581,12 @@ impl App {
                     Style::default().fg(Color::Green)
                 } else if line.contains("Buy NO") {
                     Style::default().fg(Color::Red)
+                } else if line.contains("Sell ") || line.contains("Flatten") {
+                    Style::default().fg(Color::Magenta)
+                } else if line.contains("Cancel") {
+                    Style::default().fg(Color::Yellow)
+                } else if line.contains("Fill ") {
+                    Style::default().fg(Color::LightGreen)
                 } else if line.contains("Kill switch") {
                     Style::default().fg(Color::Yellow)
                 } else if line.contains("Size") || line.contains("Max ") {
@@ -436,6 +644,22 @@ impl App {
                 Span::styled("k", Style::default().fg(Color::Yellow)),
                 Span::raw(" Toggle Kill Switch"),
             ]),
+            Line::from(vec![
+                Span::styled("  Y", Style::default().fg(Color::Green)),
+                Span::raw(" Sell YES   "),
+                Span::styled("N", Style::default().fg(Color::Red)),
+                Span::raw(" Sell NO   "),
+                Span::styled("x", Style::default().fg(Color::Magenta)),
+                Span::raw(" Flatten window (sell all at bid)"),
+            ]),
+            Line::from(vec![
+                Span::styled("  ↑/↓", Style::default().fg(Color::Cyan)),
+                Span::raw(" Select order   "),
+                Span::styled("c", Style::default().fg(Color::Yellow)),
+                Span::raw(" Cancel selected   "),
+                Span::styled("C", Style::default().fg(Color::Yellow)),
+                Span::raw(" Cancel all"),
+            ]),
             Line::from(vec![
                 Span::styled("Size/Price:", Style::default().add_modifier(Modifier::BOLD)),
             ]),
@@ -456,7 +680,9 @@ impl App {
                 Span::styled("  q", Style::default().fg(Color::Magenta)),
                 Span::raw(" Quit    "),
                 Span::styled("Ctrl+C", Style::default().fg(Color::Magenta)),
-                Span::raw(" Force exit"),
+                Span::raw(" Force exit    "),
+                Span::styled("a", Style::default().fg(Color::Red)),
+                Span::raw(" Toggle auto trading"),
             ]),
         ];
 